@@ -0,0 +1,49 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DesktopNotificationConfig {
+    #[serde(default = "DesktopNotificationConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub icon: PathBuf,
+
+    /// Duration of notifications in milliseconds
+    #[serde(default = "DesktopNotificationConfig::default_timeout")]
+    pub timeout: u64,
+
+    /// Longer text is truncated, unlimited if it is zero
+    #[serde(default = "DesktopNotificationConfig::default_long_plaintext_length")]
+    pub long_plaintext_length: usize,
+}
+
+impl DesktopNotificationConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+
+    #[inline]
+    pub const fn default_timeout() -> u64 { 5000 }
+
+    #[inline]
+    pub const fn default_long_plaintext_length() -> usize { 80 }
+}
+
+impl Default for DesktopNotificationConfig {
+    fn default() -> Self {
+        Self {
+            enable: Self::default_enable(),
+            icon: PathBuf::new(),
+            timeout: Self::default_timeout(),
+            long_plaintext_length: Self::default_long_plaintext_length(),
+        }
+    }
+}
+
+impl From<DesktopNotificationConfig> for caracal_server::config::DesktopNotificationConfig {
+    fn from(config: DesktopNotificationConfig) -> Self {
+        let DesktopNotificationConfig { enable, icon, timeout, long_plaintext_length } = config;
+        Self { enable, icon, timeout: Duration::from_millis(timeout), long_plaintext_length }
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use caracal_base::model;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HooksConfig {
+    /// Shell commands to run after every task is completed or failed
+    #[serde(default)]
+    pub commands: Vec<String>,
+
+    /// URLs to which the status of every completed or failed task is posted
+    #[serde(default, with = "caracal_base::serde::uris")]
+    pub webhooks: Vec<http::Uri>,
+
+    /// Commands and webhooks which run longer than this are aborted, in
+    /// seconds
+    #[serde(default = "HooksConfig::default_timeout")]
+    pub timeout: u64,
+
+    /// Run the commands in the hooks of tasks added by clients, they run as
+    /// the user of the daemon
+    #[serde(default)]
+    pub allow_task_commands: bool,
+}
+
+impl HooksConfig {
+    #[inline]
+    pub const fn default_timeout() -> u64 { 30 }
+
+    #[inline]
+    pub const fn timeout(&self) -> Duration { Duration::from_secs(self.timeout) }
+
+    pub fn task_hooks(&self) -> model::TaskHooks {
+        model::TaskHooks { commands: self.commands.clone(), webhooks: self.webhooks.clone() }
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            webhooks: Vec::new(),
+            timeout: Self::default_timeout(),
+            allow_task_commands: false,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 mod dbus;
+mod desktop_notification;
 mod error;
 mod grpc;
+mod hooks;
 mod mertrics;
 mod task_scheduler;
 mod web;
@@ -11,14 +13,15 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use caracal_base::profile::{minio::MinioAlias, ssh::SshConfig};
+use caracal_base::profile::{minio::MinioAlias, s3::S3Profile, ssh::SshConfig};
 use caracal_cli::profile::{self, Profile, ProfileItem};
 use resolve_path::PathResolveExt as _;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 
 pub use self::{
-    dbus::DBusConfig, error::Error, grpc::GrpcConfig, mertrics::MetricsConfig,
+    dbus::DBusConfig, desktop_notification::DesktopNotificationConfig, error::Error,
+    grpc::GrpcConfig, hooks::HooksConfig, mertrics::MetricsConfig,
     task_scheduler::TaskSchedulerConfig, web::WebConfig,
 };
 
@@ -33,6 +36,9 @@ pub struct Config {
     #[serde(default)]
     pub downloader: caracal_cli::config::DownloaderConfig,
 
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
     #[serde(default)]
     pub profile_files: Vec<PathBuf>,
 
@@ -42,6 +48,9 @@ pub struct Config {
     #[serde(default)]
     pub dbus: DBusConfig,
 
+    #[serde(default)]
+    pub desktop_notification: DesktopNotificationConfig,
+
     #[serde(default)]
     pub web: WebConfig,
 
@@ -96,8 +105,10 @@ impl Config {
             .collect()
     }
 
+    #[allow(clippy::too_many_lines)]
     pub async fn into_server_config(self) -> Result<caracal_server::Config, Error> {
         let mut minio_aliases = HashMap::new();
+        let mut s3_profiles = HashMap::new();
         let mut ssh_servers = HashMap::new();
         for profile_file in self.profile_files() {
             for profile_item in Profile::load(profile_file).await?.profiles {
@@ -119,6 +130,27 @@ impl Config {
                         let alias = MinioAlias { endpoint_url, access_key, secret_key };
                         drop(minio_aliases.insert(name, alias));
                     }
+                    ProfileItem::S3(profile::S3 {
+                        name,
+                        region,
+                        endpoint_url,
+                        force_path_style,
+                        access_key,
+                        secret_key,
+                        session_token,
+                        anonymous,
+                    }) => {
+                        let profile = S3Profile {
+                            region,
+                            endpoint_url,
+                            force_path_style,
+                            access_key,
+                            secret_key,
+                            session_token,
+                            anonymous,
+                        };
+                        drop(s3_profiles.insert(name, profile));
+                    }
                 }
             }
         }
@@ -136,14 +168,35 @@ impl Config {
         };
 
         let dbus = caracal_server::config::DBusConfig::from(self.dbus);
+        let desktop_notification =
+            caracal_server::config::DesktopNotificationConfig::from(self.desktop_notification);
         let metrics = caracal_server::config::MetricsConfig::from(self.metrics);
         let web = caracal_server::config::WebConfig::from(self.web);
         let task_scheduler = caracal_server::config::TaskSchedulerConfig {
             http: caracal_server::config::HttpConfig {
                 user_agent: self.downloader.http.user_agent,
                 concurrent_connections: self.downloader.http.concurrent_connections,
+                proxy: self.downloader.http.proxy,
             },
             concurrent_number: self.task_scheduler.concurrent_number,
+            task_store_file_path: self.task_scheduler.task_store_file_path(),
+            global_speed_limit: self.task_scheduler.speed_limit,
+            preemption: self.task_scheduler.preemption,
+            schedule: caracal_base::model::DownloadSchedule::new(self.task_scheduler.windows),
+            hooks: self.hooks.task_hooks(),
+            hook_timeout: self.hooks.timeout(),
+            allow_task_commands: self.hooks.allow_task_commands,
+            torrent_seed_ratio: self.downloader.torrent.seed_ratio,
+            verify_on_resume: self.downloader.verify_on_resume,
+            connection_tuning: self.downloader.connection_tuning.enable.then(|| {
+                caracal_engine::ConnectionTuning {
+                    min_connections: u64::from(self.downloader.connection_tuning.min_connections),
+                    max_connections: u64::from(self.downloader.connection_tuning.max_connections),
+                    interval: self.downloader.connection_tuning.interval(),
+                }
+            }),
+            retry_interval: self.task_scheduler.retry.retry_interval(),
+            chunk_retry_interval: self.downloader.retry.retry_interval(),
             default_output_directory: self
                 .downloader
                 .default_output_directory
@@ -154,10 +207,12 @@ impl Config {
             task_scheduler,
             ssh_servers,
             minio_aliases,
+            s3_profiles,
             grpc_listen_address,
             grpc_local_socket,
             grpc_access_token,
             dbus,
+            desktop_notification,
             web,
             metrics,
         })
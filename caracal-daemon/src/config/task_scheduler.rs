@@ -1,15 +1,59 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct TaskSchedulerConfig {
     #[serde(default = "TaskSchedulerConfig::default_concurrent_number")]
     pub concurrent_number: usize,
+
+    #[serde(default = "TaskSchedulerConfig::default_enable_persistence")]
+    pub enable_persistence: bool,
+
+    #[serde(default = "TaskSchedulerConfig::default_persistence_file_path")]
+    pub persistence_file_path: PathBuf,
+
+    #[serde(default)]
+    pub speed_limit: Option<u64>,
+
+    /// Pause a downloading task of lower priority to start a task of higher
+    /// priority once the concurrent number is reached
+    #[serde(default)]
+    pub preemption: bool,
+
+    /// Tasks are only downloaded within these windows, downloading is allowed
+    /// at any time if it is empty
+    #[serde(default)]
+    pub windows: Vec<caracal_base::model::DownloadWindow>,
+
+    #[serde(default)]
+    pub retry: caracal_cli::config::RetryConfig,
 }
 
 impl Default for TaskSchedulerConfig {
-    fn default() -> Self { Self { concurrent_number: Self::default_concurrent_number() } }
+    fn default() -> Self {
+        Self {
+            concurrent_number: Self::default_concurrent_number(),
+            enable_persistence: Self::default_enable_persistence(),
+            persistence_file_path: Self::default_persistence_file_path(),
+            speed_limit: None,
+            preemption: false,
+            windows: Vec::new(),
+            retry: caracal_cli::config::RetryConfig::default(),
+        }
+    }
 }
 
 impl TaskSchedulerConfig {
     pub const fn default_concurrent_number() -> usize { 10 }
+
+    pub const fn default_enable_persistence() -> bool { true }
+
+    pub fn default_persistence_file_path() -> PathBuf {
+        caracal_base::PROJECT_DATA_DIR.join(caracal_base::TASK_STORE_FILE_NAME)
+    }
+
+    pub fn task_store_file_path(&self) -> Option<PathBuf> {
+        self.enable_persistence.then(|| self.persistence_file_path.clone())
+    }
 }
@@ -7,11 +7,15 @@ pub enum Action {
     GetAllTaskStatuses,
     SelectTask { task_id: u64 },
     // TODO: use it
-    AddTask(model::CreateTask),
+    AddTask(Box<model::CreateTask>),
     RemoveTask { task_id: u64 },
     PauseTask { task_id: u64 },
     ResumeTask { task_id: u64 },
     IncreaseConcurrentNumber { task_id: u64 },
     DecreaseConcurrentNumber { task_id: u64 },
+    SetPriority { task_id: u64, priority: model::Priority },
+    RemoveGroup { group_id: u64 },
+    PauseGroup { group_id: u64 },
+    ResumeGroup { group_id: u64 },
     Shutdown,
 }
@@ -5,6 +5,8 @@ use caracal_base::model;
 pub struct State {
     task_statuses: Vec<model::TaskStatus>,
 
+    group_statuses: Vec<model::TaskGroupStatus>,
+
     server_endpoint: http::Uri,
 
     access_token: Option<String>,
@@ -33,6 +35,12 @@ impl State {
         self.task_statuses = task_statuses;
     }
 
+    pub const fn group_statuses(&self) -> &Vec<model::TaskGroupStatus> { &self.group_statuses }
+
+    pub fn set_group_statuses(&mut self, group_statuses: Vec<model::TaskGroupStatus>) {
+        self.group_statuses = group_statuses;
+    }
+
     pub const fn daemon_version(&self) -> Option<&semver::Version> { self.daemon_version.as_ref() }
 
     pub fn set_daemon_version(&mut self, daemon_version: semver::Version) {
@@ -27,7 +27,7 @@ impl StateStore {
         (Self { state_tx, server_endpoint, access_token }, state_rx)
     }
 
-    #[allow(clippy::cognitive_complexity)]
+    #[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
     pub async fn serve(
         self,
         mut action_rx: mpsc::UnboundedReceiver<Action>,
@@ -70,6 +70,9 @@ impl StateStore {
                             task_statuses.sort_unstable_by_key(|status| status.id);
                             state.set_task_statuses(task_statuses);
                         }
+                        if let Ok(group_statuses) = client.get_all_group_statuses().await {
+                            state.set_group_statuses(group_statuses);
+                        }
                         if let Ok(version) = client.get_version().await {
                             state.set_daemon_version(version);
                         } else {
@@ -121,6 +124,34 @@ impl StateStore {
                         tracing::warn!("{err}");
                     }
                 }
+                Action::SetPriority { task_id, priority } => {
+                    if let Some(ref client) = client
+                        && let Err(err) = client.set_priority(task_id, priority).await
+                    {
+                        tracing::warn!("{err}");
+                    }
+                }
+                Action::RemoveGroup { group_id } => {
+                    if let Some(ref client) = client
+                        && let Err(err) = client.remove_group(group_id).await
+                    {
+                        tracing::warn!("{err}");
+                    }
+                }
+                Action::PauseGroup { group_id } => {
+                    if let Some(ref client) = client
+                        && let Err(err) = client.pause_group(group_id).await
+                    {
+                        tracing::warn!("{err}");
+                    }
+                }
+                Action::ResumeGroup { group_id } => {
+                    if let Some(ref client) = client
+                        && let Err(err) = client.resume_group(group_id).await
+                    {
+                        tracing::warn!("{err}");
+                    }
+                }
                 _ => continue,
             }
             self.state_tx.send(state.clone()).ok().context(error::StateReceiverClosedSnafu)?;
@@ -0,0 +1,195 @@
+use caracal_base::model;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    Frame,
+    prelude::*,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::tui::{
+    state_store::{Action, State},
+    ui::components::{Component, ComponentRender},
+};
+
+struct Props {
+    group_statuses: Vec<model::TaskGroupStatus>,
+}
+
+impl From<&State> for Props {
+    fn from(state: &State) -> Self { Self { group_statuses: state.group_statuses().clone() } }
+}
+
+pub struct GroupStatusList {
+    /// Sending actions to the state store
+    action_tx: UnboundedSender<Action>,
+    /// State Mapped `TaskGroupStatus` Props
+    props: Props,
+    /// Table with optional selection and current offset
+    pub table_state: TableState,
+}
+
+impl GroupStatusList {
+    fn next(&mut self) {
+        if self.group_statuses().is_empty() {
+            self.table_state.select(None);
+            return;
+        }
+
+        let i = self
+            .table_state
+            .selected()
+            .map_or(0, |i| if i >= self.group_statuses().len() - 1 { 0 } else { i + 1 });
+        self.table_state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        if self.group_statuses().is_empty() {
+            self.table_state.select(None);
+            return;
+        }
+
+        let i = self
+            .table_state
+            .selected()
+            .map_or(0, |i| if i == 0 { self.group_statuses().len() - 1 } else { i - 1 });
+        self.table_state.select(Some(i));
+    }
+
+    pub(super) const fn group_statuses(&self) -> &Vec<model::TaskGroupStatus> {
+        &self.props.group_statuses
+    }
+
+    pub fn get_selected_group_status(&self) -> Option<&model::TaskGroupStatus> {
+        self.table_state.selected().and_then(|selected_idx| self.group_statuses().get(selected_idx))
+    }
+}
+
+impl Component for GroupStatusList {
+    fn new(state: &State, action_tx: UnboundedSender<Action>) -> Self {
+        Self { action_tx, props: Props::from(state), table_state: TableState::default() }
+    }
+
+    fn move_with_state(self, state: &State) -> Self
+    where
+        Self: Sized,
+    {
+        Self { props: Props::from(state), ..self }
+    }
+
+    fn name(&self) -> &'static str { "Group Status List" }
+
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.previous();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.next();
+            }
+            // Force refresh
+            KeyCode::Char('R') => {
+                let _unused = self.action_tx.send(Action::GetAllTaskStatuses);
+            }
+            // Delete group
+            KeyCode::Delete | KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                if let Some(group_status) = self.get_selected_group_status() {
+                    let _unused =
+                        self.action_tx.send(Action::RemoveGroup { group_id: group_status.id });
+                }
+            }
+            // Pause group
+            KeyCode::Char('p') => {
+                if let Some(group_status) = self.get_selected_group_status() {
+                    let _unused =
+                        self.action_tx.send(Action::PauseGroup { group_id: group_status.id });
+                }
+            }
+            // Resume group
+            KeyCode::Char('r') => {
+                if let Some(group_status) = self.get_selected_group_status() {
+                    let _unused =
+                        self.action_tx.send(Action::ResumeGroup { group_id: group_status.id });
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+pub struct RenderProps {
+    pub area: Rect,
+
+    pub is_focused: bool,
+}
+
+impl ComponentRender<RenderProps> for GroupStatusList {
+    // SAFETY: the precision loss is acceptable
+    #[allow(clippy::cast_precision_loss)]
+    fn render(&self, frame: &mut Frame<'_>, props: RenderProps) {
+        let rects = Layout::default().constraints([Constraint::Percentage(100)]).split(props.area);
+
+        let selected_style = if props.is_focused {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let normal_style = Style::default();
+        let header_cells = ["ID", "NAME", "STATE", "TASKS", "FILE SIZE", "REMAIN", "PROGRESS"]
+            .iter()
+            .map(|&h| Cell::from(h).style(Style::default()));
+        let header = Row::new(header_cells).style(normal_style).height(1);
+        let rows = self.group_statuses().iter().map(|status| {
+            let received_bytes = status.received_bytes();
+            let total_bytes = status.content_length().max(received_bytes);
+            let progress_percentage = if total_bytes == 0 {
+                String::from("0.00%")
+            } else {
+                format!("{:.2}%", (received_bytes as f64 / total_bytes as f64) * 100.0)
+            };
+
+            let cells = vec![
+                Cell::from(status.id.to_string()),
+                Cell::from(status.name.clone()),
+                Cell::from(status.state.to_string()),
+                Cell::from(status.tasks.len().to_string()),
+                Cell::new(humansize::format_size(total_bytes, humansize::BINARY)),
+                Cell::new(humansize::format_size(
+                    total_bytes.saturating_sub(received_bytes),
+                    humansize::BINARY,
+                )),
+                Cell::new(progress_percentage),
+            ];
+            Row::new(cells).height(1)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Max(10),
+                Constraint::Min(50),
+                Constraint::Min(20),
+                Constraint::Min(10),
+                Constraint::Min(15),
+                Constraint::Min(15),
+                Constraint::Min(10),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Groups")
+                .title_alignment(Alignment::Center),
+        )
+        .row_highlight_style(selected_style);
+        let mut app_group_status_list_state = self.table_state;
+        frame.render_stateful_widget(table, rects[0], &mut app_group_status_list_state);
+    }
+}
@@ -100,11 +100,14 @@ impl ComponentRender<RenderProps> for InformationArea {
 
         let keybind_keys = Paragraph::new(Text::from(vec![
             Line::from("<R>"),
+            Line::from("<tab>"),
             Line::from("<p>"),
             Line::from("<r>"),
             Line::from("<ctrl-d>"),
             Line::from("<+>"),
             Line::from("<->"),
+            Line::from("<>>"),
+            Line::from("<<>"),
             Line::from("<q>"),
         ]))
         .cyan();
@@ -112,11 +115,14 @@ impl ComponentRender<RenderProps> for InformationArea {
 
         let keybind_values = Paragraph::new(Text::from(vec![
             Line::from("Refresh"),
-            Line::from("Pause task"),
-            Line::from("Resume task"),
-            Line::from("Delete task"),
+            Line::from("Switch between tasks and groups"),
+            Line::from("Pause task or group"),
+            Line::from("Resume task or group"),
+            Line::from("Delete task or group"),
             Line::from("Increase concurrent number"),
             Line::from("Decrease concurrent number"),
+            Line::from("Raise priority"),
+            Line::from("Lower priority"),
             Line::from("Quit"),
         ]));
         frame.render_widget(keybind_values, keybind_values_container);
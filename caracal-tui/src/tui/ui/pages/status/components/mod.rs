@@ -1,2 +1,3 @@
+pub mod group_status;
 pub mod information;
 pub mod task_status;
@@ -136,6 +136,22 @@ impl Component for TaskStatusList {
                         .send(Action::IncreaseConcurrentNumber { task_id: task_status.id });
                 }
             }
+            KeyCode::Char('>') => {
+                if let Some(task_status) = self.get_selected_task_status() {
+                    let _unused = self.action_tx.send(Action::SetPriority {
+                        task_id: task_status.id,
+                        priority: task_status.priority.raise(),
+                    });
+                }
+            }
+            KeyCode::Char('<') => {
+                if let Some(task_status) = self.get_selected_task_status() {
+                    let _unused = self.action_tx.send(Action::SetPriority {
+                        task_id: task_status.id,
+                        priority: task_status.priority.lower(),
+                    });
+                }
+            }
             KeyCode::Char('-') => {
                 if let Some(task_status) = self.get_selected_task_status() {
                     let _unused = self
@@ -150,6 +166,8 @@ impl Component for TaskStatusList {
 
 pub struct RenderProps {
     pub area: Rect,
+
+    pub is_focused: bool,
 }
 
 impl ComponentRender<RenderProps> for TaskStatusList {
@@ -158,7 +176,11 @@ impl ComponentRender<RenderProps> for TaskStatusList {
     fn render(&self, frame: &mut Frame<'_>, props: RenderProps) {
         let rects = Layout::default().constraints([Constraint::Percentage(100)]).split(props.area);
 
-        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+        let selected_style = if props.is_focused {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
         let normal_style = Style::default();
         let header_cells = [
             "ID",
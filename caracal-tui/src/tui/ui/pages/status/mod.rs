@@ -8,19 +8,30 @@ use ratatui::{
 use tokio::sync::mpsc;
 
 use self::components::{
-    information, information::InformationArea, task_status, task_status::TaskStatusList,
+    group_status, group_status::GroupStatusList, information, information::InformationArea,
+    task_status, task_status::TaskStatusList,
 };
 use crate::tui::{
     state_store::{Action, State},
     ui::components::{Component, ComponentRender},
 };
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Focus {
+    Tasks,
+    Groups,
+}
+
 pub struct StatusPage {
     action_tx: mpsc::UnboundedSender<Action>,
 
     information_area: InformationArea,
 
+    group_status_list: GroupStatusList,
+
     task_status_list: TaskStatusList,
+
+    focus: Focus,
 }
 
 impl Component for StatusPage {
@@ -31,7 +42,9 @@ impl Component for StatusPage {
         Self {
             action_tx: action_tx.clone(),
             information_area: InformationArea::new(state, action_tx.clone()),
+            group_status_list: GroupStatusList::new(state, action_tx.clone()),
             task_status_list: TaskStatusList::new(state, action_tx),
+            focus: Focus::Tasks,
         }
         .move_with_state(state)
     }
@@ -43,6 +56,7 @@ impl Component for StatusPage {
         Self {
             // propagate the update to the child components
             information_area: self.information_area.move_with_state(state),
+            group_status_list: self.group_status_list.move_with_state(state),
             task_status_list: self.task_status_list.move_with_state(state),
             ..self
         }
@@ -66,25 +80,46 @@ impl Component for StatusPage {
                 // TODO:
                 // show popup for creating new task
             }
-            _ => {
-                self.task_status_list.handle_key_event(key);
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Tasks => Focus::Groups,
+                    Focus::Groups => Focus::Tasks,
+                };
             }
+            _ => match self.focus {
+                Focus::Tasks => self.task_status_list.handle_key_event(key),
+                Focus::Groups => self.group_status_list.handle_key_event(key),
+            },
         }
     }
 }
 
 impl ComponentRender<()> for StatusPage {
     fn render(&self, frame: &mut Frame<'_>, _props: ()) {
-        let [information_area, task_status_list_area] = *Layout::default()
+        let [information_area, group_status_list_area, task_status_list_area] = *Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Max(20), Constraint::Percentage(80)].as_ref())
+            .constraints(
+                [Constraint::Max(20), Constraint::Max(10), Constraint::Percentage(80)].as_ref(),
+            )
             .split(frame.area())
         else {
-            panic!("The main layout should have 2 chunks")
+            panic!("The main layout should have 3 chunks")
         };
 
         self.information_area.render(frame, information::RenderProps { area: information_area });
-        self.task_status_list
-            .render(frame, task_status::RenderProps { area: task_status_list_area });
+        self.group_status_list.render(
+            frame,
+            group_status::RenderProps {
+                area: group_status_list_area,
+                is_focused: self.focus == Focus::Groups,
+            },
+        );
+        self.task_status_list.render(
+            frame,
+            task_status::RenderProps {
+                area: task_status_list_area,
+                is_focused: self.focus == Focus::Tasks,
+            },
+        );
     }
 }
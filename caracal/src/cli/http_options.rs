@@ -0,0 +1,158 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use caracal_base::model;
+use snafu::ResultExt;
+
+use crate::{error, error::Error};
+
+/// Headers, cookies, credential and proxy of the HTTP requests of new tasks
+#[derive(Clone, Debug, Default)]
+pub struct HttpOptions {
+    headers: Vec<model::HttpHeader>,
+
+    cookies: Vec<Cookie>,
+
+    basic_auth: Option<model::BasicAuth>,
+
+    proxy: Option<model::Proxy>,
+}
+
+impl HttpOptions {
+    /// # Errors
+    pub async fn load(
+        headers: Vec<model::HttpHeader>,
+        cookie_file: Option<PathBuf>,
+        basic_auth: Option<model::BasicAuth>,
+        proxy: Option<model::Proxy>,
+    ) -> Result<Self, Error> {
+        let cookies = if let Some(file_path) = cookie_file {
+            let content = tokio::fs::read_to_string(&file_path)
+                .await
+                .with_context(|_| error::ReadCookieFileSnafu { file_path })?;
+            Cookie::parse_netscape(&content)
+        } else {
+            Vec::new()
+        };
+        Ok(Self { headers, cookies, basic_auth, proxy })
+    }
+
+    /// Headers of requests to `uri`, the cookies which match `uri` are joined
+    /// into a `Cookie` header
+    pub fn headers(&self, uri: &http::Uri) -> Vec<model::HttpHeader> {
+        let mut headers = self.headers.clone();
+        if let Some(cookie) = self.cookie(uri) {
+            headers.push(model::HttpHeader::new("Cookie", cookie));
+        }
+        headers
+    }
+
+    /// Value of the `Cookie` header of requests to `uri`
+    pub fn cookie(&self, uri: &http::Uri) -> Option<String> {
+        let cookies = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(uri))
+            .map(|Cookie { name, value, .. }| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        (!cookies.is_empty()).then(|| cookies.join("; "))
+    }
+
+    pub fn headers_without_cookies(&self) -> Vec<model::HttpHeader> { self.headers.clone() }
+
+    pub fn basic_auth(&self) -> Option<model::BasicAuth> { self.basic_auth.clone() }
+
+    pub fn proxy(&self) -> Option<model::Proxy> { self.proxy.clone() }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Cookie {
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    // seconds since UNIX epoch, `0` for session cookies
+    expires: u64,
+    name: String,
+    value: String,
+}
+
+impl Cookie {
+    // parse cookies in the format written by curl, wget and browser extensions,
+    // malformed lines are skipped
+    fn parse_netscape(content: &str) -> Vec<Self> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+                if line.trim().is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let fields = line.split('\t').collect::<Vec<_>>();
+                let [domain, include_subdomains, path, secure, expires, name, value] =
+                    fields.as_slice()
+                else {
+                    return None;
+                };
+                Some(Self {
+                    domain: domain.trim_start_matches('.').to_lowercase(),
+                    include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE"),
+                    path: (*path).to_string(),
+                    secure: secure.eq_ignore_ascii_case("TRUE"),
+                    expires: expires.parse().ok()?,
+                    name: (*name).to_string(),
+                    value: value.trim_end_matches('\r').to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn matches(&self, uri: &http::Uri) -> bool {
+        let Some(host) = uri.host().map(str::to_lowercase) else {
+            return false;
+        };
+        let domain_matches = host == self.domain
+            || (self.include_subdomains && host.ends_with(&format!(".{}", self.domain)));
+        let is_expired = self.expires != 0
+            && SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .is_ok_and(|now| now.as_secs() > self.expires);
+        domain_matches
+            && uri.path().starts_with(&self.path)
+            && (!self.secure || uri.scheme_str() == Some("https"))
+            && !is_expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cookie;
+
+    #[test]
+    fn test_parse_netscape_cookies() {
+        let content = [
+            "# Netscape HTTP Cookie File",
+            ".example.com\tTRUE\t/\tFALSE\t0\tsession\tabc",
+            "#HttpOnly_example.com\tFALSE\t/private\tTRUE\t0\ttoken\tdef",
+            "example.org\tFALSE\t/\tFALSE\t1\texpired\tghi",
+            "malformed line",
+        ]
+        .join("\n");
+        let cookies = Cookie::parse_netscape(&content);
+        assert_eq!(cookies.len(), 3);
+
+        let uri = "https://dl.example.com/private/file".parse().unwrap();
+        assert!(cookies[0].matches(&uri));
+        assert!(!cookies[1].matches(&uri));
+
+        let uri = "https://example.com/private/file".parse().unwrap();
+        assert!(cookies[1].matches(&uri));
+        let uri = "http://example.com/private/file".parse().unwrap();
+        assert!(!cookies[1].matches(&uri));
+
+        let uri = "https://example.org/".parse().unwrap();
+        assert!(!cookies[2].matches(&uri));
+    }
+}
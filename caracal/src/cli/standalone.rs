@@ -1,4 +1,6 @@
-use std::{future::Future, path::Path, pin::Pin, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap, future::Future, path::PathBuf, pin::Pin, sync::Arc, time::Duration,
+};
 
 use caracal_base::model;
 use caracal_engine::DownloaderFactory;
@@ -7,7 +9,7 @@ use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use sigfinn::{ExitStatus, LifecycleManager};
 use snafu::ResultExt;
 
-use crate::{error, error::Error};
+use crate::{cli::http_options::HttpOptions, error, error::Error};
 
 const PROGRESS_STYLE_TEMPLATE: &str = "{spinner:.green} [{elapsed_precise}] [{msg}] \
                                        [{wide_bar:.cyan/blue}] {binary_bytes_per_sec} {percent}% \
@@ -18,22 +20,49 @@ enum Event {
     UpdateProgress,
 }
 
-pub async fn run<P>(
+pub struct TaskOptions {
+    pub output_directory: Option<PathBuf>,
+
+    pub concurrent_number: Option<u16>,
+
+    pub connection_timeout: Option<Duration>,
+
+    pub checksum: Option<model::Checksum>,
+
+    pub mirrors: Vec<http::Uri>,
+
+    /// Content of a Metalink document
+    pub metalink: Option<String>,
+
+    /// Filter of the files if a URI points to a directory
+    pub filter: model::PathFilter,
+
+    pub http_options: HttpOptions,
+}
+
+pub async fn run(
     uris: Vec<http::Uri>,
-    output_directory: Option<P>,
-    concurrent_number: Option<u16>,
-    connection_timeout: Option<Duration>,
+    TaskOptions {
+        output_directory,
+        concurrent_number,
+        connection_timeout,
+        checksum,
+        mirrors,
+        metalink,
+        filter,
+        http_options,
+    }: TaskOptions,
     downloader_factory: DownloaderFactory,
-) -> Result<(), Error>
-where
-    P: AsRef<Path> + Send,
-{
-    if uris.is_empty() {
+) -> Result<(), Error> {
+    if uris.is_empty() && metalink.is_none() {
         return Err(Error::NoUri);
     }
+    if !mirrors.is_empty() && uris.len() > 1 {
+        return Err(Error::MirrorsWithMultipleUris);
+    }
 
     let output_directory = if let Some(output_directory) = output_directory {
-        output_directory.as_ref().to_path_buf()
+        output_directory
     } else {
         std::env::current_dir().context(error::GetCurrentDirectorySnafu)?
     };
@@ -45,6 +74,52 @@ where
         return Err(Error::OutputDirectoryPathIsFile { output_directory });
     }
 
+    let mut tasks = Vec::with_capacity(uris.len());
+    for uri in uris {
+        let new_task = model::CreateTask {
+            headers: http_options.headers(&uri),
+            mirrors: mirrors.clone(),
+            output_directory: Some(output_directory.clone()),
+            concurrent_number: concurrent_number.map(u64::from),
+            connection_timeout,
+            checksum: checksum.clone(),
+            basic_auth: http_options.basic_auth(),
+            proxy: http_options.proxy(),
+            ..model::CreateTask::new(uri)
+        };
+        // a directory is expanded into the files under it
+        match downloader_factory.expand_directory(&new_task, &filter).await {
+            Ok(Some(new_tasks)) => tasks.extend(new_tasks),
+            Ok(None) => tasks.push(new_task),
+            Err(source) => {
+                return Err(Error::ExpandDirectory { uri: Box::new(new_task.uri), source });
+            }
+        }
+    }
+    if let Some(metalink) = metalink {
+        let metalink_tasks = model::CreateMetalinkTasks {
+            metalink,
+            output_directory: Some(output_directory.clone()),
+            concurrent_number: concurrent_number.map(u64::from),
+            connection_timeout,
+            priority: model::Priority::Normal,
+            headers: Vec::new(),
+            basic_auth: http_options.basic_auth(),
+            proxy: http_options.proxy(),
+            cookies: HashMap::new(),
+            not_before: None,
+            hooks: model::TaskHooks::default(),
+            on_remote_change: model::RemoteChangePolicy::default(),
+        }
+        .create_tasks()
+        .context(error::ParseMetalinkSnafu)?;
+        tasks.extend(
+            metalink_tasks
+                .into_iter()
+                .map(|task| model::CreateTask { headers: http_options.headers(&task.uri), ..task }),
+        );
+    }
+
     let downloader_factory = Arc::new(downloader_factory);
     let multi_progress = MultiProgress::new();
     let sty = ProgressStyle::with_template(PROGRESS_STYLE_TEMPLATE)
@@ -53,17 +128,7 @@ where
 
     let lifecycle_manager = LifecycleManager::<Error>::new();
 
-    for (idx, uri) in uris.into_iter().enumerate() {
-        let task = model::CreateTask {
-            uri,
-            output_directory: Some(output_directory.clone()),
-            filename: None,
-            concurrent_number: concurrent_number.map(u64::from),
-            connection_timeout,
-            priority: model::Priority::Normal,
-            creation_timestamp: time::OffsetDateTime::now_utc(),
-        };
-
+    for (idx, task) in tasks.into_iter().enumerate() {
         let progress_bar = multi_progress.add(ProgressBar::new(0));
         progress_bar.set_style(sty.clone());
 
@@ -28,9 +28,14 @@ pub fn render_task_statuses_table(task_statuses: &[model::TaskStatus]) -> String
         } else {
             format!("{:.2}%", (received_bytes as f64 / total_bytes as f64) * 100.0)
         };
+        let state = if status.seeding {
+            format!("{} (seeding)", status.state)
+        } else {
+            status.state.to_string()
+        };
         Row::from([
             Cell::new(status.id),
-            Cell::new(status.state),
+            Cell::new(state),
             Cell::new(status.file_path.display()),
             Cell::new(humansize::format_size(received_bytes, humansize::BINARY)),
             Cell::new(humansize::format_size(total_bytes, humansize::BINARY)),
@@ -46,6 +51,46 @@ pub fn render_task_statuses_table(task_statuses: &[model::TaskStatus]) -> String
     build_table().set_header(header).add_rows(rows).to_string()
 }
 
+pub fn render_task_group_status(group_status: &model::TaskGroupStatus) -> String {
+    let mut task_statuses = group_status.tasks.clone();
+    task_statuses.sort_unstable_by_key(|status| status.id);
+    format!(
+        "Group {id}: {name}\nState: {state}, received {received} of {size}\n\n{table}",
+        id = group_status.id,
+        name = group_status.name,
+        state = group_status.state,
+        received = humansize::format_size(group_status.received_bytes(), humansize::BINARY),
+        size = humansize::format_size(group_status.content_length(), humansize::BINARY),
+        table = render_task_statuses_table(&task_statuses)
+    )
+}
+
+// SAFETY: the precision loss is acceptable
+#[allow(clippy::cast_precision_loss)]
+pub fn render_task_groups_table(group_statuses: &[model::TaskGroupStatus]) -> String {
+    let header = Row::from(["ID", "NAME", "STATE", "TASKS", "RECEIVED", "SIZE", "PROGRESS"]);
+    let rows = group_statuses.iter().map(|status| {
+        let received_bytes = status.received_bytes();
+        let total_bytes = status.content_length().max(received_bytes);
+        let progress_percentage = if total_bytes == 0 {
+            String::from("0.00%")
+        } else {
+            format!("{:.2}%", (received_bytes as f64 / total_bytes as f64) * 100.0)
+        };
+        Row::from([
+            Cell::new(status.id),
+            Cell::new(&status.name),
+            Cell::new(status.state),
+            Cell::new(status.tasks.len()),
+            Cell::new(humansize::format_size(received_bytes, humansize::BINARY)),
+            Cell::new(humansize::format_size(total_bytes, humansize::BINARY)),
+            Cell::new(progress_percentage),
+        ])
+    });
+
+    build_table().set_header(header).add_rows(rows).to_string()
+}
+
 pub fn build_table() -> Table {
     let mut table = Table::new();
     let _ = table
@@ -7,7 +7,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use caracal_base::profile::{minio::MinioAlias, ssh::SshConfig};
+use caracal_base::profile::{minio::MinioAlias, s3::S3Profile, ssh::SshConfig};
 use caracal_cli::{
     profile,
     profile::{Profile, ProfileItem},
@@ -82,6 +82,7 @@ impl Config {
 
     pub async fn load_profiles(&self) -> Result<Profiles, Error> {
         let mut minio_aliases = HashMap::new();
+        let mut s3_profiles = HashMap::new();
         let mut ssh_servers = HashMap::new();
         for profile_file in self.profile_files() {
             for profile_item in Profile::load(profile_file).await?.profiles {
@@ -103,16 +104,40 @@ impl Config {
                         let alias = MinioAlias { endpoint_url, access_key, secret_key };
                         drop(minio_aliases.insert(name, alias));
                     }
+                    ProfileItem::S3(profile::S3 {
+                        name,
+                        region,
+                        endpoint_url,
+                        force_path_style,
+                        access_key,
+                        secret_key,
+                        session_token,
+                        anonymous,
+                    }) => {
+                        let profile = S3Profile {
+                            region,
+                            endpoint_url,
+                            force_path_style,
+                            access_key,
+                            secret_key,
+                            session_token,
+                            anonymous,
+                        };
+                        drop(s3_profiles.insert(name, profile));
+                    }
                 }
             }
         }
 
-        Ok(Profiles { minio_aliases, ssh_servers })
+        Ok(Profiles { minio_aliases, s3_profiles, ssh_servers })
     }
 }
 
+// named after the options of `DownloaderFactory`
+#[allow(clippy::struct_field_names)]
 #[derive(Clone, Debug)]
 pub struct Profiles {
     pub minio_aliases: HashMap<String, MinioAlias>,
+    pub s3_profiles: HashMap<String, S3Profile>,
     pub ssh_servers: HashMap<String, SshConfig>,
 }
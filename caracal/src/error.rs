@@ -29,6 +29,9 @@ pub enum Error {
     #[snafu(display("Error occurs while downloading {uri}, error: {error}"))]
     Downloader { uri: Box<http::Uri>, error: caracal_engine::Error },
 
+    #[snafu(display("Error occurs while listing directory {uri}, error: {source}"))]
+    ExpandDirectory { uri: Box<http::Uri>, source: caracal_engine::Error },
+
     #[snafu(display("Error occurs while running lifecycle manager, error: {source}"))]
     LifecycleManager { source: sigfinn::Error },
 
@@ -43,6 +46,18 @@ pub enum Error {
 
     #[snafu(display("No URI is provided"))]
     NoUri,
+
+    #[snafu(display("Mirrors can only be provided with a single URI"))]
+    MirrorsWithMultipleUris,
+
+    #[snafu(display("Could not read Metalink file {}, error: {source}", file_path.display()))]
+    ReadMetalinkFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not parse Metalink file, error: {source}"))]
+    ParseMetalink { source: caracal_base::model::ParseMetalinkError },
+
+    #[snafu(display("Could not read cookie file {}, error: {source}", file_path.display()))]
+    ReadCookieFile { file_path: PathBuf, source: std::io::Error },
 }
 
 impl From<crate::config::Error> for Error {
@@ -59,6 +74,12 @@ impl From<caracal_grpc_client::error::AddUriError> for Error {
     }
 }
 
+impl From<caracal_grpc_client::error::AddMetalinkError> for Error {
+    fn from(error: caracal_grpc_client::error::AddMetalinkError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
 impl From<caracal_grpc_client::error::PauseTaskError> for Error {
     fn from(error: caracal_grpc_client::error::PauseTaskError) -> Self {
         Self::Operation { error: error.to_string() }
@@ -101,14 +122,68 @@ impl From<caracal_grpc_client::error::DecreaseConcurrentNumberError> for Error {
     }
 }
 
+impl From<caracal_grpc_client::error::SetGlobalSpeedLimitError> for Error {
+    fn from(error: caracal_grpc_client::error::SetGlobalSpeedLimitError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::SetTaskSpeedLimitError> for Error {
+    fn from(error: caracal_grpc_client::error::SetTaskSpeedLimitError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
 impl From<caracal_grpc_client::error::GetTaskStatusError> for Error {
     fn from(error: caracal_grpc_client::error::GetTaskStatusError) -> Self {
         Self::Operation { error: error.to_string() }
     }
 }
 
+impl From<caracal_grpc_client::error::GetGroupStatusError> for Error {
+    fn from(error: caracal_grpc_client::error::GetGroupStatusError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
 impl From<caracal_grpc_client::error::GetAllTaskStatusesError> for Error {
     fn from(error: caracal_grpc_client::error::GetAllTaskStatusesError) -> Self {
         Self::Operation { error: error.to_string() }
     }
 }
+
+impl From<caracal_grpc_client::error::GetAllGroupStatusesError> for Error {
+    fn from(error: caracal_grpc_client::error::GetAllGroupStatusesError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::CreateGroupError> for Error {
+    fn from(error: caracal_grpc_client::error::CreateGroupError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::PauseGroupError> for Error {
+    fn from(error: caracal_grpc_client::error::PauseGroupError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::ResumeGroupError> for Error {
+    fn from(error: caracal_grpc_client::error::ResumeGroupError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::RemoveGroupError> for Error {
+    fn from(error: caracal_grpc_client::error::RemoveGroupError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
+
+impl From<caracal_grpc_client::error::SetPriorityError> for Error {
+    fn from(error: caracal_grpc_client::error::SetPriorityError) -> Self {
+        Self::Operation { error: error.to_string() }
+    }
+}
@@ -18,9 +18,9 @@ pub const PROJECT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const CONTROL_FILE_SUFFIX: &str = "caracal";
 
 pub const DBUS_SERVICE_NAME: &str = "org.caracal.caracal-daemon";
-pub const DBUS_OBJECT_PATH_PREFIX: &str = "/org/caracal/caracal-daemon";
-pub const DBUS_SYSTEM_OBJECT_PATH: &str = "/org/caracal/caracal-daemon/system";
-pub const DBUS_MANAGER_OBJECT_PATH: &str = "/org/caracal/caracal-daemon/manager";
+pub const DBUS_OBJECT_PATH_PREFIX: &str = "/org/caracal/caracal_daemon";
+pub const DBUS_SYSTEM_OBJECT_PATH: &str = "/org/caracal/caracal_daemon/system";
+pub const DBUS_MANAGER_OBJECT_PATH: &str = "/org/caracal/caracal_daemon/manager";
 
 pub const FALLBACK_FILENAME: &str = "index.html";
 
@@ -51,6 +51,8 @@ pub const DAEMON_CONFIG_NAME: &str = "caracal-daemon.toml";
 pub const TUI_PROGRAM_NAME: &str = "caracal-tui";
 pub const TUI_CONFIG_NAME: &str = "caracal-tui.toml";
 
+pub const TASK_STORE_FILE_NAME: &str = "tasks.json";
+
 pub const DEFAULT_GRPC_PORT: u16 = 37000;
 pub const DEFAULT_GRPC_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
@@ -66,3 +68,10 @@ pub static PROJECT_CONFIG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
         .config_dir()
         .to_path_buf()
 });
+
+pub static PROJECT_DATA_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+    ProjectDirs::from("", PROJECT_NAME, PROJECT_NAME)
+        .expect("Creating `ProjectDirs` should always success")
+        .data_dir()
+        .to_path_buf()
+});
@@ -0,0 +1,158 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use snafu::Snafu;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha512,
+    Sha1,
+    Md5,
+    Blake3,
+}
+
+impl ChecksumAlgorithm {
+    /// Length of the digest in bytes
+    #[must_use]
+    pub const fn digest_length(self) -> usize {
+        match self {
+            Self::Sha256 | Self::Blake3 => 32,
+            Self::Sha512 => 64,
+            Self::Sha1 => 20,
+            Self::Md5 => 16,
+        }
+    }
+}
+
+impl fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Sha1 => "sha1",
+            Self::Md5 => "md5",
+            Self::Blake3 => "blake3",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for ChecksumAlgorithm {
+    type Err = ParseChecksumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(Self::Sha256),
+            "sha512" | "sha-512" => Ok(Self::Sha512),
+            "sha1" | "sha-1" => Ok(Self::Sha1),
+            "md5" => Ok(Self::Md5),
+            "blake3" => Ok(Self::Blake3),
+            _ => Err(ParseChecksumError::UnsupportedAlgorithm { algorithm: s.to_string() }),
+        }
+    }
+}
+
+/// Expected digest of a downloaded file, written as `<algorithm>=<hex digest>`
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Checksum {
+    algorithm: ChecksumAlgorithm,
+
+    digest: String,
+}
+
+impl Checksum {
+    /// # Errors
+    pub fn new<S>(algorithm: ChecksumAlgorithm, digest: S) -> Result<Self, ParseChecksumError>
+    where
+        S: AsRef<str>,
+    {
+        let digest = digest.as_ref().trim().to_lowercase();
+        if digest.len() == algorithm.digest_length() * 2
+            && digest.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            Ok(Self { algorithm, digest })
+        } else {
+            Err(ParseChecksumError::InvalidDigest { algorithm, digest })
+        }
+    }
+
+    #[must_use]
+    pub const fn algorithm(&self) -> ChecksumAlgorithm { self.algorithm }
+
+    /// Lowercase hex digest
+    #[must_use]
+    pub fn digest(&self) -> &str { &self.digest }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.algorithm, self.digest)
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = ParseChecksumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, digest) = s.split_once('=').ok_or(ParseChecksumError::InvalidFormat)?;
+        Self::new(algorithm.trim().parse()?, digest)
+    }
+}
+
+impl Serialize for Checksum {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Checksum {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ParseChecksumError {
+    #[snafu(display("Checksum should be in the form of `<algorithm>=<hex digest>`"))]
+    InvalidFormat,
+
+    #[snafu(display(
+        "Checksum algorithm `{algorithm}` is not supported, available values: sha256, sha512, \
+         sha1, md5, blake3"
+    ))]
+    UnsupportedAlgorithm { algorithm: String },
+
+    #[snafu(display("`{digest}` is not a valid {algorithm} digest"))]
+    InvalidDigest { algorithm: ChecksumAlgorithm, digest: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checksum, ChecksumAlgorithm};
+
+    #[test]
+    fn test_parse() {
+        let checksum = "SHA256=E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855"
+            .parse::<Checksum>()
+            .unwrap();
+        assert_eq!(checksum.algorithm(), ChecksumAlgorithm::Sha256);
+        assert_eq!(
+            checksum.to_string(),
+            "sha256=e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        assert!("d41d8cd98f00b204e9800998ecf8427e".parse::<Checksum>().is_err());
+        assert!("crc32=d41d8cd9".parse::<Checksum>().is_err());
+        assert!("md5=d41d8cd98f00b204e9800998ecf8427".parse::<Checksum>().is_err());
+        assert!("md5=d41d8cd98f00b204e9800998ecf8427z".parse::<Checksum>().is_err());
+        assert!("md5=d41d8cd98f00b204e9800998ecf8427e".parse::<Checksum>().is_ok());
+    }
+}
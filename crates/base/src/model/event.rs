@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskEvent {
+    Added { task_id: u64 },
+    Started { task_id: u64 },
+    Progress { task_id: u64, received_bytes: u64, total_length: u64 },
+    Paused { task_id: u64 },
+    Resumed { task_id: u64 },
+    Completed { task_id: u64 },
+    Failed { task_id: u64, error: String },
+    Removed { task_id: u64 },
+    // stopped for a task of higher priority, the task is queued again
+    Preempted { task_id: u64 },
+}
+
+impl TaskEvent {
+    #[must_use]
+    pub const fn task_id(&self) -> u64 {
+        match self {
+            Self::Added { task_id }
+            | Self::Started { task_id }
+            | Self::Progress { task_id, .. }
+            | Self::Paused { task_id }
+            | Self::Resumed { task_id }
+            | Self::Completed { task_id }
+            | Self::Failed { task_id, .. }
+            | Self::Removed { task_id }
+            | Self::Preempted { task_id } => *task_id,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// External actions triggered once a task is completed or failed
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct TaskHooks {
+    /// Shell commands to run, the task metadata is passed in the environment
+    /// variables `CARACAL_TASK_ID`, `CARACAL_TASK_STATE`, `CARACAL_TASK_URI`,
+    /// `CARACAL_FILE_PATH`, `CARACAL_CONTENT_LENGTH` and `CARACAL_TASK_ERROR`
+    #[schema(example = json!(["unzip -o \"$CARACAL_FILE_PATH\""]))]
+    #[serde(default)]
+    pub commands: Vec<String>,
+
+    /// URLs to which the status of the task is posted in JSON
+    #[schema(value_type = Vec<String>, example = json!(["http://127.0.0.1:8080/hooks/caracal"]))]
+    #[serde(default, with = "crate::serde::uris")]
+    pub webhooks: Vec<http::Uri>,
+}
+
+impl TaskHooks {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool { self.commands.is_empty() && self.webhooks.is_empty() }
+}
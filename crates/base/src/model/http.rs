@@ -0,0 +1,142 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use utoipa::ToSchema;
+
+/// Header attached to every HTTP request of a task, written as `<name>:
+/// <value>`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct HttpHeader {
+    #[schema(example = "Authorization")]
+    pub name: String,
+
+    #[schema(example = "Bearer <token>")]
+    pub value: String,
+}
+
+impl HttpHeader {
+    pub fn new<N, V>(name: N, value: V) -> Self
+    where
+        N: fmt::Display,
+        V: fmt::Display,
+    {
+        Self { name: name.to_string(), value: value.to_string() }
+    }
+}
+
+impl fmt::Display for HttpHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+impl FromStr for HttpHeader {
+    type Err = ParseHttpHeaderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s.split_once(':').ok_or(ParseHttpHeaderError::InvalidFormat)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ParseHttpHeaderError::InvalidFormat);
+        }
+        Ok(Self::new(name, value.trim()))
+    }
+}
+
+/// Credential of HTTP basic authentication, written as
+/// `<username>[:<password>]`
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct BasicAuth {
+    pub username: String,
+
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl FromStr for BasicAuth {
+    type Err = ParseBasicAuthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (username, password) = match s.split_once(':') {
+            Some((username, password)) => (username, Some(password.to_string())),
+            None => (s, None),
+        };
+        if username.is_empty() {
+            return Err(ParseBasicAuthError::EmptyUsername);
+        }
+        Ok(Self { username: username.to_string(), password })
+    }
+}
+
+/// Proxy of HTTP requests, the scheme of `uri` is one of `http`, `https`,
+/// `socks5` and `socks5h`
+#[derive(Clone, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct Proxy {
+    #[schema(example = "http://proxy.example.com:3128")]
+    pub uri: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Hosts which are connected directly, e.g. `localhost`, `.example.com` or
+    /// `10.0.0.0/8`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("uri", &self.uri)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ParseHttpHeaderError {
+    #[snafu(display("HTTP header should be in the form of `<name>: <value>`"))]
+    InvalidFormat,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ParseBasicAuthError {
+    #[snafu(display("Username of basic authentication should not be empty"))]
+    EmptyUsername,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BasicAuth, HttpHeader};
+
+    #[test]
+    fn test_parse() {
+        let header = "Authorization: Bearer abc:def".parse::<HttpHeader>().unwrap();
+        assert_eq!(header, HttpHeader::new("Authorization", "Bearer abc:def"));
+        assert_eq!(header.to_string(), "Authorization: Bearer abc:def");
+        assert!(": value".parse::<HttpHeader>().is_err());
+        assert!("no-colon".parse::<HttpHeader>().is_err());
+
+        let auth = "user:pass:word".parse::<BasicAuth>().unwrap();
+        assert_eq!(auth.username, "user");
+        assert_eq!(auth.password.as_deref(), Some("pass:word"));
+        assert_eq!("user".parse::<BasicAuth>().unwrap().password, None);
+        assert!(":password".parse::<BasicAuth>().is_err());
+        assert!(!format!("{auth:?}").contains("pass:word"));
+    }
+}
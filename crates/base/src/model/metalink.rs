@@ -0,0 +1,347 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Snafu};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::model::{
+    BasicAuth, Checksum, ChecksumAlgorithm, CreateTask, HttpHeader, Priority, Proxy,
+    RemoteChangePolicy, TaskHooks,
+};
+
+// the lowest priority defined by RFC 5854
+const DEFAULT_URL_PRIORITY: u32 = 999_999;
+
+/// Files described by a Metalink document, both Metalink 4 (RFC 5854) and
+/// Metalink 3 are supported
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metalink {
+    pub files: Vec<MetalinkFile>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetalinkFile {
+    /// Relative path of the file
+    pub name: PathBuf,
+
+    pub size: Option<u64>,
+
+    /// The strongest supported hash of the file
+    pub checksum: Option<Checksum>,
+
+    /// The URI with the highest priority
+    pub uri: http::Uri,
+
+    /// Other URIs, ordered by priority
+    pub mirrors: Vec<http::Uri>,
+}
+
+impl FromStr for Metalink {
+    type Err = ParseMetalinkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = roxmltree::Document::parse(s).context(InvalidXmlSnafu)?;
+        let root = document.root_element();
+        if root.tag_name().name() != "metalink" {
+            return Err(ParseMetalinkError::NotMetalink);
+        }
+
+        let files = root
+            .descendants()
+            .filter(|node| node.tag_name().name() == "file")
+            .map(MetalinkFile::from_node)
+            .collect::<Result<Vec<_>, _>>()?;
+        if files.is_empty() {
+            return Err(ParseMetalinkError::NoFile);
+        }
+        Ok(Self { files })
+    }
+}
+
+impl MetalinkFile {
+    fn from_node(node: roxmltree::Node<'_, '_>) -> Result<Self, ParseMetalinkError> {
+        let name = node.attribute("name").context(NoFileNameSnafu)?;
+        if !is_relative_path(name) {
+            return Err(ParseMetalinkError::InvalidFileName { name: name.to_string() });
+        }
+
+        let size = child_text(node, "size")
+            .map(|size| {
+                size.parse::<u64>().ok().context(InvalidSizeSnafu { name: name.to_string() })
+            })
+            .transpose()?;
+
+        // Metalink 3 places hashes in `<verification>`, piece hashes are ignored
+        let checksum = node
+            .children()
+            .filter(|child| child.tag_name().name() == "verification")
+            .chain(std::iter::once(node))
+            .flat_map(|parent| parent.children())
+            .filter(|child| child.tag_name().name() == "hash")
+            .filter_map(|hash| {
+                let algorithm = hash.attribute("type")?.parse::<ChecksumAlgorithm>().ok()?;
+                Checksum::new(algorithm, hash.text()?).ok()
+            })
+            .max_by_key(|checksum| algorithm_strength(checksum.algorithm()));
+
+        let mut uris = node
+            .descendants()
+            .filter(|child| child.tag_name().name() == "url")
+            .filter(|url| url.attribute("type").is_none_or(|kind| kind != "bittorrent"))
+            .filter_map(|url| {
+                let uri = url.text()?.trim().parse::<http::Uri>().ok()?;
+                Some((url_priority(url), uri))
+            })
+            .collect::<Vec<_>>();
+        uris.sort_by_key(|(priority, _)| *priority);
+
+        let mut uris = uris.into_iter().map(|(_, uri)| uri);
+        let uri = uris.next().context(NoUriSnafu { name: name.to_string() })?;
+        Ok(Self { name: PathBuf::from(name), size, checksum, uri, mirrors: uris.collect() })
+    }
+}
+
+/// Create a task for each file of a Metalink document
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct CreateMetalinkTasks {
+    /// Content of the Metalink document
+    pub metalink: String,
+
+    #[schema(value_type = Option<String>, example = "/tmp")]
+    pub output_directory: Option<PathBuf>,
+
+    #[schema(value_type = Option<u64>, example = "5")]
+    pub concurrent_number: Option<u64>,
+
+    #[schema(value_type = Option<u64>, example = "null")]
+    pub connection_timeout: Option<Duration>,
+
+    pub priority: Priority,
+
+    #[serde(default)]
+    pub headers: Vec<HttpHeader>,
+
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuth>,
+
+    #[serde(default)]
+    pub proxy: Option<Proxy>,
+
+    /// Value of the `Cookie` header of the requests to each URI of the
+    /// document, the cookies of a file are not sent to its mirrors
+    #[schema(example = json!({"https://example.com/file.iso": "session=abc"}))]
+    #[serde(default)]
+    pub cookies: HashMap<String, String>,
+
+    /// The tasks are not started before this moment
+    #[schema(value_type = Option<String>, example = "2024-01-01T22:00:00Z")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub not_before: Option<OffsetDateTime>,
+
+    /// Run after each of the tasks is completed or failed
+    #[serde(default)]
+    pub hooks: TaskHooks,
+
+    #[serde(default)]
+    pub on_remote_change: RemoteChangePolicy,
+}
+
+impl CreateMetalinkTasks {
+    /// # Errors
+    pub fn create_tasks(&self) -> Result<Vec<CreateTask>, ParseMetalinkError> {
+        let Metalink { files } = self.metalink.parse()?;
+        let creation_timestamp = OffsetDateTime::now_utc();
+        Ok(files
+            .into_iter()
+            .map(|MetalinkFile { name, size, checksum, uri, mirrors }| CreateTask {
+                headers: self.headers(&uri),
+                uri,
+                mirrors,
+                filename: Some(name),
+                output_directory: self.output_directory.clone(),
+                concurrent_number: self.concurrent_number,
+                connection_timeout: self.connection_timeout,
+                priority: self.priority,
+                creation_timestamp,
+                checksum,
+                basic_auth: self.basic_auth.clone(),
+                proxy: self.proxy.clone(),
+                content_length: size,
+                not_before: self.not_before,
+                hooks: self.hooks.clone(),
+                on_remote_change: self.on_remote_change,
+            })
+            .collect())
+    }
+
+    fn headers(&self, uri: &http::Uri) -> Vec<HttpHeader> {
+        let mut headers = self.headers.clone();
+        if let Some(cookie) = self.cookies.get(&uri.to_string()) {
+            headers.push(HttpHeader::new("Cookie", cookie.clone()));
+        }
+        headers
+    }
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children().find(|child| child.tag_name().name() == name)?.text().map(str::trim)
+}
+
+// Metalink 4 uses `priority` (1 is the highest), Metalink 3 uses `preference`
+// (100 is the highest)
+fn url_priority(url: roxmltree::Node<'_, '_>) -> u32 {
+    url.attribute("priority")
+        .and_then(|priority| priority.parse().ok())
+        .or_else(|| {
+            url.attribute("preference")
+                .and_then(|preference| preference.parse::<u32>().ok())
+                .map(|preference| 101 - preference.min(100))
+        })
+        .unwrap_or(DEFAULT_URL_PRIORITY)
+}
+
+const fn algorithm_strength(algorithm: ChecksumAlgorithm) -> u8 {
+    match algorithm {
+        ChecksumAlgorithm::Md5 => 0,
+        ChecksumAlgorithm::Sha1 => 1,
+        ChecksumAlgorithm::Sha256 => 2,
+        ChecksumAlgorithm::Blake3 => 3,
+        ChecksumAlgorithm::Sha512 => 4,
+    }
+}
+
+// file names must not escape the output directory
+fn is_relative_path(name: &str) -> bool {
+    let path = Path::new(name);
+    path.components().next().is_some()
+        && path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ParseMetalinkError {
+    #[snafu(display("Invalid XML document, error: {source}"))]
+    InvalidXml { source: roxmltree::Error },
+
+    #[snafu(display("Document is not a Metalink document"))]
+    NotMetalink,
+
+    #[snafu(display("Metalink document does not contain any file"))]
+    NoFile,
+
+    #[snafu(display("Name of file is not provided"))]
+    NoFileName,
+
+    #[snafu(display("File name `{name}` is not a relative path"))]
+    InvalidFileName { name: String },
+
+    #[snafu(display("Size of file `{name}` is invalid"))]
+    InvalidSize { name: String },
+
+    #[snafu(display("No supported URI is provided for file `{name}`"))]
+    NoUri { name: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{CreateMetalinkTasks, Metalink, ParseMetalinkError};
+    use crate::model::{ChecksumAlgorithm, HttpHeader, Priority, RemoteChangePolicy, TaskHooks};
+
+    #[test]
+    fn test_parse_metalink_4() {
+        let metalink = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="example/file.tar.gz">
+    <size>14471447</size>
+    <hash type="md5">d41d8cd98f00b204e9800998ecf8427e</hash>
+    <hash type="sha-256">e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855</hash>
+    <pieces length="262144" type="sha-1">
+      <hash>da39a3ee5e6b4b0d3255bfef95601890afd80709</hash>
+    </pieces>
+    <url priority="2">https://mirror.example.com/file.tar.gz</url>
+    <url priority="1">https://www.example.com/file.tar.gz</url>
+    <metaurl mediatype="torrent">https://www.example.com/file.torrent</metaurl>
+  </file>
+</metalink>"#
+            .parse::<Metalink>()
+            .unwrap();
+
+        assert_eq!(metalink.files.len(), 1);
+        let file = &metalink.files[0];
+        assert_eq!(file.name, PathBuf::from("example/file.tar.gz"));
+        assert_eq!(file.size, Some(14_471_447));
+        assert_eq!(file.checksum.as_ref().unwrap().algorithm(), ChecksumAlgorithm::Sha256);
+        assert_eq!(file.uri, "https://www.example.com/file.tar.gz");
+        assert_eq!(file.mirrors, vec!["https://mirror.example.com/file.tar.gz"]);
+    }
+
+    #[test]
+    fn test_parse_metalink_3() {
+        let metalink = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metalink version="3.0" xmlns="http://www.metalinker.org/">
+  <files>
+    <file name="file.iso">
+      <verification>
+        <hash type="sha1">da39a3ee5e6b4b0d3255bfef95601890afd80709</hash>
+      </verification>
+      <resources>
+        <url type="bittorrent" preference="100">https://www.example.com/file.torrent</url>
+        <url type="http" preference="50">https://mirror.example.com/file.iso</url>
+        <url type="http" preference="90">https://www.example.com/file.iso</url>
+      </resources>
+    </file>
+  </files>
+</metalink>"#
+            .parse::<Metalink>()
+            .unwrap();
+
+        let file = &metalink.files[0];
+        assert_eq!(file.size, None);
+        assert_eq!(file.checksum.as_ref().unwrap().algorithm(), ChecksumAlgorithm::Sha1);
+        assert_eq!(file.uri, "https://www.example.com/file.iso");
+        assert_eq!(file.mirrors, vec!["https://mirror.example.com/file.iso"]);
+    }
+
+    #[test]
+    fn test_reject_file_name_outside_output_directory() {
+        let err = r#"<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="../file"><url>https://www.example.com/file</url></file>
+</metalink>"#
+            .parse::<Metalink>()
+            .unwrap_err();
+        assert!(matches!(err, ParseMetalinkError::InvalidFileName { .. }));
+    }
+
+    #[test]
+    fn test_send_cookies_to_uri_of_file() {
+        let create_tasks = CreateMetalinkTasks {
+            metalink: r#"<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="a"><url>https://www.example.com/a</url></file>
+  <file name="b"><url>https://mirror.example.com/b</url></file>
+</metalink>"#
+                .to_string(),
+            output_directory: None,
+            concurrent_number: None,
+            connection_timeout: None,
+            priority: Priority::Normal,
+            headers: Vec::new(),
+            basic_auth: None,
+            proxy: None,
+            cookies: [("https://www.example.com/a".to_string(), "session=abc".to_string())].into(),
+            not_before: None,
+            hooks: TaskHooks::default(),
+            on_remote_change: RemoteChangePolicy::default(),
+        };
+        let tasks = create_tasks.create_tasks().unwrap();
+        assert_eq!(tasks[0].headers, vec![HttpHeader::new("Cookie", "session=abc")]);
+        assert_eq!(tasks[1].headers, Vec::new());
+    }
+}
@@ -1,7 +1,25 @@
+mod checksum;
+mod event;
+mod hook;
+mod http;
+mod metalink;
+mod path_filter;
 mod priority;
+mod schedule;
 mod task;
 
 pub use self::{
+    checksum::{Checksum, ChecksumAlgorithm, ParseChecksumError},
+    event::TaskEvent,
+    hook::TaskHooks,
+    http::{BasicAuth, HttpHeader, ParseBasicAuthError, ParseHttpHeaderError, Proxy},
+    metalink::{CreateMetalinkTasks, Metalink, MetalinkFile, ParseMetalinkError},
+    path_filter::PathFilter,
     priority::Priority,
-    task::{CreateTask, ProgressChunk, TaskState, TaskStatus},
+    schedule::{DownloadSchedule, DownloadWindow},
+    task::{
+        ConnectionTuningAction, ConnectionTuningDecision, CreateTask, CreateTaskGroup,
+        ProgressChunk, RemoteChangePolicy, SpeedLimit, TaskGroupStatus, TaskPriority, TaskState,
+        TaskStatus,
+    },
 };
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::glob_match;
+
+/// Glob patterns selecting the files to download from a directory
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct PathFilter {
+    /// Download only the files matching any of the patterns, all files are
+    /// downloaded if it is empty
+    #[schema(example = json!(["*.iso", "images/**"]))]
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Skip the files matching any of the patterns
+    #[schema(example = json!(["*.tmp"]))]
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl PathFilter {
+    /// `path` is relative to the downloaded directory
+    #[must_use]
+    pub fn is_match(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| glob_match(pattern, path)))
+            && !self.exclude.iter().any(|pattern| glob_match(pattern, path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathFilter;
+
+    #[test]
+    fn test_is_match() {
+        assert!(PathFilter::default().is_match("a/b.txt"));
+
+        let filter = PathFilter {
+            include: vec!["*.iso".to_string(), "docs/**".to_string()],
+            exclude: vec!["*-testing.iso".to_string(), "*.tmp".to_string()],
+        };
+        assert!(filter.is_match("debian.iso"));
+        assert!(filter.is_match("images/debian.iso"));
+        assert!(filter.is_match("docs/guide/index.html"));
+        assert!(!filter.is_match("debian-testing.iso"));
+        assert!(!filter.is_match("docs/draft.tmp"));
+        assert!(!filter.is_match("README.md"));
+    }
+}
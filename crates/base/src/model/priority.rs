@@ -18,6 +18,30 @@ pub enum Priority {
     Highest = 4,
 }
 
+impl Priority {
+    /// The next higher priority, `Highest` stays unchanged
+    #[must_use]
+    pub const fn raise(self) -> Self {
+        match self {
+            Self::Lowest => Self::Low,
+            Self::Low => Self::Normal,
+            Self::Normal => Self::High,
+            Self::High | Self::Highest => Self::Highest,
+        }
+    }
+
+    /// The next lower priority, `Lowest` stays unchanged
+    #[must_use]
+    pub const fn lower(self) -> Self {
+        match self {
+            Self::Lowest | Self::Low => Self::Lowest,
+            Self::Normal => Self::Low,
+            Self::High => Self::Normal,
+            Self::Highest => Self::High,
+        }
+    }
+}
+
 impl From<String> for Priority {
     fn from(s: String) -> Self {
         match s.to_lowercase().as_str() {
@@ -0,0 +1,191 @@
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+
+/// A recurring period of time in which tasks are allowed to download
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DownloadWindow {
+    /// The days on which the window starts, e.g. `["mon", "fri"]`, the window
+    /// opens every day if it is empty
+    #[serde(default, with = "crate::serde::weekdays")]
+    pub days: Vec<Weekday>,
+
+    /// Local time at which the window opens, e.g. `"22:00"`
+    #[serde(with = "crate::serde::time_of_day")]
+    pub start: Time,
+
+    /// Local time at which the window closes, the window ends on the next day
+    /// if it is not later than `start`
+    #[serde(with = "crate::serde::time_of_day")]
+    pub end: Time,
+}
+
+impl DownloadWindow {
+    fn opens_on(&self, day: Weekday) -> bool { self.days.is_empty() || self.days.contains(&day) }
+
+    /// `now` is expected to be in the local offset
+    #[must_use]
+    pub fn contains(&self, now: OffsetDateTime) -> bool {
+        let time = now.time();
+        let day = now.weekday();
+        if self.start < self.end {
+            self.opens_on(day) && self.start <= time && time < self.end
+        } else {
+            (self.opens_on(day) && self.start <= time)
+                || (self.opens_on(day.previous()) && time < self.end)
+        }
+    }
+
+    /// The local times at which the window opens and closes if it opens on
+    /// `date`
+    fn boundaries_on(&self, date: Date) -> Option<[PrimitiveDateTime; 2]> {
+        if !self.opens_on(date.weekday()) {
+            return None;
+        }
+        let end_date = if self.start < self.end { date } else { date.next_day()? };
+        Some([date.with_time(self.start), end_date.with_time(self.end)])
+    }
+}
+
+/// The windows in which tasks are allowed to download
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DownloadSchedule {
+    pub windows: Vec<DownloadWindow>,
+
+    /// The offset of the windows, the local offset in effect at each instant
+    /// is used if it is `None` so that the windows follow daylight saving time
+    pub utc_offset: Option<UtcOffset>,
+}
+
+impl DownloadSchedule {
+    /// The windows are in local time
+    #[must_use]
+    pub const fn new(windows: Vec<DownloadWindow>) -> Self { Self { windows, utc_offset: None } }
+
+    #[must_use]
+    pub const fn with_utc_offset(mut self, utc_offset: UtcOffset) -> Self {
+        self.utc_offset = Some(utc_offset);
+        self
+    }
+
+    fn offset_at(&self, datetime: OffsetDateTime) -> UtcOffset {
+        self.utc_offset.unwrap_or_else(|| local_offset_at(datetime))
+    }
+
+    /// Downloading is always allowed if no window is configured
+    #[must_use]
+    pub fn is_open(&self, now: OffsetDateTime) -> bool {
+        if self.windows.is_empty() {
+            return true;
+        }
+        let now = now.to_offset(self.offset_at(now));
+        self.windows.iter().any(|window| window.contains(now))
+    }
+
+    /// The first instant after `now` at which a window opens or closes,
+    /// `None` if no window is configured
+    #[must_use]
+    pub fn next_boundary(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        let offset = self.offset_at(now);
+        let today = now.to_offset(offset).date();
+        // an overnight window which opened yesterday closes today and every
+        // window opens again within a week
+        (-1..=7)
+            .filter_map(|days| today.checked_add(Duration::days(days)))
+            .flat_map(|date| {
+                self.windows.iter().filter_map(move |window| window.boundaries_on(date))
+            })
+            .flatten()
+            .map(|boundary| boundary.assume_offset(self.offset_at(boundary.assume_offset(offset))))
+            .filter(|boundary| *boundary > now)
+            .min()
+    }
+}
+
+/// The offset of the local time zone at `datetime`
+fn local_offset_at(datetime: OffsetDateTime) -> UtcOffset {
+    Local
+        .timestamp_opt(datetime.unix_timestamp(), 0)
+        .single()
+        .and_then(|local| UtcOffset::from_whole_seconds(local.offset().local_minus_utc()).ok())
+        .unwrap_or(UtcOffset::UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{
+        Weekday,
+        macros::{datetime, offset, time},
+    };
+
+    use super::{DownloadSchedule, DownloadWindow};
+
+    #[test]
+    fn test_contains() {
+        let window = DownloadWindow {
+            days: vec![Weekday::Monday, Weekday::Friday],
+            start: time!(09:00),
+            end: time!(17:30),
+        };
+        // 2024-01-01 is a Monday
+        assert!(window.contains(datetime!(2024-01-01 09:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-01 17:29 UTC)));
+        assert!(!window.contains(datetime!(2024-01-01 17:30 UTC)));
+        assert!(!window.contains(datetime!(2024-01-01 08:59 UTC)));
+        assert!(!window.contains(datetime!(2024-01-02 10:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-05 10:00 UTC)));
+    }
+
+    #[test]
+    fn test_contains_overnight() {
+        let window =
+            DownloadWindow { days: vec![Weekday::Friday], start: time!(22:00), end: time!(06:00) };
+        assert!(window.contains(datetime!(2024-01-05 23:00 UTC)));
+        assert!(window.contains(datetime!(2024-01-06 05:59 UTC)));
+        assert!(!window.contains(datetime!(2024-01-06 06:00 UTC)));
+        assert!(!window.contains(datetime!(2024-01-06 23:00 UTC)));
+        assert!(!window.contains(datetime!(2024-01-05 05:00 UTC)));
+    }
+
+    #[test]
+    fn test_is_open() {
+        assert!(DownloadSchedule::default().is_open(datetime!(2024-01-01 12:00 UTC)));
+
+        let schedule = DownloadSchedule::new(vec![DownloadWindow {
+            days: Vec::new(),
+            start: time!(01:00),
+            end: time!(07:00),
+        }])
+        .with_utc_offset(offset!(+8));
+        assert!(schedule.is_open(datetime!(2024-01-01 18:00 UTC)));
+        assert!(!schedule.is_open(datetime!(2024-01-01 01:00 UTC)));
+    }
+
+    #[test]
+    fn test_next_boundary() {
+        assert_eq!(
+            DownloadSchedule::default().next_boundary(datetime!(2024-01-01 12:00 UTC)),
+            None
+        );
+
+        let schedule = DownloadSchedule::new(vec![DownloadWindow {
+            days: vec![Weekday::Friday],
+            start: time!(22:00),
+            end: time!(06:00),
+        }])
+        .with_utc_offset(offset!(+8));
+        // 2024-01-01 is a Monday
+        assert_eq!(
+            schedule.next_boundary(datetime!(2024-01-01 12:00 UTC)),
+            Some(datetime!(2024-01-05 22:00 +8))
+        );
+        assert_eq!(
+            schedule.next_boundary(datetime!(2024-01-05 14:00 UTC)),
+            Some(datetime!(2024-01-06 06:00 +8))
+        );
+        assert_eq!(
+            schedule.next_boundary(datetime!(2024-01-05 22:00 UTC)),
+            Some(datetime!(2024-01-12 22:00 +8))
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use std::{fmt, path::PathBuf, time::Duration};
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use utoipa::ToSchema;
 
-use crate::model::Priority;
+use crate::model::{BasicAuth, Checksum, HttpHeader, Priority, Proxy, TaskHooks};
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
 pub enum TaskState {
@@ -16,6 +16,22 @@ pub enum TaskState {
     Failed,
 }
 
+impl TaskState {
+    /// State of a group of tasks, the group is downloading as long as any of
+    /// its tasks is active and is completed once all of them are completed
+    #[must_use]
+    pub fn aggregate<I>(states: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let states = states.into_iter().collect::<Vec<_>>();
+        [Self::Downloading, Self::Pending, Self::Paused, Self::Failed, Self::Canceled]
+            .into_iter()
+            .find(|state| states.contains(state))
+            .unwrap_or(Self::Completed)
+    }
+}
+
 impl fmt::Display for TaskState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -30,12 +46,93 @@ impl fmt::Display for TaskState {
     }
 }
 
+/// What to do when a task is resumed and the remote content has been changed
+/// since the download started
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub enum RemoteChangePolicy {
+    /// Discard the downloaded data and download the content from the
+    /// beginning
+    #[default]
+    Restart,
+
+    Fail,
+}
+
+impl FromStr for RemoteChangePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "restart" => Ok(Self::Restart),
+            "fail" => Ok(Self::Fail),
+            _ => Err(format!("unknown policy `{s}`, expected \"restart\" or \"fail\"")),
+        }
+    }
+}
+
+impl fmt::Display for RemoteChangePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Restart => "Restart",
+            Self::Fail => "Fail",
+        };
+        f.write_str(s)
+    }
+}
+
+/// How the number of connections of a task is changed by the connection tuner
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub enum ConnectionTuningAction {
+    Increase,
+
+    Decrease,
+
+    /// A connection is closed as the server asks to slow down with `429 Too
+    /// Many Requests` or `503 Service Unavailable`
+    BackOff,
+}
+
+impl fmt::Display for ConnectionTuningAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Increase => "Increase",
+            Self::Decrease => "Decrease",
+            Self::BackOff => "BackOff",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct ConnectionTuningDecision {
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, example = OffsetDateTime::now_utc)]
+    pub timestamp: OffsetDateTime,
+
+    pub action: ConnectionTuningAction,
+
+    /// Number of connections after the decision
+    #[schema(value_type = u32, example = 6)]
+    pub concurrent_number: usize,
+
+    /// Transfer rate of all connections in bytes per second when the decision
+    /// is made
+    #[schema(value_type = u64, example = 1_048_576)]
+    pub speed: u64,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
 pub struct CreateTask {
     #[schema(value_type = String, example = "https://httpbin.org/ip")]
     #[serde(with = "crate::serde::uri")]
     pub uri: http::Uri,
 
+    /// Other URIs serving the same content as `uri`, possibly of different
+    /// schemes
+    #[schema(value_type = Vec<String>, example = json!(["https://mirror.example.com/ip"]))]
+    #[serde(default, with = "crate::serde::uris")]
+    pub mirrors: Vec<http::Uri>,
+
     #[schema(value_type = Option<String>, example = "ip.json")]
     pub filename: Option<PathBuf>,
 
@@ -52,6 +149,64 @@ pub struct CreateTask {
 
     #[schema(default, value_type = String, example = OffsetDateTime::now_utc)]
     pub creation_timestamp: OffsetDateTime,
+
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "sha256=e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")]
+    pub checksum: Option<Checksum>,
+
+    #[serde(default)]
+    pub headers: Vec<HttpHeader>,
+
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuth>,
+
+    /// Override the proxy of the downloader factory
+    #[serde(default)]
+    pub proxy: Option<Proxy>,
+
+    /// Expected length of the content, the task fails if the remote file
+    /// differs
+    #[schema(value_type = Option<u64>, example = "null")]
+    #[serde(default)]
+    pub content_length: Option<u64>,
+
+    /// The task is not started before this moment
+    #[schema(value_type = Option<String>, example = "2024-01-01T22:00:00Z")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub not_before: Option<OffsetDateTime>,
+
+    /// Run after the task is completed or failed, in addition to the hooks of
+    /// the daemon
+    #[serde(default)]
+    pub hooks: TaskHooks,
+
+    #[serde(default)]
+    pub on_remote_change: RemoteChangePolicy,
+}
+
+impl CreateTask {
+    /// A task of normal priority created now, the other options are unset
+    #[must_use]
+    pub fn new(uri: http::Uri) -> Self {
+        Self {
+            uri,
+            mirrors: Vec::new(),
+            filename: None,
+            output_directory: None,
+            concurrent_number: None,
+            connection_timeout: None,
+            priority: Priority::Normal,
+            creation_timestamp: OffsetDateTime::now_utc(),
+            checksum: None,
+            headers: Vec::new(),
+            basic_auth: None,
+            proxy: None,
+            content_length: None,
+            not_before: None,
+            hooks: TaskHooks::default(),
+            on_remote_change: RemoteChangePolicy::default(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
@@ -77,6 +232,64 @@ pub struct TaskStatus {
     #[serde(with = "time::serde::rfc3339")]
     #[schema(default, value_type = String, example = OffsetDateTime::now_utc)]
     pub creation_timestamp: OffsetDateTime,
+
+    #[schema(value_type = u32, example = 1)]
+    pub attempts: usize,
+
+    #[schema(value_type = Option<String>, example = "Connection timed out")]
+    pub last_error: Option<String>,
+
+    /// The latest changes of the number of connections made by the connection
+    /// tuner
+    #[serde(default)]
+    pub connection_tuning_log: Vec<ConnectionTuningDecision>,
+
+    /// Whether the completed file is being uploaded to other peers
+    #[serde(default)]
+    pub seeding: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct CreateTaskGroup {
+    /// Name of the group, the ID of the group is used if it is empty
+    #[schema(value_type = String, example = "release-sync")]
+    #[serde(default)]
+    pub name: String,
+
+    pub tasks: Vec<CreateTask>,
+}
+
+/// Tasks which are managed as one unit, e.g. the files of a directory
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct TaskGroupStatus {
+    #[schema(value_type = u64, example = 3)]
+    pub id: u64,
+
+    #[schema(value_type = String, example = "release-sync")]
+    pub name: String,
+
+    pub state: TaskState,
+
+    pub tasks: Vec<TaskStatus>,
+}
+
+impl TaskGroupStatus {
+    #[must_use]
+    pub fn new(id: u64, name: String, tasks: Vec<TaskStatus>) -> Self {
+        let state = TaskState::aggregate(tasks.iter().map(|task| task.state));
+        Self { id, name, state, tasks }
+    }
+
+    #[must_use]
+    pub fn received_bytes(&self) -> u64 {
+        self.tasks
+            .iter()
+            .map(|task| task.chunks.iter().map(|chunk| chunk.received).sum::<u64>())
+            .sum()
+    }
+
+    #[must_use]
+    pub fn content_length(&self) -> u64 { self.tasks.iter().map(|task| task.content_length).sum() }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize, ToSchema)]
@@ -109,3 +322,16 @@ impl ProgressChunk {
     #[must_use]
     pub const fn is_completed(&self) -> bool { self.is_completed }
 }
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct TaskPriority {
+    pub priority: Priority,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ToSchema)]
+pub struct SpeedLimit {
+    /// Maximum transfer rate in bytes per second, `null` or `0` for unlimited
+    #[schema(value_type = Option<u64>, example = 1_048_576)]
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
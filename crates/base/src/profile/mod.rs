@@ -1,2 +1,3 @@
 pub mod minio;
+pub mod s3;
 pub mod ssh;
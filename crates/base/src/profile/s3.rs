@@ -0,0 +1,27 @@
+#[derive(Clone, Debug, Default)]
+pub struct S3Profile {
+    pub region: Option<String>,
+
+    pub endpoint_url: Option<http::Uri>,
+
+    pub force_path_style: bool,
+
+    pub access_key: Option<String>,
+
+    pub secret_key: Option<String>,
+
+    pub session_token: Option<String>,
+
+    /// Send unsigned requests if no credentials are found, requests fail
+    /// otherwise
+    pub anonymous: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct S3Path {
+    pub profile: Option<String>,
+
+    pub bucket: String,
+
+    pub key: String,
+}
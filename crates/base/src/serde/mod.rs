@@ -1,2 +1,6 @@
 pub mod mime;
+pub mod option_uri;
+pub mod time_of_day;
 pub mod uri;
+pub mod uris;
+pub mod weekdays;
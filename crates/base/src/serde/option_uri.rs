@@ -0,0 +1,26 @@
+use serde::{
+    de,
+    de::{Deserialize, Deserializer},
+    ser::Serializer,
+};
+
+/// # Errors
+pub fn serialize<S>(uri: &Option<http::Uri>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match uri {
+        Some(uri) => s.serialize_some(uri.to_string().as_str()),
+        None => s.serialize_none(),
+    }
+}
+
+/// # Errors
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<http::Uri>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| crate::utils::parse_uri(s.as_str()).map_err(de::Error::custom))
+        .transpose()
+}
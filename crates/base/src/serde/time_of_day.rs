@@ -0,0 +1,26 @@
+use serde::{
+    de,
+    de::{Deserialize, Deserializer},
+    ser::Serializer,
+};
+use time::{Time, macros::format_description};
+
+const FORMAT: &[time::format_description::FormatItem<'static>] =
+    format_description!("[hour]:[minute]");
+
+/// # Errors
+pub fn serialize<S>(time: &Time, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(time.format(FORMAT).map_err(serde::ser::Error::custom)?.as_str())
+}
+
+/// # Errors
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Time, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Time::parse(s.trim(), FORMAT).map_err(de::Error::custom)
+}
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use serde::{
     de,
     de::{Deserialize, Deserializer},
@@ -20,5 +18,5 @@ where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    http::Uri::from_str(s.as_str()).map_err(de::Error::custom)
+    crate::utils::parse_uri(s.as_str()).map_err(de::Error::custom)
 }
@@ -0,0 +1,28 @@
+use serde::{
+    de,
+    de::{Deserialize, Deserializer},
+    ser::{SerializeSeq, Serializer},
+};
+
+/// # Errors
+pub fn serialize<S>(uris: &[http::Uri], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = s.serialize_seq(Some(uris.len()))?;
+    for uri in uris {
+        seq.serialize_element(uri.to_string().as_str())?;
+    }
+    seq.end()
+}
+
+/// # Errors
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<http::Uri>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| crate::utils::parse_uri(s.as_str()).map_err(de::Error::custom))
+        .collect()
+}
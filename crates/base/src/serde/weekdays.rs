@@ -0,0 +1,47 @@
+use serde::{
+    de,
+    de::{Deserialize, Deserializer},
+    ser::{SerializeSeq, Serializer},
+};
+use time::Weekday;
+
+const NAMES: [(Weekday, &str); 7] = [
+    (Weekday::Monday, "mon"),
+    (Weekday::Tuesday, "tue"),
+    (Weekday::Wednesday, "wed"),
+    (Weekday::Thursday, "thu"),
+    (Weekday::Friday, "fri"),
+    (Weekday::Saturday, "sat"),
+    (Weekday::Sunday, "sun"),
+];
+
+/// # Errors
+pub fn serialize<S>(days: &[Weekday], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = s.serialize_seq(Some(days.len()))?;
+    for day in days {
+        let name = NAMES.iter().find(|(d, _)| d == day).map_or("", |(_, name)| name);
+        seq.serialize_element(name)?;
+    }
+    seq.end()
+}
+
+/// # Errors
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Weekday>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| {
+            let s = s.trim().to_lowercase();
+            NAMES
+                .iter()
+                .find(|(day, name)| s == *name || s == day.to_string().to_lowercase())
+                .map(|(day, _)| *day)
+                .ok_or_else(|| de::Error::custom(format!("unknown day of week `{s}`")))
+        })
+        .collect()
+}
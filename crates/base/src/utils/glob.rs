@@ -0,0 +1,90 @@
+/// Match a slash-separated `path` against a glob `pattern`
+///
+/// `*` matches any characters except `/`, `**` matches any number of
+/// directories, `?` matches a single character and `[a-z]` or `[!a-z]` matches
+/// a character class. A pattern without `/` is matched against the file name
+/// only, e.g. `*.iso` matches `images/debian.iso`.
+#[must_use]
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.contains('/') {
+        let pattern = pattern.split('/').collect::<Vec<_>>();
+        let path = path.split('/').collect::<Vec<_>>();
+        match_segments(&pattern, &path)
+    } else {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        pattern == "**" || match_segment(pattern.as_bytes(), file_name.as_bytes())
+    }
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, path)| {
+            match_segment(first.as_bytes(), segment.as_bytes()) && match_segments(rest, path)
+        }),
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => name.split_first().is_some_and(|(_, name)| match_segment(rest, name)),
+        Some((b'[', rest)) => {
+            let Some(end) = rest.iter().skip(1).position(|&c| c == b']').map(|pos| pos + 1) else {
+                return name.first() == Some(&b'[') && match_segment(rest, &name[1..]);
+            };
+            name.split_first().is_some_and(|(&c, name)| {
+                match_class(&rest[..end], c) && match_segment(&rest[end + 1..], name)
+            })
+        }
+        Some((&c, rest)) => {
+            name.split_first().is_some_and(|(&n, name)| n == c && match_segment(rest, name))
+        }
+    }
+}
+
+fn match_class(class: &[u8], c: u8) -> bool {
+    let (negated, class) = match class.split_first() {
+        Some((b'!' | b'^', class)) => (true, class),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == b'-' {
+            matched |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= class[i] == c;
+            i += 1;
+        }
+    }
+    matched != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.iso", "debian.iso"));
+        assert!(glob_match("*.iso", "images/amd64/debian.iso"));
+        assert!(!glob_match("*.iso", "debian.iso.sig"));
+        assert!(glob_match("images/*.iso", "images/debian.iso"));
+        assert!(!glob_match("images/*.iso", "images/amd64/debian.iso"));
+        assert!(glob_match("images/**/*.iso", "images/debian.iso"));
+        assert!(glob_match("images/**/*.iso", "images/amd64/netinst/debian.iso"));
+        assert!(glob_match("**/tmp/**", "a/tmp/b/c.txt"));
+        assert!(glob_match("file-?.txt", "file-1.txt"));
+        assert!(!glob_match("file-?.txt", "file-10.txt"));
+        assert!(glob_match("file-[0-9].txt", "file-7.txt"));
+        assert!(!glob_match("file-[!0-9].txt", "file-7.txt"));
+        assert!(glob_match("file-[!0-9].txt", "file-a.txt"));
+        assert!(glob_match("[]].txt", "].txt"));
+    }
+}
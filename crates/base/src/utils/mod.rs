@@ -1,3 +1,5 @@
+mod glob;
 mod retry_interval;
+mod uri;
 
-pub use self::retry_interval::RetryInterval;
+pub use self::{glob::glob_match, retry_interval::RetryInterval, uri::parse_uri};
@@ -8,6 +8,10 @@ pub struct RetryInterval {
 }
 
 impl RetryInterval {
+    pub const RECOMMENDED_INTERVAL: Duration = Duration::from_secs(3);
+    pub const RECOMMENDED_MAX_ATTEMPTS: usize = 5;
+    pub const RECOMMENDED_PHASES: [(usize, Duration); 1] = [(2, Duration::from_secs(1))];
+
     #[must_use]
     pub fn new(max_try_count: usize, fallback_interval: Duration) -> Self {
         Self {
@@ -23,6 +27,17 @@ impl RetryInterval {
         self
     }
 
+    /// The retry policy of downloaders and tasks unless one is configured
+    #[must_use]
+    pub fn recommended() -> Self {
+        Self::RECOMMENDED_PHASES.into_iter().fold(
+            Self::new(Self::RECOMMENDED_MAX_ATTEMPTS, Self::RECOMMENDED_INTERVAL),
+            |retry_interval, (upper_bound, interval)| {
+                retry_interval.add_phase(upper_bound, interval)
+            },
+        )
+    }
+
     pub const fn reset(&mut self) { self.count = 0; }
 
     #[must_use]
@@ -0,0 +1,67 @@
+use std::{fmt::Write, str::FromStr};
+
+/// Parse a URI, magnet links (`magnet:?xt=urn:btih:<info hash>&...`) are
+/// rewritten into `magnet://<hex info hash>/?xt=urn:btih:<info hash>&...`
+/// because `http::Uri` requires an authority
+///
+/// # Errors
+pub fn parse_uri(s: &str) -> Result<http::Uri, http::uri::InvalidUri> {
+    let s = s.trim();
+    let Some(query) = s.strip_prefix("magnet:?") else {
+        return http::Uri::from_str(s);
+    };
+    // an invalid info hash is left for the fetcher to report
+    let info_hash = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("xt=urn:btih:"))
+        .and_then(info_hash_to_hex)
+        .unwrap_or_default();
+    http::Uri::from_str(&format!("magnet://{info_hash}/?{query}"))
+}
+
+// the info hash of a magnet link is encoded in either hex or base32
+fn info_hash_to_hex(info_hash: &str) -> Option<String> {
+    match info_hash.len() {
+        40 if info_hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(info_hash.to_lowercase()),
+        32 => {
+            let mut bits = 0_u64;
+            let mut bit_count = 0;
+            let mut hex = String::with_capacity(40);
+            for c in info_hash.chars() {
+                let value = match c.to_ascii_uppercase() {
+                    c @ 'A'..='Z' => u64::from(c) - u64::from('A'),
+                    c @ '2'..='7' => u64::from(c) - u64::from('2') + 26,
+                    _ => return None,
+                };
+                bits = (bits << 5) | value;
+                bit_count += 5;
+                if bit_count >= 8 {
+                    bit_count -= 8;
+                    let _ = write!(hex, "{:02x}", (bits >> bit_count) & 0xff);
+                }
+            }
+            Some(hex)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_uri;
+
+    #[test]
+    fn test_parse_magnet_uri() {
+        let uri =
+            parse_uri("magnet:?xt=urn:btih:C9E15763F722F23E98A29DECDFAE341B98D53056&dn=a").unwrap();
+        assert_eq!(uri.scheme_str(), Some("magnet"));
+        assert_eq!(uri.host(), Some("c9e15763f722f23e98a29decdfae341b98d53056"));
+        assert_eq!(uri.query(), Some("xt=urn:btih:C9E15763F722F23E98A29DECDFAE341B98D53056&dn=a"));
+
+        let uri = parse_uri("magnet:?xt=urn:btih:ZHQVOY7XELZD5GFCTXWN7LRUDOMNKMCW").unwrap();
+        assert_eq!(uri.host(), Some("c9e15763f722f23e98a29decdfae341b98d53056"));
+
+        let uri = parse_uri("https://www.example.com/file").unwrap();
+        assert_eq!(uri.host(), Some("www.example.com"));
+    }
+}
@@ -1,12 +1,71 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
+use caracal_base::model;
 use serde::{Deserialize, Serialize};
 
+use crate::config::RetryConfig;
+
 #[derive(Clone, Debug, Deserialize, Default, Serialize)]
 pub struct DownloaderConfig {
     pub http: HttpConfig,
 
     pub default_output_directory: Option<PathBuf>,
+
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    #[serde(default)]
+    pub torrent: TorrentConfig,
+
+    /// Check the data downloaded before a task is resumed, the corrupted
+    /// chunks are downloaded again
+    #[serde(default)]
+    pub verify_on_resume: bool,
+
+    #[serde(default)]
+    pub connection_tuning: ConnectionTuningConfig,
+}
+
+/// Adjust the number of connections of each task to the measured transfer rate
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConnectionTuningConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default = "ConnectionTuningConfig::default_min_connections")]
+    pub min_connections: u16,
+
+    #[serde(default = "ConnectionTuningConfig::default_max_connections")]
+    pub max_connections: u16,
+
+    /// Seconds to measure the transfer rate before each decision
+    #[serde(default = "ConnectionTuningConfig::default_interval")]
+    pub interval: u64,
+}
+
+impl Default for ConnectionTuningConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            min_connections: Self::default_min_connections(),
+            max_connections: Self::default_max_connections(),
+            interval: Self::default_interval(),
+        }
+    }
+}
+
+impl ConnectionTuningConfig {
+    #[must_use]
+    pub const fn default_min_connections() -> u16 { 1 }
+
+    #[must_use]
+    pub const fn default_max_connections() -> u16 { 16 }
+
+    #[must_use]
+    pub const fn default_interval() -> u64 { 5 }
+
+    #[must_use]
+    pub const fn interval(&self) -> Duration { Duration::from_secs(self.interval) }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -16,6 +75,9 @@ pub struct HttpConfig {
 
     #[serde(default = "HttpConfig::default_concurrent_connections")]
     pub concurrent_connections: u16,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<model::Proxy>,
 }
 
 impl Default for HttpConfig {
@@ -23,6 +85,7 @@ impl Default for HttpConfig {
         Self {
             user_agent: Self::default_user_agent(),
             concurrent_connections: Self::default_concurrent_connections(),
+            proxy: None,
         }
     }
 }
@@ -32,3 +95,11 @@ impl HttpConfig {
 
     pub const fn default_concurrent_connections() -> u16 { 5 }
 }
+
+#[derive(Clone, Debug, Deserialize, Default, Serialize)]
+pub struct TorrentConfig {
+    /// Keep seeding a completed torrent until this ratio of uploaded to
+    /// downloaded data is reached, `0` disables seeding
+    #[serde(default)]
+    pub seed_ratio: f64,
+}
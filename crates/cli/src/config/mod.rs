@@ -1,4 +1,9 @@
 mod downloader;
 mod log;
+mod retry;
 
-pub use self::{downloader::DownloaderConfig, log::LogConfig};
+pub use self::{
+    downloader::{ConnectionTuningConfig, DownloaderConfig, TorrentConfig},
+    log::LogConfig,
+    retry::{RetryConfig, RetryPhase},
+};
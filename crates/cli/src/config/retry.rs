@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use caracal_base::utils::RetryInterval;
+use serde::{Deserialize, Serialize};
+use serde_with::{DurationMilliSeconds, serde_as};
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RetryConfig {
+    #[serde(default = "RetryConfig::default_max_attempts")]
+    pub max_attempts: usize,
+
+    #[serde(default = "RetryConfig::default_interval")]
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub interval: Duration,
+
+    #[serde(default = "RetryConfig::default_phases")]
+    pub phases: Vec<RetryPhase>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RetryPhase {
+    pub until_attempt: usize,
+
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    pub interval: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: Self::default_max_attempts(),
+            interval: Self::default_interval(),
+            phases: Self::default_phases(),
+        }
+    }
+}
+
+impl RetryConfig {
+    #[inline]
+    #[must_use]
+    pub const fn default_max_attempts() -> usize { RetryInterval::RECOMMENDED_MAX_ATTEMPTS }
+
+    #[inline]
+    #[must_use]
+    pub const fn default_interval() -> Duration { RetryInterval::RECOMMENDED_INTERVAL }
+
+    #[inline]
+    #[must_use]
+    pub fn default_phases() -> Vec<RetryPhase> {
+        RetryInterval::RECOMMENDED_PHASES
+            .into_iter()
+            .map(|(until_attempt, interval)| RetryPhase { until_attempt, interval })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn retry_interval(&self) -> RetryInterval {
+        self.phases.iter().fold(
+            RetryInterval::new(self.max_attempts, self.interval),
+            |retry_interval, RetryPhase { until_attempt, interval }| {
+                retry_interval.add_phase(*until_attempt, *interval)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use caracal_base::utils::RetryInterval;
+
+    use super::{RetryConfig, RetryPhase};
+
+    #[test]
+    fn test_default_retry_interval() {
+        assert_eq!(
+            RetryConfig::default().retry_interval().collect::<Vec<_>>(),
+            RetryInterval::recommended().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_retry_interval() {
+        let config = RetryConfig {
+            max_attempts: 4,
+            interval: Duration::from_secs(3),
+            phases: vec![RetryPhase { until_attempt: 2, interval: Duration::from_secs(1) }],
+        };
+        assert_eq!(
+            config.retry_interval().collect::<Vec<_>>(),
+            [1, 1, 3, 3].map(Duration::from_secs).to_vec()
+        );
+    }
+}
@@ -48,6 +48,17 @@ impl Profile {
             secret_key: "secret_key".to_string(),
         });
 
+        let s3 = ProfileItem::S3(S3 {
+            name: "s3-example".to_string(),
+            region: Some("us-east-1".to_string()),
+            endpoint_url: None,
+            force_path_style: false,
+            access_key: Some("access_key".to_string()),
+            secret_key: Some("secret_key".to_string()),
+            session_token: None,
+            anonymous: false,
+        });
+
         let ssh = ProfileItem::Ssh(Ssh {
             name: "ssh-example".to_string(),
             endpoint: "www.example.com".to_string(),
@@ -55,7 +66,7 @@ impl Profile {
             identity_file: PathBuf::from("/path/to/identity/file"),
         });
 
-        Self { profiles: vec![minio, ssh] }
+        Self { profiles: vec![minio, s3, ssh] }
     }
 }
 
@@ -64,6 +75,8 @@ pub enum ProfileItem {
     #[serde(rename = "MinIO")]
     Minio(Minio),
 
+    S3(S3),
+
     #[serde(rename = "SSH")]
     Ssh(Ssh),
 }
@@ -80,6 +93,35 @@ pub struct Minio {
     pub secret_key: String,
 }
 
+/// Unset credentials are loaded from the environment variables and the shared
+/// credentials file of AWS
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct S3 {
+    pub name: String,
+
+    #[serde(default)]
+    pub region: Option<String>,
+
+    #[serde(default, with = "caracal_base::serde::option_uri")]
+    pub endpoint_url: Option<http::Uri>,
+
+    #[serde(default)]
+    pub force_path_style: bool,
+
+    #[serde(default)]
+    pub access_key: Option<String>,
+
+    #[serde(default)]
+    pub secret_key: Option<String>,
+
+    #[serde(default)]
+    pub session_token: Option<String>,
+
+    /// Send unsigned requests if no credentials are found, for public buckets
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Ssh {
     pub name: String,
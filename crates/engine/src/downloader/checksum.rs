@@ -0,0 +1,99 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use caracal_base::model;
+use sha2::Digest;
+use snafu::ResultExt;
+
+use crate::{error, error::Error};
+
+/// # Errors
+pub async fn verify<P>(file_path: P, checksum: &model::Checksum) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let file_path = file_path.as_ref().to_path_buf();
+    tracing::info!("Verifying {} checksum of `{}`", checksum.algorithm(), file_path.display());
+
+    let algorithm = checksum.algorithm();
+    let actual = tokio::task::spawn_blocking({
+        let file_path = file_path.clone();
+        move || compute(&file_path, algorithm)
+    })
+    .await
+    .context(error::JoinTaskSnafu)?
+    .with_context(|_| error::ComputeChecksumSnafu { file_path: file_path.clone() })?;
+
+    if actual == checksum.digest() {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch {
+            file_path,
+            algorithm,
+            expected: checksum.digest().to_string(),
+            actual,
+        })
+    }
+}
+
+fn compute(file_path: &Path, algorithm: model::ChecksumAlgorithm) -> io::Result<String> {
+    let reader = BufReader::new(File::open(file_path)?);
+    match algorithm {
+        model::ChecksumAlgorithm::Sha256 => digest::<sha2::Sha256, _>(reader),
+        model::ChecksumAlgorithm::Sha512 => digest::<sha2::Sha512, _>(reader),
+        model::ChecksumAlgorithm::Sha1 => digest::<sha1::Sha1, _>(reader),
+        model::ChecksumAlgorithm::Md5 => digest::<md5::Md5, _>(reader),
+        model::ChecksumAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            let _ = io::copy(&mut { reader }, &mut hasher)?;
+            Ok(hex::encode(hasher.finalize().as_bytes()))
+        }
+    }
+}
+
+fn digest<D, R>(mut reader: R) -> io::Result<String>
+where
+    D: Digest + io::Write,
+    R: Read,
+{
+    let mut hasher = D::new();
+    let _ = io::copy(&mut reader, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use caracal_base::model;
+
+    use super::verify;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_verify() {
+        let file_path =
+            std::env::temp_dir().join(format!("caracal-test-checksum-{}", std::process::id()));
+        tokio::fs::write(&file_path, b"").await.unwrap();
+
+        for checksum in [
+            "sha256=e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "sha512=cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            "sha1=da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            "md5=d41d8cd98f00b204e9800998ecf8427e",
+            "blake3=af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        ] {
+            let checksum = checksum.parse::<model::Checksum>().unwrap();
+            assert!(verify(&file_path, &checksum).await.is_ok(), "{checksum}");
+        }
+
+        let checksum = "md5=00000000000000000000000000000000".parse::<model::Checksum>().unwrap();
+        assert!(matches!(
+            verify(&file_path, &checksum).await,
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        drop(tokio::fs::remove_file(&file_path).await);
+    }
+}
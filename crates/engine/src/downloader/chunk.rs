@@ -29,18 +29,29 @@ impl Chunk {
             if len <= MINIMUM_CHUNK_SIZE {
                 None
             } else {
+                // the bytes which are received are kept by the origin chunk
                 let new_chunk = Self {
-                    start: self.start + len,
+                    start: self.start + self.received + len,
                     end: self.end,
                     received: 0,
                     is_completed: false,
                 };
-                self.end = self.start + len - 1;
+                self.end = new_chunk.start - 1;
                 Some(new_chunk)
             }
         }
     }
 
+    /// Move the end of the chunk backward but not before the next byte to
+    /// receive, `false` is returned if the event is for another chunk
+    pub fn shrink(&mut self, start: u64, end: u64, received: u64) -> bool {
+        if self.start != start {
+            return false;
+        }
+        self.end = self.end.min(end.max(self.start + received));
+        true
+    }
+
     pub const fn freeze(&mut self) -> Option<Self> {
         if self.received == 0 {
             None
@@ -95,6 +106,34 @@ mod tests {
         assert_eq!(len, origin_chunk.len() + new_chunk.len());
         assert_eq!(500 * KB, origin_chunk.len());
         assert_eq!(500 * KB + 1, new_chunk.len());
+
+        let len = 2048 * KB;
+        let received = 1024 * KB;
+        let mut origin_chunk = Chunk { start: 0, end: len - 1, received, is_completed: false };
+        let new_chunk = origin_chunk.split().unwrap();
+        assert_eq!(
+            new_chunk,
+            Chunk { start: 1536 * KB, end: len - 1, received: 0, is_completed: false }
+        );
+        assert_eq!(len, origin_chunk.len() + new_chunk.len());
+        assert_eq!(512 * KB, origin_chunk.remaining());
+        assert_eq!(512 * KB, new_chunk.len());
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut chunk = Chunk { start: 100, end: 999, received: 0, is_completed: false };
+        assert!(!chunk.shrink(0, 499, 0));
+        assert_eq!(chunk.end, 999);
+
+        assert!(chunk.shrink(100, 499, 200));
+        assert_eq!(chunk.end, 499);
+
+        // the worker has already passed the requested end
+        assert!(chunk.shrink(100, 299, 300));
+        assert_eq!(chunk.end, 400);
+        assert!(chunk.shrink(100, 899, 0));
+        assert_eq!(chunk.end, 400);
     }
 
     #[test]
@@ -0,0 +1,240 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use caracal_base::model;
+use time::OffsetDateTime;
+
+// a new connection is kept if it adds at least this ratio of the speed of a
+// connection
+const MINIMUM_MARGINAL_GAIN: f64 = 0.5;
+
+// intervals to wait before adding connections again
+const COOLDOWN_INTERVALS: u32 = 3;
+const BACK_OFF_INTERVALS: u32 = 6;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConnectionTuning {
+    pub min_connections: u64,
+
+    pub max_connections: u64,
+
+    // how long the speed is measured before each decision
+    pub interval: Duration,
+}
+
+impl ConnectionTuning {
+    #[must_use]
+    pub fn clamp(&self, concurrent_number: u64) -> u64 {
+        let min_connections = self.min_connections.max(1);
+        concurrent_number.clamp(min_connections, self.max_connections.max(min_connections))
+    }
+}
+
+/// Adds connections while they increase the total transfer rate and removes
+/// them when they do not or when the server asks to slow down
+#[derive(Debug)]
+pub struct ConnectionTuner {
+    config: ConnectionTuning,
+
+    // bytes received by each worker since the last decision
+    received: HashMap<u64, u64>,
+
+    measured_at: Instant,
+
+    state: State,
+
+    // the state after the last decision, it takes effect once the decision is
+    // applied
+    pending: Option<State>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct State {
+    is_throttled: bool,
+
+    // total and per-connection speed measured before the last increase
+    probe: Option<(f64, f64)>,
+
+    // the interval after an increase is skipped as the new connection is
+    // still being established
+    is_warming_up: bool,
+
+    cooldown: u32,
+}
+
+impl ConnectionTuner {
+    pub fn new(config: ConnectionTuning) -> Self {
+        Self {
+            config,
+            received: HashMap::new(),
+            measured_at: Instant::now(),
+            state: State::default(),
+            pending: None,
+        }
+    }
+
+    pub const fn interval(&self) -> Duration { self.config.interval }
+
+    pub fn record(&mut self, worker_id: u64, bytes: u64) {
+        *self.received.entry(worker_id).or_default() += bytes;
+    }
+
+    pub const fn throttle(&mut self) { self.state.is_throttled = true; }
+
+    /// Measure the speed since the last call and decide how to change the
+    /// number of connections, the decision is only taken into account after
+    /// `apply` is called
+    pub fn tune(&mut self, concurrent_number: u64) -> Option<model::ConnectionTuningDecision> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.measured_at);
+        self.measured_at = now;
+        self.decide(concurrent_number, elapsed)
+    }
+
+    /// Report that the last decision has been carried out
+    pub const fn apply(&mut self) {
+        if let Some(state) = self.pending.take() {
+            self.state = state;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn decide(
+        &mut self,
+        concurrent_number: u64,
+        elapsed: Duration,
+    ) -> Option<model::ConnectionTuningDecision> {
+        let received = self.received.values().sum::<u64>();
+        let active_workers = self.received.values().filter(|&&bytes| bytes > 0).count();
+        self.received.clear();
+        let speed = received as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        let min_connections = self.config.clamp(0);
+        let max_connections = self.config.clamp(u64::MAX);
+
+        let mut state = self.state;
+        self.pending = None;
+        let action = if std::mem::take(&mut state.is_throttled) {
+            state.probe = None;
+            state.is_warming_up = false;
+            state.cooldown = BACK_OFF_INTERVALS;
+            (concurrent_number > min_connections).then_some(model::ConnectionTuningAction::BackOff)
+        } else if concurrent_number < min_connections {
+            Some(model::ConnectionTuningAction::Increase)
+        } else if concurrent_number > max_connections {
+            Some(model::ConnectionTuningAction::Decrease)
+        } else if std::mem::take(&mut state.is_warming_up) {
+            None
+        } else if let Some((previous_speed, connection_speed)) = state.probe.take() {
+            if speed - previous_speed < connection_speed * MINIMUM_MARGINAL_GAIN {
+                state.cooldown = COOLDOWN_INTERVALS;
+                Some(model::ConnectionTuningAction::Decrease)
+            } else {
+                self.probe_increase(&mut state, concurrent_number, speed, active_workers)
+            }
+        } else if state.cooldown > 0 {
+            state.cooldown -= 1;
+            None
+        } else {
+            self.probe_increase(&mut state, concurrent_number, speed, active_workers)
+        };
+        let Some(action) = action else {
+            self.state = state;
+            return None;
+        };
+        self.pending = Some(state);
+
+        let concurrent_number = match action {
+            model::ConnectionTuningAction::Increase => concurrent_number + 1,
+            model::ConnectionTuningAction::Decrease | model::ConnectionTuningAction::BackOff => {
+                concurrent_number - 1
+            }
+        };
+        Some(model::ConnectionTuningDecision {
+            timestamp: OffsetDateTime::now_utc(),
+            action,
+            concurrent_number: usize::try_from(concurrent_number).unwrap_or(usize::MAX),
+            speed: speed as u64,
+        })
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn probe_increase(
+        &self,
+        state: &mut State,
+        concurrent_number: u64,
+        speed: f64,
+        active_workers: usize,
+    ) -> Option<model::ConnectionTuningAction> {
+        // nothing is learned from idle workers
+        if concurrent_number >= self.config.clamp(u64::MAX) || active_workers == 0 {
+            return None;
+        }
+        state.probe = Some((speed, speed / active_workers as f64));
+        state.is_warming_up = true;
+        Some(model::ConnectionTuningAction::Increase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use caracal_base::model::ConnectionTuningAction;
+
+    use super::{ConnectionTuner, ConnectionTuning};
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn decide(
+        tuner: &mut ConnectionTuner,
+        concurrent_number: u64,
+        speeds: &[u64],
+    ) -> Option<ConnectionTuningAction> {
+        for (worker_id, &speed) in (0..).zip(speeds) {
+            tuner.record(worker_id, speed);
+        }
+        let action = tuner.decide(concurrent_number, SECOND).map(|decision| decision.action);
+        tuner.apply();
+        action
+    }
+
+    #[test]
+    fn test_decide() {
+        let config = ConnectionTuning { min_connections: 2, max_connections: 4, interval: SECOND };
+        let mut tuner = ConnectionTuner::new(config);
+
+        assert_eq!(decide(&mut tuner, 2, &[100, 100]), Some(ConnectionTuningAction::Increase));
+        // the new connection is still being established
+        assert_eq!(decide(&mut tuner, 3, &[100, 100, 0]), None);
+        // the new connection adds enough speed
+        assert_eq!(decide(&mut tuner, 3, &[100, 100, 100]), Some(ConnectionTuningAction::Increase));
+        assert_eq!(decide(&mut tuner, 4, &[100, 100, 100, 10]), None);
+        // the new connection only takes the bandwidth of the others
+        assert_eq!(
+            decide(&mut tuner, 4, &[75, 75, 75, 75]),
+            Some(ConnectionTuningAction::Decrease)
+        );
+        for _ in 0..3 {
+            assert_eq!(decide(&mut tuner, 3, &[100, 100, 100]), None);
+        }
+        assert_eq!(decide(&mut tuner, 3, &[100, 100, 100]), Some(ConnectionTuningAction::Increase));
+
+        tuner.throttle();
+        assert_eq!(
+            decide(&mut tuner, 4, &[100, 100, 100, 100]),
+            Some(ConnectionTuningAction::BackOff)
+        );
+        tuner.throttle();
+        assert_eq!(decide(&mut tuner, 2, &[100, 100]), None);
+
+        // the back-off is decided again until it is carried out
+        tuner.throttle();
+        assert!(tuner.decide(3, SECOND).is_some());
+        assert_eq!(decide(&mut tuner, 3, &[100, 100, 100]), Some(ConnectionTuningAction::BackOff));
+        assert_eq!(decide(&mut tuner, 2, &[100, 100]), None);
+        assert_eq!(decide(&mut tuner, 1, &[100]), Some(ConnectionTuningAction::Increase));
+        assert_eq!(decide(&mut tuner, 5, &[100]), Some(ConnectionTuningAction::Decrease));
+    }
+}
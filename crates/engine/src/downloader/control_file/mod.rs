@@ -1,95 +1,389 @@
 mod v1;
+mod v2;
 
 use std::{
+    collections::HashMap,
     io::SeekFrom,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
+use caracal_base::model;
+use serde::Deserialize;
 use snafu::ResultExt;
 use tokio::{
     fs::File,
-    io::{AsyncSeekExt, AsyncWriteExt},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
 };
 
-use crate::{downloader::TransferStatus, error, error::Error};
+use crate::{
+    downloader::{Chunk, TransferStatus},
+    error,
+    error::Error,
+    fetcher::Fetcher,
+};
 
-pub struct ControlFile {
-    file: File,
+/// Number of bytes read from the data file at a time while computing the
+/// checksums
+const CHECKSUM_BLOCK_SIZE: u64 = 1024 * 1024;
+
+/// Number of bytes at the end of the received part of a chunk compared with
+/// the source if the chunk has no checksum
+const SAMPLE_WINDOW: u64 = 64 * 1024;
 
+pub struct ControlFile {
     file_path: PathBuf,
 
-    uri: http::Uri,
+    data_file_path: PathBuf,
+
+    uris: Vec<http::Uri>,
+
+    fetcher: &'static str,
+
+    etag: Option<String>,
+
+    last_modified: Option<String>,
+
+    // CRC-32 of the received part of the chunks, keyed by the start of the
+    // chunks, a checksum is extended with the data received after it instead
+    // of reading the whole chunk again
+    checksums: Mutex<HashMap<u64, Checksum>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Checksum {
+    // number of bytes from the start of the chunk covered by the checksum
+    len: u64,
+
+    crc32: u32,
 }
 
 impl ControlFile {
+    /// Load the progress of the previous run of the download if there is one,
+    /// the progress is discarded if the remote content has been changed and
+    /// the policy is `Restart`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the control file exists but it
+    /// can not be read or it is corrupted, or the remote content has been
+    /// changed and the policy is `Fail`.
     pub async fn new<P>(
-        file_path: P,
-        uri: http::Uri,
+        data_file_path: P,
+        uris: Vec<http::Uri>,
+        source: &Fetcher,
+        on_remote_change: model::RemoteChangePolicy,
     ) -> Result<(Self, Option<TransferStatus>), Error>
     where
         P: AsRef<Path> + Send,
     {
-        let file_path = Self::file_path(file_path);
-
-        let transfer_status = tokio::fs::read(&file_path).await.map_or(None, |contents| {
-            serde_json::from_slice::<v1::Control>(&contents).ok().map(TransferStatus::from)
-        });
-
-        let file = tokio::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .read(false)
-            .open(&file_path)
-            .await
-            .context(error::CreateControlFileSnafu { file_path: file_path.clone() })?;
+        let data_file_path = data_file_path.as_ref().to_path_buf();
+        let file_path = Self::file_path(&data_file_path);
+        let metadata = source.fetch_metadata();
+        let control = match Self::load(&file_path).await? {
+            Some(control) if control.is_changed(&metadata) => match on_remote_change {
+                model::RemoteChangePolicy::Restart => {
+                    tracing::warn!(
+                        "Remote content of `{}` has been changed, restart the download",
+                        data_file_path.display()
+                    );
+                    None
+                }
+                model::RemoteChangePolicy::Fail => {
+                    return Err(Error::RemoteContentChanged {
+                        uri: uris.first().cloned().unwrap_or_default(),
+                    });
+                }
+            },
+            control => control,
+        };
+        let checksums = control
+            .iter()
+            .flat_map(|control| &control.chunks)
+            .filter_map(|chunk| {
+                let len = chunk.received.min(chunk.end - chunk.start + 1);
+                chunk.crc32.map(|crc32| (chunk.start, Checksum { len, crc32 }))
+            })
+            .collect();
 
-        Ok((Self { file, file_path, uri }, transfer_status))
+        Ok((
+            Self {
+                file_path,
+                data_file_path,
+                uris,
+                fetcher: source.kind(),
+                etag: metadata.etag,
+                last_modified: metadata.last_modified,
+                checksums: Mutex::new(checksums),
+            },
+            control.map(TransferStatus::from),
+        ))
     }
 
-    pub async fn update_progress(&mut self, transfer_status: &TransferStatus) -> Result<(), Error> {
-        let control = v1::Control {
-            schema: 1,
-            uris: vec![self.uri.to_string()],
-            content_length: Some(transfer_status.content_length),
-            chunks: transfer_status.chunks().into_iter().map(Into::into).collect(),
+    async fn load(file_path: &Path) -> Result<Option<v2::Control>, Error> {
+        #[derive(Deserialize)]
+        struct Header {
+            schema: u32,
+        }
+
+        let contents = match tokio::fs::read(file_path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(Error::ReadFile { file_path: file_path.to_path_buf(), source });
+            }
+        };
+        let corrupted = |err: serde_json::Error| Error::CorruptedControlFile {
+            file_path: file_path.to_path_buf(),
+            reason: err.to_string(),
         };
 
-        self.file
-            .set_len(0)
-            .await
-            .with_context(|_| error::ResizeFileSnafu { file_path: self.file_path.clone() })?;
+        let Header { schema } = serde_json::from_slice(&contents).map_err(corrupted)?;
+        let control = match schema {
+            1 => serde_json::from_slice::<v1::Control>(&contents).map(v2::Control::from),
+            v2::SCHEMA => serde_json::from_slice::<v2::Control>(&contents),
+            _ => {
+                return Err(Error::UnsupportedControlFileSchema {
+                    file_path: file_path.to_path_buf(),
+                    schema,
+                });
+            }
+        }
+        .map_err(corrupted)?;
+        control.validate().map_err(|reason| Error::CorruptedControlFile {
+            file_path: file_path.to_path_buf(),
+            reason,
+        })?;
+        Ok(Some(control))
+    }
 
-        let _ = self
-            .file
-            .seek(SeekFrom::Start(0))
+    /// Replace the control file with the progress, the previous progress is
+    /// kept if the process crashes in the middle of the update
+    pub async fn update_progress(&self, transfer_status: &TransferStatus) -> Result<(), Error> {
+        let chunks = self.checksum_chunks(transfer_status.chunks()).await?;
+        self.write(transfer_status.content_length, chunks).await
+    }
+
+    /// Replace the control file with the layout of the chunks while they are
+    /// being transferred, the received data may not be written to the disk
+    /// yet, so only the checksums of the chunks which have not received more
+    /// data since the last update of the progress are kept
+    pub async fn update_layout(&self, transfer_status: &TransferStatus) -> Result<(), Error> {
+        let chunks = {
+            let checksums = self.checksums.lock().expect("lock is not poisoned");
+            transfer_status
+                .chunks()
+                .into_iter()
+                .map(|chunk| {
+                    let len = received_end(&chunk) - chunk.start;
+                    let crc32 = checksums
+                        .get(&chunk.start)
+                        .filter(|checksum| checksum.len == len)
+                        .map(|checksum| checksum.crc32);
+                    let Chunk { start, end, received, is_completed } = chunk;
+                    v2::Chunk { start, end, received, is_completed, crc32 }
+                })
+                .collect()
+        };
+        self.write(transfer_status.content_length, chunks).await
+    }
+
+    async fn write(&self, content_length: u64, chunks: Vec<v2::Chunk>) -> Result<(), Error> {
+        let control = v2::Control {
+            schema: v2::SCHEMA,
+            uris: self.uris.iter().map(ToString::to_string).collect(),
+            fetcher: Some(self.fetcher.to_string()),
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+            content_length,
+            chunks,
+        };
+
+        let temporary_file_path = self.temporary_file_path();
+        let mut file = File::create(&temporary_file_path).await.with_context(|_| {
+            error::CreateControlFileSnafu { file_path: temporary_file_path.clone() }
+        })?;
+        file.write_all(&serde_json::to_vec(&control).expect("Control is serializable; qed"))
+            .await
+            .with_context(|_| error::WriteFileSnafu { file_path: temporary_file_path.clone() })?;
+        file.sync_all()
             .await
-            .with_context(|_| error::SeekFileSnafu { file_path: self.file_path.clone() })?;
+            .with_context(|_| error::FlushFileSnafu { file_path: temporary_file_path.clone() })?;
+        drop(file);
+
+        tokio::fs::rename(&temporary_file_path, &self.file_path).await.with_context(|_| {
+            error::ReplaceControlFileSnafu { file_path: self.file_path.clone() }
+        })?;
+        self.sync_directory().await
+    }
 
-        self.file
-            .write_all(
-                serde_json::to_string(&control).expect("Control is serializable; qed").as_bytes(),
-            )
+    // the rename is only durable once the directory entry is flushed
+    #[cfg(unix)]
+    async fn sync_directory(&self) -> Result<(), Error> {
+        let directory = match self.file_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(directory)
+            .await
+            .with_context(|_| error::OpenFileSnafu { file_path: directory.to_path_buf() })?
+            .sync_all()
             .await
-            .with_context(|_| error::WriteFileSnafu { file_path: self.file_path.clone() })?;
+            .with_context(|_| error::FlushFileSnafu { file_path: directory.to_path_buf() })
+    }
+
+    #[cfg(not(unix))]
+    #[allow(clippy::unused_async)]
+    async fn sync_directory(&self) -> Result<(), Error> { Ok(()) }
+
+    // only the data received after the previous checksum of a chunk is read
+    async fn checksum_chunks(&self, chunks: Vec<Chunk>) -> Result<Vec<v2::Chunk>, Error> {
+        let mut file = self.open_data_file().await?;
+        let mut buffer = Vec::new();
+        let mut checksums = HashMap::with_capacity(chunks.len());
+        let mut checksummed_chunks = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let len = received_end(&chunk) - chunk.start;
+            let crc32 = if len == 0 {
+                None
+            } else {
+                let previous = self
+                    .checksum(chunk.start)
+                    .filter(|checksum| checksum.len <= len)
+                    .unwrap_or(Checksum { len: 0, crc32: 0 });
+                let crc32 = self
+                    .checksum_data(
+                        &mut file,
+                        chunk.start + previous.len,
+                        len - previous.len,
+                        previous.crc32,
+                        &mut buffer,
+                    )
+                    .await?;
+                let _unused = checksums.insert(chunk.start, Checksum { len, crc32 });
+                Some(crc32)
+            };
+            let Chunk { start, end, received, is_completed } = chunk;
+            checksummed_chunks.push(v2::Chunk { start, end, received, is_completed, crc32 });
+        }
+        *self.checksums.lock().expect("lock is not poisoned") = checksums;
+        Ok(checksummed_chunks)
+    }
+
+    fn checksum(&self, chunk_start: u64) -> Option<Checksum> {
+        self.checksums.lock().expect("lock is not poisoned").get(&chunk_start).copied()
+    }
 
+    /// Check the received part of each chunk against its checksum in the
+    /// control file, or the last `SAMPLE_WINDOW` bytes of it against the same
+    /// range of `source` if there is no checksum, the chunks which do not
+    /// match are downloaded again
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data file or the source can
+    /// not be read.
+    pub async fn verify(
+        &self,
+        transfer_status: &mut TransferStatus,
+        source: &mut Fetcher,
+    ) -> Result<(), Error> {
+        // sampling a torrent would download whole pieces from the peers, its
+        // chunks are only checked against their checksums
+        let samples_source = source.supports_range_request() && source.piece_length().is_none();
+        let mut file = self.open_data_file().await?;
+        let mut buffer = Vec::new();
+        for chunk in transfer_status.chunks() {
+            let received_end = received_end(&chunk);
+            if received_end == chunk.start {
+                continue;
+            }
+            let len = received_end - chunk.start;
+            let is_intact = if let Some(checksum) =
+                self.checksum(chunk.start).filter(|checksum| checksum.len == len)
+            {
+                self.checksum_data(&mut file, chunk.start, len, 0, &mut buffer).await?
+                    == checksum.crc32
+            } else if samples_source {
+                let window = (received_end - chunk.start).min(SAMPLE_WINDOW);
+                self.read_data(&mut file, received_end - window, window, &mut buffer).await?;
+                let mut stream =
+                    source.fetch_bytes(received_end - window, received_end - 1).await?;
+                let mut sample = Vec::with_capacity(buffer.len());
+                while sample.len() < buffer.len() {
+                    match stream.bytes().await? {
+                        Some(bytes) => sample.extend_from_slice(bytes),
+                        None => break,
+                    }
+                }
+                sample.truncate(buffer.len());
+                sample == buffer
+            } else {
+                continue;
+            };
+            if !is_intact {
+                tracing::warn!(
+                    "Data of `{}` in range {}-{} is corrupted, download it again",
+                    self.data_file_path.display(),
+                    chunk.start,
+                    chunk.end
+                );
+                transfer_status.reset_progress(chunk.start);
+                let _unused =
+                    self.checksums.lock().expect("lock is not poisoned").remove(&chunk.start);
+            }
+        }
         Ok(())
     }
 
-    pub async fn flush(&mut self) -> Result<(), Error> {
-        self.file
-            .flush()
+    async fn open_data_file(&self) -> Result<File, Error> {
+        File::open(&self.data_file_path)
             .await
-            .with_context(|_| error::FlushFileSnafu { file_path: self.file_path.clone() })?;
-        self.file
-            .sync_all()
+            .with_context(|_| error::OpenFileSnafu { file_path: self.data_file_path.clone() })
+    }
+
+    // extend `crc32` with `len` bytes of the data file from `offset`
+    async fn checksum_data(
+        &self,
+        file: &mut File,
+        offset: u64,
+        len: u64,
+        crc32: u32,
+        buffer: &mut Vec<u8>,
+    ) -> Result<u32, Error> {
+        let mut hasher = crc32fast::Hasher::new_with_initial(crc32);
+        let end = offset + len;
+        let mut position = offset;
+        while position < end {
+            let block_len = (end - position).min(CHECKSUM_BLOCK_SIZE);
+            self.read_data(file, position, block_len, buffer).await?;
+            hasher.update(buffer);
+            position += block_len;
+        }
+        Ok(hasher.finalize())
+    }
+
+    async fn read_data(
+        &self,
+        file: &mut File,
+        offset: u64,
+        len: u64,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let file_path = &self.data_file_path;
+        let _ = file
+            .seek(SeekFrom::Start(offset))
             .await
-            .with_context(|_| error::FlushFileSnafu { file_path: self.file_path.clone() })?;
+            .with_context(|_| error::SeekFileSnafu { file_path: file_path.clone() })?;
+        buffer.resize(usize::try_from(len).unwrap_or(usize::MAX), 0);
+        let _ = file
+            .read_exact(buffer)
+            .await
+            .with_context(|_| error::ReadFileSnafu { file_path: file_path.clone() })?;
         Ok(())
     }
 
     pub async fn remove(self) {
-        drop(self.file);
         if let Err(err) = tokio::fs::remove_file(&self.file_path).await {
             tracing::warn!(
                 "Error occurs while removing control file `{}`, error: {err}",
@@ -98,6 +392,12 @@ impl ControlFile {
         }
     }
 
+    fn temporary_file_path(&self) -> PathBuf {
+        let mut file_name = self.file_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+        self.file_path.with_file_name(file_name)
+    }
+
     pub fn file_path<P>(file_path: P) -> PathBuf
     where
         P: AsRef<Path>,
@@ -119,3 +419,237 @@ impl ControlFile {
         }
     }
 }
+
+/// End of the received part of the chunk, exclusive
+const fn received_end(chunk: &Chunk) -> u64 {
+    let len = chunk.len();
+    chunk.start + if chunk.received < len { chunk.received } else { len }
+}
+
+#[cfg(test)]
+mod tests {
+    use caracal_base::model::RemoteChangePolicy;
+
+    use super::ControlFile;
+    use crate::{downloader::TransferStatus, error::Error, fetcher::Fetcher};
+
+    #[tokio::test]
+    async fn test_update_progress() {
+        let dir =
+            std::env::temp_dir().join(format!("caracal-test-control-file-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let data_file_path = dir.join("data.bin");
+        tokio::fs::write(&data_file_path, vec![7_u8; 4096]).await.unwrap();
+        let source = Fetcher::new_file(&data_file_path).await.unwrap();
+        let uris = vec!["/tmp/data.bin".parse().unwrap()];
+
+        let (control_file, transfer_status) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        assert!(transfer_status.is_none());
+        let mut transfer_status = TransferStatus::new(4096, 1024).unwrap();
+        transfer_status.update_progress(1024, 512);
+        control_file.update_progress(&transfer_status).await.unwrap();
+        assert!(!tokio::fs::try_exists(control_file.temporary_file_path()).await.unwrap());
+
+        let (_, loaded) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        assert_eq!(loaded.unwrap().chunks(), transfer_status.chunks());
+
+        // a control file of schema 1 is migrated
+        let control_file_path = ControlFile::file_path(&data_file_path);
+        tokio::fs::write(
+            &control_file_path,
+            serde_json::json!({
+                "schema": 1,
+                "uris": [],
+                "content_length": 4096,
+                "chunks": [
+                    { "start": 0, "end": 4095, "received": 2048, "is_completed": false },
+                ],
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        let (_, loaded) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        assert_eq!(loaded.unwrap().total_received(), 2048);
+
+        // a truncated control file is reported instead of being discarded
+        tokio::fs::write(&control_file_path, r#"{"schema":2,"uris":"#).await.unwrap();
+        let result =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await;
+        assert!(matches!(result, Err(Error::CorruptedControlFile { .. })));
+
+        tokio::fs::write(
+            &control_file_path,
+            serde_json::json!({
+                "schema": 1,
+                "uris": [],
+                "content_length": 4096,
+                "chunks": [
+                    { "start": 0, "end": 1023, "received": 0, "is_completed": false },
+                ],
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        let result =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await;
+        assert!(matches!(result, Err(Error::CorruptedControlFile { .. })));
+
+        // the length of the remote file is changed
+        tokio::fs::write(
+            &control_file_path,
+            serde_json::json!({
+                "schema": 1,
+                "uris": [],
+                "content_length": 1024,
+                "chunks": [
+                    { "start": 0, "end": 1023, "received": 512, "is_completed": false },
+                ],
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        let (_, loaded) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        assert!(loaded.is_none());
+        let result =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Fail)
+                .await;
+        assert!(matches!(result, Err(Error::RemoteContentChanged { .. })));
+
+        tokio::fs::write(&control_file_path, r#"{"schema":3}"#).await.unwrap();
+        let result =
+            ControlFile::new(&data_file_path, uris, &source, RemoteChangePolicy::Restart).await;
+        assert!(matches!(result, Err(Error::UnsupportedControlFileSchema { schema: 3, .. })));
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let dir = std::env::temp_dir()
+            .join(format!("caracal-test-control-file-verify-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let source_file_path = dir.join("source.bin");
+        tokio::fs::write(&source_file_path, vec![7_u8; 4096]).await.unwrap();
+        let data_file_path = dir.join("data.bin");
+        tokio::fs::write(&data_file_path, vec![7_u8; 4096]).await.unwrap();
+        let mut source = Fetcher::new_file(&source_file_path).await.unwrap();
+        let uris = vec!["/tmp/source.bin".parse().unwrap()];
+
+        let (control_file, _) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut transfer_status = TransferStatus::new(4096, 1024).unwrap();
+        for start in [0, 1024, 2048] {
+            transfer_status.update_progress(start, 512);
+        }
+        control_file.update_progress(&transfer_status).await.unwrap();
+
+        // the first chunk is checked against its checksum
+        let mut data = vec![7_u8; 4096];
+        data[100] = 0;
+        tokio::fs::write(&data_file_path, &data).await.unwrap();
+        let (control_file, loaded) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut loaded = loaded.unwrap();
+        control_file.verify(&mut loaded, &mut source).await.unwrap();
+        let received = loaded.chunks().iter().map(|chunk| chunk.received).collect::<Vec<_>>();
+        assert_eq!(received, [0, 512, 512, 0]);
+
+        // the chunks without checksum are compared with the source
+        tokio::fs::write(
+            ControlFile::file_path(&data_file_path),
+            serde_json::json!({
+                "schema": 1,
+                "uris": [],
+                "content_length": 4096,
+                "chunks": [
+                    { "start": 0, "end": 2047, "received": 1024, "is_completed": false },
+                    { "start": 2048, "end": 4095, "received": 1024, "is_completed": false },
+                ],
+            })
+            .to_string(),
+        )
+        .await
+        .unwrap();
+        data[100] = 7;
+        data[2048 + 1000] = 0;
+        tokio::fs::write(&data_file_path, &data).await.unwrap();
+        let (control_file, loaded) =
+            ControlFile::new(&data_file_path, uris, &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut loaded = loaded.unwrap();
+        control_file.verify(&mut loaded, &mut source).await.unwrap();
+        let received = loaded.chunks().iter().map(|chunk| chunk.received).collect::<Vec<_>>();
+        assert_eq!(received, [1024, 0]);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checksum_whole_chunk() {
+        let dir = std::env::temp_dir()
+            .join(format!("caracal-test-control-file-checksum-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let content_length = 3 * 1024 * 1024;
+        let data_file_path = dir.join("data.bin");
+        let mut data = vec![7_u8; 3 * 1024 * 1024];
+        tokio::fs::write(&data_file_path, &data).await.unwrap();
+        let mut source = Fetcher::new_file(&data_file_path).await.unwrap();
+        let uris = vec!["/tmp/data.bin".parse().unwrap()];
+
+        let (control_file, _) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut transfer_status = TransferStatus::new(content_length, content_length).unwrap();
+        transfer_status.update_progress(0, 1024 * 1024);
+        control_file.update_progress(&transfer_status).await.unwrap();
+        // the checksum is extended with the data received since then
+        transfer_status.update_progress(0, 2 * 1024 * 1024 + 512);
+        control_file.update_progress(&transfer_status).await.unwrap();
+        // the checksum is kept as the chunk has not received more data
+        control_file.update_layout(&transfer_status).await.unwrap();
+
+        let (control_file, loaded) =
+            ControlFile::new(&data_file_path, uris.clone(), &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut loaded = loaded.unwrap();
+        control_file.verify(&mut loaded, &mut source).await.unwrap();
+        assert_eq!(loaded.total_received(), 2 * 1024 * 1024 + 512);
+
+        // corruption far from the end of the received part is detected
+        data[10] = 0;
+        tokio::fs::write(&data_file_path, &data).await.unwrap();
+        let (control_file, loaded) =
+            ControlFile::new(&data_file_path, uris, &source, RemoteChangePolicy::Restart)
+                .await
+                .unwrap();
+        let mut loaded = loaded.unwrap();
+        control_file.verify(&mut loaded, &mut source).await.unwrap();
+        assert_eq!(loaded.total_received(), 0);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -1,8 +1,6 @@
-use std::collections::HashMap;
-
 use serde::{Deserialize, Serialize};
 
-use crate::{downloader, downloader::TransferStatus};
+use super::v2;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Control {
@@ -26,25 +24,22 @@ pub struct Chunk {
     pub is_completed: bool,
 }
 
-impl From<Chunk> for downloader::Chunk {
+impl From<Chunk> for v2::Chunk {
     fn from(Chunk { start, end, received, is_completed }: Chunk) -> Self {
-        Self { start, end, received, is_completed }
-    }
-}
-
-impl From<downloader::Chunk> for Chunk {
-    fn from(downloader::Chunk { start, end, received, is_completed }: downloader::Chunk) -> Self {
-        Self { start, end, received, is_completed }
+        Self { start, end, received, is_completed, crc32: None }
     }
 }
 
-impl From<Control> for TransferStatus {
-    fn from(Control { content_length, chunks, .. }: Control) -> Self {
-        let content_length = content_length.unwrap_or(0);
-        let chunks = chunks
-            .into_iter()
-            .map(|chunk| (chunk.start, downloader::Chunk::from(chunk)))
-            .collect::<HashMap<_, _>>();
-        Self { content_length, chunks, concurrent_number: 0 }
+impl From<Control> for v2::Control {
+    fn from(Control { uris, content_length, chunks, .. }: Control) -> Self {
+        Self {
+            schema: v2::SCHEMA,
+            uris,
+            fetcher: None,
+            etag: None,
+            last_modified: None,
+            content_length: content_length.unwrap_or(0),
+            chunks: chunks.into_iter().map(Into::into).collect(),
+        }
     }
 }
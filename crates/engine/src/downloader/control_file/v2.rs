@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{downloader, downloader::TransferStatus, fetcher::Metadata};
+
+pub const SCHEMA: u32 = 2;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Control {
+    pub schema: u32,
+
+    pub uris: Vec<String>,
+
+    /// Kind of the fetcher of the primary URI, it is unknown if the control
+    /// file is migrated from schema 1
+    pub fetcher: Option<String>,
+
+    pub etag: Option<String>,
+
+    pub last_modified: Option<String>,
+
+    pub content_length: u64,
+
+    pub chunks: Vec<Chunk>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Chunk {
+    pub start: u64,
+
+    pub end: u64,
+
+    pub received: u64,
+
+    pub is_completed: bool,
+
+    /// CRC-32 of the received part of the chunk, it is left out if the chunk
+    /// has received more data since the checksum was computed
+    pub crc32: Option<u32>,
+}
+
+impl Control {
+    /// Whether the remote content differs from the one being downloaded, the
+    /// validators are compared only if both sides have them
+    pub fn is_changed(&self, metadata: &Metadata) -> bool {
+        fn differs(recorded: Option<&String>, current: Option<&String>) -> bool {
+            recorded.zip(current).is_some_and(|(recorded, current)| recorded != current)
+        }
+
+        self.content_length != metadata.length
+            || differs(self.etag.as_ref(), metadata.etag.as_ref())
+            || differs(self.last_modified.as_ref(), metadata.last_modified.as_ref())
+    }
+
+    /// Check whether the chunks cover the whole content without gaps or
+    /// overlaps
+    pub fn validate(&self) -> Result<(), String> {
+        let mut chunks = self.chunks.iter().collect::<Vec<_>>();
+        chunks.sort_unstable_by_key(|chunk| chunk.start);
+        let mut next_start = 0;
+        for chunk in chunks {
+            if chunk.start != next_start || chunk.end < chunk.start {
+                return Err(format!(
+                    "chunk {start}-{end} does not follow byte {next_start}",
+                    start = chunk.start,
+                    end = chunk.end
+                ));
+            }
+            next_start = chunk.end + 1;
+        }
+        if next_start == self.content_length {
+            Ok(())
+        } else {
+            Err(format!(
+                "chunks cover {next_start} bytes, content length is {content_length} bytes",
+                content_length = self.content_length
+            ))
+        }
+    }
+}
+
+impl From<Chunk> for downloader::Chunk {
+    fn from(Chunk { start, end, received, is_completed, .. }: Chunk) -> Self {
+        Self { start, end, received, is_completed }
+    }
+}
+
+impl From<Control> for TransferStatus {
+    fn from(Control { content_length, chunks, .. }: Control) -> Self {
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| (chunk.start, downloader::Chunk::from(chunk)))
+            .collect::<HashMap<_, _>>();
+        Self { content_length, chunks, concurrent_number: 0, connection_tuning_log: Vec::new() }
+    }
+}
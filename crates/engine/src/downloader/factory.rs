@@ -8,7 +8,12 @@ use std::{
 
 use caracal_base::{
     model,
-    profile::{minio::MinioAlias, ssh::SshConfig},
+    profile::{
+        minio::{MinioAlias, MinioPath},
+        s3::{S3Path, S3Profile},
+        ssh::SshConfig,
+    },
+    utils::RetryInterval,
 };
 use futures::{FutureExt, future};
 use snafu::{OptionExt, ResultExt};
@@ -16,12 +21,18 @@ use tokio::fs::OpenOptions;
 
 pub use crate::error::Error;
 use crate::{
-    downloader::{Downloader, TransferStatus, control_file::ControlFile},
+    downloader::{
+        ConnectionTuning, Downloader, RateLimiter, TransferStatus, control_file::ControlFile,
+    },
     error,
     ext::UriExt,
+    fetcher,
     fetcher::Fetcher,
 };
 
+// used by `s3://bucket/key` without a profile
+const DEFAULT_S3_PROFILE: &str = "default";
+
 #[derive(Clone, Debug)]
 pub struct Builder {
     pub default_concurrent_number: u64,
@@ -34,9 +45,23 @@ pub struct Builder {
 
     pub minio_aliases: HashMap<String, MinioAlias>,
 
+    pub s3_profiles: HashMap<String, S3Profile>,
+
     pub ssh_servers: HashMap<String, SshConfig>,
 
     pub connection_timeout: Duration,
+
+    pub retry_interval: RetryInterval,
+
+    pub speed_limit: Option<u64>,
+
+    pub proxy: Option<model::Proxy>,
+
+    pub torrent_seed_ratio: f64,
+
+    pub verify_on_resume: bool,
+
+    pub connection_tuning: Option<ConnectionTuning>,
 }
 
 impl Builder {
@@ -50,8 +75,15 @@ impl Builder {
             http_user_agent: None,
             minimum_chunk_size: 100 * 1024,
             minio_aliases: HashMap::new(),
+            s3_profiles: HashMap::new(),
             ssh_servers: HashMap::new(),
             connection_timeout: Duration::from_secs(60),
+            retry_interval: RetryInterval::recommended(),
+            speed_limit: None,
+            proxy: None,
+            torrent_seed_ratio: 0.0,
+            verify_on_resume: false,
+            connection_tuning: None,
         })
     }
 
@@ -78,11 +110,57 @@ impl Builder {
         self
     }
 
+    /// Profiles of S3 services, the profile named `default` is used if the URI
+    /// does not specify one
+    pub fn s3_profiles(mut self, s3_profiles: HashMap<String, S3Profile>) -> Self {
+        self.s3_profiles = s3_profiles;
+        self
+    }
+
     pub const fn connection_timeout(mut self, connection_timeout: Duration) -> Self {
         self.connection_timeout = connection_timeout;
         self
     }
 
+    pub fn retry_interval(mut self, retry_interval: RetryInterval) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    pub const fn speed_limit(mut self, speed_limit: Option<u64>) -> Self {
+        self.speed_limit = speed_limit;
+        self
+    }
+
+    /// Send HTTP requests through `proxy`, the proxy is taken from the
+    /// environment variables `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` if it is not provided
+    pub fn proxy(mut self, proxy: Option<model::Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Keep uploading completed torrents until `ratio` times of their length is
+    /// uploaded, `0` disables seeding
+    pub const fn torrent_seed_ratio(mut self, ratio: f64) -> Self {
+        self.torrent_seed_ratio = ratio;
+        self
+    }
+
+    /// Check the data downloaded before a task is resumed, the chunks which do
+    /// not match are downloaded again
+    pub const fn verify_on_resume(mut self, verify_on_resume: bool) -> Self {
+        self.verify_on_resume = verify_on_resume;
+        self
+    }
+
+    /// Adjust the number of connections of each task to the measured transfer
+    /// rate, the concurrent number of a task is used as the initial value
+    pub const fn connection_tuning(mut self, connection_tuning: Option<ConnectionTuning>) -> Self {
+        self.connection_tuning = connection_tuning;
+        self
+    }
+
     pub fn ssh_servers(mut self, ssh_servers: HashMap<String, SshConfig>) -> Self {
         self.ssh_servers = ssh_servers;
         self
@@ -103,35 +181,96 @@ impl Builder {
             default_output_directory_path,
             default_concurrent_number,
             minio_aliases,
+            s3_profiles,
             ssh_servers,
             minimum_chunk_size,
             connection_timeout,
+            retry_interval,
+            speed_limit,
+            proxy,
+            torrent_seed_ratio,
+            verify_on_resume,
+            connection_tuning,
         } = self;
 
-        let http_client = reqwest::Client::builder()
-            .user_agent(
-                http_user_agent
-                    .unwrap_or_else(|| caracal_base::DEFAULT_HTTP_USER_AGENT.to_string()),
-            )
-            .build()
-            .context(error::BuildHttpClientSnafu)?;
+        let http_user_agent =
+            http_user_agent.unwrap_or_else(|| caracal_base::DEFAULT_HTTP_USER_AGENT.to_string());
+        let http_client = build_http_client(&http_user_agent, proxy.as_ref())?;
 
         Ok(Factory {
             http_client,
+            http_user_agent,
             default_output_directory_path,
             default_concurrent_number,
             minimum_chunk_size,
             minio_aliases,
+            s3_profiles,
             ssh_servers,
             connection_timeout,
+            retry_interval,
+            rate_limiter: RateLimiter::new(speed_limit),
+            torrent_seed_ratio,
+            verify_on_resume,
+            connection_tuning,
         })
     }
 }
 
+#[allow(clippy::result_large_err)]
+fn build_http_client(
+    user_agent: &str,
+    proxy: Option<&model::Proxy>,
+) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder().user_agent(user_agent);
+    if let Some(model::Proxy { uri, username, password, no_proxy }) = proxy {
+        let mut url = reqwest::Url::parse(uri)
+            .ok()
+            .context(error::InvalidProxyUrlSnafu { uri: uri.clone() })?;
+        // credentials are carried by the URL, it works with both HTTP and SOCKS proxies
+        if let Some(username) = username {
+            url.set_username(username)
+                .ok()
+                .context(error::InvalidProxyUrlSnafu { uri: uri.clone() })?;
+            url.set_password(password.as_deref())
+                .ok()
+                .context(error::InvalidProxyUrlSnafu { uri: uri.clone() })?;
+        }
+        let proxy = reqwest::Proxy::all(url)
+            .with_context(|_| error::InvalidProxySnafu { uri: uri.clone() })?
+            .no_proxy(reqwest::NoProxy::from_string(&no_proxy.join(",")));
+        builder = builder.proxy(proxy);
+    }
+    builder.build().context(error::BuildHttpClientSnafu)
+}
+
+// headers and credentials of a task are only sent to the origin of its URI,
+// mirrors of other origins are accessed without them, the proxy belongs to the
+// client and is used for every mirror
+fn mirror_task(new_task: &model::CreateTask, mirror: &http::Uri) -> model::CreateTask {
+    let is_same_origin =
+        mirror.scheme() == new_task.uri.scheme() && mirror.authority() == new_task.uri.authority();
+    if is_same_origin {
+        new_task.clone()
+    } else {
+        model::CreateTask { headers: Vec::new(), basic_auth: None, ..new_task.clone() }
+    }
+}
+
+async fn create_parent_directory(file_path: &Path) -> Result<(), Error> {
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent).await.with_context(|_| error::CreateDirectorySnafu {
+            directory_path: parent.to_path_buf(),
+        })?;
+    }
+    Ok(())
+}
+
 #[derive(Clone, Debug)]
 pub struct Factory {
     http_client: reqwest::Client,
 
+    http_user_agent: String,
+
     default_output_directory_path: PathBuf,
 
     default_concurrent_number: u64,
@@ -140,9 +279,22 @@ pub struct Factory {
 
     minio_aliases: HashMap<String, MinioAlias>,
 
+    s3_profiles: HashMap<String, S3Profile>,
+
     ssh_servers: HashMap<String, SshConfig>,
 
     connection_timeout: Duration,
+
+    retry_interval: RetryInterval,
+
+    // shared by all downloaders created by this factory
+    rate_limiter: RateLimiter,
+
+    torrent_seed_ratio: f64,
+
+    verify_on_resume: bool,
+
+    connection_tuning: Option<ConnectionTuning>,
 }
 
 impl Factory {
@@ -151,22 +303,116 @@ impl Factory {
     #[allow(clippy::result_large_err)]
     pub fn builder() -> Result<Builder, Error> { Builder::new() }
 
-    /// # Errors
-    pub async fn create_new_task(&self, new_task: &model::CreateTask) -> Result<Downloader, Error> {
-        let source = {
-            let source_fut = self.create_fetcher(new_task).boxed();
-            let timeout =
-                tokio::time::sleep(new_task.connection_timeout.unwrap_or(self.connection_timeout));
+    #[must_use]
+    pub fn speed_limit(&self) -> Option<u64> { self.rate_limiter.limit() }
 
-            tokio::pin!(timeout);
+    /// Set the maximum transfer rate of all downloaders in bytes per second,
+    /// `None` or `0` for unlimited
+    pub fn set_speed_limit(&self, limit: Option<u64>) { self.rate_limiter.set_limit(limit); }
 
-            match future::select(source_fut, timeout).await {
-                future::Either::Left((source, _)) => source?,
-                future::Either::Right((_timeout, _)) => return Err(Error::ConnectionTimedOut),
+    /// Reject the task before it is queued if it can never be downloaded, e.g.
+    /// a `.torrent` file of multiple files. Magnet links are checked once
+    /// their metadata is fetched.
+    ///
+    /// # Errors
+    pub async fn check_task(&self, new_task: &model::CreateTask) -> Result<(), Error> {
+        let uri = &new_task.uri;
+        match uri.scheme_str() {
+            Some("file") | None if uri.path().ends_with(".torrent") => {
+                fetcher::check_torrent_file(uri.decoded_path()).await
             }
+            _ => Ok(()),
+        }
+    }
+
+    /// Expand a task whose URI points to a directory or a prefix into the tasks
+    /// of the files under it, the layout of the directory is kept under the
+    /// output directory. `None` is returned if the URI does not point to a
+    /// directory.
+    ///
+    /// # Errors
+    pub async fn expand_directory(
+        &self,
+        new_task: &model::CreateTask,
+        filter: &model::PathFilter,
+    ) -> Result<Option<Vec<model::CreateTask>>, Error> {
+        let uri = &new_task.uri;
+        let Some(files) = self.list_directory(uri).await? else {
+            return Ok(None);
+        };
+
+        // the files are stored in a directory named after the listed one
+        let directory_name = new_task.filename.clone().unwrap_or_else(|| {
+            let decoded_path = uri.decoded_path();
+            decoded_path
+                .trim_end_matches('/')
+                .rsplit('/')
+                .find(|name| !name.is_empty())
+                .or_else(|| uri.host())
+                .map_or_else(|| PathBuf::from(caracal_base::FALLBACK_FILENAME), PathBuf::from)
+        });
+        let output_directory = new_task
+            .output_directory
+            .as_ref()
+            .unwrap_or(&self.default_output_directory_path)
+            .join(directory_name);
+
+        let base_path = format!("{}/", uri.path().trim_end_matches('/'));
+        let new_tasks = files
+            .iter()
+            .filter(|file| filter.is_match(file))
+            .map(|file| {
+                let encoded = file.split('/').map(urlencoding::encode).collect::<Vec<_>>();
+                let mut parts = uri.clone().into_parts();
+                parts.path_and_query = Some(
+                    format!("{base_path}{}", encoded.join("/"))
+                        .parse()
+                        .ok()
+                        .with_context(|| error::InvalidChildUriSnafu { path: file.clone() })?,
+                );
+                let child_uri = http::Uri::from_parts(parts)
+                    .ok()
+                    .with_context(|| error::InvalidChildUriSnafu { path: file.clone() })?;
+                let file = Path::new(file);
+                Ok(model::CreateTask {
+                    uri: child_uri,
+                    mirrors: Vec::new(),
+                    filename: file.file_name().map(PathBuf::from),
+                    output_directory: Some(
+                        file.parent().filter(|parent| !parent.as_os_str().is_empty()).map_or_else(
+                            || output_directory.clone(),
+                            |parent| output_directory.join(parent),
+                        ),
+                    ),
+                    checksum: None,
+                    content_length: None,
+                    ..new_task.clone()
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if new_tasks.is_empty() {
+            return Err(Error::NoFileInDirectory { uri: uri.clone() });
+        }
+        Ok(Some(new_tasks))
+    }
+
+    /// # Errors
+    #[allow(clippy::too_many_lines)]
+    pub async fn create_new_task(&self, new_task: &model::CreateTask) -> Result<Downloader, Error> {
+        let (uri, source) = match self.create_fetcher_with_timeout(new_task, &new_task.uri).await {
+            Ok(source) => (new_task.uri.clone(), source),
+            Err(err) => self.create_fallback_fetcher(new_task, err).await?,
         };
 
         let metadata = source.fetch_metadata();
+        // the length is unknown without range requests if it is `0`, it is checked
+        // after the transfer as well
+        if let Some(expected) = new_task.content_length
+            && (source.supports_range_request() || metadata.length != 0)
+            && expected != metadata.length
+        {
+            return Err(Error::ContentLengthMismatch { uri, expected, actual: metadata.length });
+        }
         if source.supports_range_request() {
             let filename = new_task.filename.clone().unwrap_or_else(|| metadata.filename.clone());
             let full_path = [
@@ -181,6 +427,7 @@ impl Factory {
                 return Err(Error::DestinationFileExists { file_path: full_path.clone() });
             }
 
+            create_parent_directory(&full_path).await?;
             let sink = OpenOptions::new()
                 .create(true)
                 .truncate(false)
@@ -204,8 +451,22 @@ impl Factory {
                 };
                 (metadata.length / concurrent_number, concurrent_number)
             };
+            // a file which fits in one chunk is not worth more connections
+            let connection_tuning =
+                self.connection_tuning.filter(|_| metadata.length > self.minimum_chunk_size);
+            let worker_number = connection_tuning
+                .map_or(worker_number, |connection_tuning| connection_tuning.clamp(worker_number));
+            // a piece of torrent is verified as a whole, chunks should not share pieces
+            let chunk_size = source.piece_length().map_or(chunk_size, |piece_length| {
+                chunk_size.div_ceil(piece_length) * piece_length
+            });
 
             let transfer_status = TransferStatus::new(metadata.length, chunk_size)?;
+            let (mirror_uris, mirrors) = self
+                .create_mirror_fetchers(new_task, &uri, metadata.length)
+                .await
+                .into_iter()
+                .unzip();
 
             Ok(Downloader {
                 use_single_worker: false,
@@ -213,10 +474,20 @@ impl Factory {
                 transfer_status,
                 sink,
                 source,
-                uri: new_task.uri.clone(),
+                mirrors,
+                uri,
+                mirror_uris,
                 file_path: full_path,
                 handle: None,
                 is_completed: Arc::new(AtomicBool::new(false)),
+                retry_interval: self.retry_interval.clone(),
+                checksum: new_task.checksum.clone(),
+                content_length: new_task.content_length,
+                rate_limiter: self.rate_limiter.child(None),
+                seed_ratio: self.torrent_seed_ratio,
+                verify_on_resume: self.verify_on_resume,
+                on_remote_change: new_task.on_remote_change,
+                connection_tuning,
             })
         } else {
             let filename =
@@ -231,6 +502,7 @@ impl Factory {
                 return Err(Error::DestinationFileExists { file_path: full_path.clone() });
             }
 
+            create_parent_directory(&full_path).await?;
             let sink = OpenOptions::new()
                 .create(true)
                 .truncate(false)
@@ -250,40 +522,126 @@ impl Factory {
                 transfer_status,
                 sink,
                 source,
-                uri: new_task.uri.clone(),
+                // the transfer can not be split without range requests, mirrors are not used
+                mirrors: Vec::new(),
+                uri,
+                mirror_uris: Vec::new(),
                 file_path: full_path,
                 handle: None,
                 is_completed: Arc::new(AtomicBool::new(false)),
+                retry_interval: self.retry_interval.clone(),
+                checksum: new_task.checksum.clone(),
+                content_length: new_task.content_length,
+                rate_limiter: self.rate_limiter.child(None),
+                seed_ratio: self.torrent_seed_ratio,
+                verify_on_resume: self.verify_on_resume,
+                on_remote_change: new_task.on_remote_change,
+                connection_tuning: None,
             })
         }
     }
 
-    async fn create_fetcher(&self, new_task: &model::CreateTask) -> Result<Fetcher, Error> {
-        match new_task.uri.scheme_str() {
-            Some("file") | None => Fetcher::new_file(new_task.uri.path()).await,
+    async fn create_fetcher_with_timeout(
+        &self,
+        new_task: &model::CreateTask,
+        uri: &http::Uri,
+    ) -> Result<Fetcher, Error> {
+        let source_fut = self.create_fetcher(new_task, uri).boxed();
+        let timeout =
+            tokio::time::sleep(new_task.connection_timeout.unwrap_or(self.connection_timeout));
+
+        tokio::pin!(timeout);
+
+        match future::select(source_fut, timeout).await {
+            future::Either::Left((source, _)) => source,
+            future::Either::Right((_timeout, _)) => Err(Error::ConnectionTimedOut),
+        }
+    }
+
+    // the first reachable mirror takes over if the origin is unreachable
+    async fn create_fallback_fetcher(
+        &self,
+        new_task: &model::CreateTask,
+        err: Error,
+    ) -> Result<(http::Uri, Fetcher), Error> {
+        for uri in &new_task.mirrors {
+            match self.create_fetcher_with_timeout(&mirror_task(new_task, uri), uri).await {
+                Ok(mirror) => {
+                    tracing::warn!("Download from mirror `{uri}`, error of origin: {err}");
+                    return Ok((uri.clone(), mirror));
+                }
+                Err(mirror_err) => tracing::warn!("Skip mirror `{uri}`, error: {mirror_err}"),
+            }
+        }
+        Err(err)
+    }
+
+    // mirrors which are unreachable or do not serve the same content are skipped
+    async fn create_mirror_fetchers(
+        &self,
+        new_task: &model::CreateTask,
+        source_uri: &http::Uri,
+        content_length: u64,
+    ) -> Vec<(http::Uri, Fetcher)> {
+        // the source is one of the mirrors if the origin is unreachable
+        let uris = new_task.mirrors.iter().filter(|uri| *uri != source_uri).collect::<Vec<_>>();
+        let mirrors = future::join_all(uris.iter().map(|uri| async move {
+            self.create_fetcher_with_timeout(&mirror_task(new_task, uri), uri).await
+        }))
+        .await;
+
+        uris.into_iter()
+            .zip(mirrors)
+            .filter_map(|(uri, mirror)| match mirror {
+                Ok(mirror) if !mirror.supports_range_request() => {
+                    tracing::warn!("Skip mirror `{uri}`, range request is not supported");
+                    None
+                }
+                Ok(mirror) if mirror.fetch_metadata().length != content_length => {
+                    tracing::warn!(
+                        "Skip mirror `{uri}`, content length {} does not match {content_length}",
+                        mirror.fetch_metadata().length
+                    );
+                    None
+                }
+                Ok(mirror) => Some((uri.clone(), mirror)),
+                Err(err) => {
+                    tracing::warn!("Skip mirror `{uri}`, error: {err}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    async fn create_fetcher(
+        &self,
+        new_task: &model::CreateTask,
+        uri: &http::Uri,
+    ) -> Result<Fetcher, Error> {
+        match uri.scheme_str() {
+            Some("file") | None if uri.path().ends_with(".torrent") => {
+                Fetcher::new_torrent_file(self.http_client(new_task)?, uri.decoded_path()).await
+            }
+            Some("file") | None => Fetcher::new_file(uri.decoded_path()).await,
+            Some("magnet") => Fetcher::new_magnet(self.http_client(new_task)?, uri).await,
             Some("http" | "https") => {
-                Fetcher::new_http(self.http_client.clone(), new_task.uri.clone()).await
+                Fetcher::new_http(
+                    self.http_client(new_task)?,
+                    uri.clone(),
+                    &new_task.headers,
+                    new_task.basic_auth.clone(),
+                )
+                .await
+            }
+            Some("ftp" | "ftps" | "ftpes") => {
+                Fetcher::new_ftp(uri.clone(), new_task.basic_auth.clone()).await
             }
             Some("sftp") => {
-                let endpoint = new_task.uri.host().context(error::HostnameNotProvidedSnafu)?;
-                let file_path = new_task.uri.path();
-                let SshConfig { endpoint, user, identity_file } = self
-                    .ssh_servers
-                    .get(endpoint)
-                    .context(error::SshConfigNotFoundSnafu { endpoint: endpoint.to_string() })?;
-                Fetcher::new_sftp(endpoint, user, identity_file, file_path).await
+                let SshConfig { endpoint, user, identity_file } = self.ssh_config(uri)?;
+                Fetcher::new_sftp(endpoint, user, identity_file, uri.decoded_path()).await
             }
             Some("minio") => {
-                let minio_path = new_task
-                    .uri
-                    .minio_path()
-                    .with_context(|| error::InvalidMinioUrlSnafu { uri: new_task.uri.clone() })?;
-
-                let alias = self
-                    .minio_aliases
-                    .get(&minio_path.alias)
-                    .context(error::MinioAliasNotFoundSnafu { alias: minio_path.alias.clone() })?;
-
+                let (alias, minio_path) = self.minio_alias(uri)?;
                 Fetcher::new_minio(
                     &alias.endpoint_url,
                     &alias.access_key,
@@ -293,7 +651,81 @@ impl Factory {
                 )
                 .await
             }
+            Some("s3") => {
+                let (profile, s3_path) = self.s3_profile(uri)?;
+                Fetcher::new_s3(uri.clone(), &profile, s3_path).await
+            }
             Some(scheme) => Err(Error::UnsupportedScheme { scheme: scheme.to_string() }),
         }
     }
+
+    // relative paths of the files under `uri`, `None` if it is not a directory
+    async fn list_directory(&self, uri: &http::Uri) -> Result<Option<Vec<String>>, Error> {
+        match uri.scheme_str() {
+            Some("file") | None => fetcher::list_file_directory(uri.decoded_path()).await,
+            Some("sftp") => {
+                let SshConfig { endpoint, user, identity_file } = self.ssh_config(uri)?;
+                fetcher::list_sftp_directory(endpoint, user, identity_file, uri.decoded_path())
+                    .await
+            }
+            Some("minio") => {
+                let (alias, minio_path) = self.minio_alias(uri)?;
+                fetcher::list_minio_prefix(
+                    &alias.endpoint_url,
+                    &alias.access_key,
+                    &alias.secret_key,
+                    minio_path.bucket,
+                    minio_path.object,
+                )
+                .await
+            }
+            Some("s3") => {
+                let (profile, s3_path) = self.s3_profile(uri)?;
+                fetcher::list_s3_prefix(&profile, &s3_path).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn ssh_config(&self, uri: &http::Uri) -> Result<&SshConfig, Error> {
+        let endpoint = uri.host().context(error::HostnameNotProvidedSnafu)?;
+        self.ssh_servers
+            .get(endpoint)
+            .context(error::SshConfigNotFoundSnafu { endpoint: endpoint.to_string() })
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn minio_alias(&self, uri: &http::Uri) -> Result<(&MinioAlias, MinioPath), Error> {
+        let minio_path =
+            uri.minio_path().with_context(|| error::InvalidMinioUrlSnafu { uri: uri.clone() })?;
+        let alias = self
+            .minio_aliases
+            .get(&minio_path.alias)
+            .context(error::MinioAliasNotFoundSnafu { alias: minio_path.alias.clone() })?;
+        Ok((alias, minio_path))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn s3_profile(&self, uri: &http::Uri) -> Result<(S3Profile, S3Path), Error> {
+        let s3_path =
+            uri.s3_path().with_context(|| error::InvalidS3UrlSnafu { uri: uri.clone() })?;
+        let profile = match s3_path.profile.as_deref() {
+            Some(name) => self
+                .s3_profiles
+                .get(name)
+                .context(error::S3ProfileNotFoundSnafu { profile: name.to_string() })?
+                .clone(),
+            None => self.s3_profiles.get(DEFAULT_S3_PROFILE).cloned().unwrap_or_default(),
+        };
+        Ok((profile, s3_path))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn http_client(&self, new_task: &model::CreateTask) -> Result<reqwest::Client, Error> {
+        new_task.proxy.as_ref().map_or_else(
+            || Ok(self.http_client.clone()),
+            |proxy| build_http_client(&self.http_user_agent, Some(proxy)),
+        )
+    }
 }
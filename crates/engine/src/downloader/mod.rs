@@ -1,13 +1,16 @@
+mod checksum;
 mod chunk;
+mod connection_tuner;
 mod control_file;
 mod factory;
 mod progress_updater;
+mod rate_limiter;
 mod status;
 mod transfer_status;
 mod worker;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::SeekFrom,
     path::PathBuf,
     sync::{
@@ -16,7 +19,8 @@ use std::{
     },
 };
 
-use futures::future;
+use caracal_base::{model, utils::RetryInterval};
+use futures::{TryFutureExt, future};
 use snafu::ResultExt;
 use tokio::{
     fs::File,
@@ -27,13 +31,16 @@ use tokio::{
 
 pub use self::{
     chunk::{Chunk, MINIMUM_CHUNK_SIZE},
+    connection_tuner::ConnectionTuning,
     factory::Factory as DownloaderFactory,
     status::DownloaderStatus,
     transfer_status::TransferStatus,
 };
 use self::{
+    connection_tuner::ConnectionTuner,
     control_file::ControlFile,
     progress_updater::ProgressUpdater,
+    rate_limiter::RateLimiter,
     worker::{Worker, WorkerEvent},
 };
 use crate::{error, error::Error, fetcher::Fetcher};
@@ -46,10 +53,20 @@ pub struct Downloader {
     transfer_status: TransferStatus,
     sink: File,
     source: Fetcher,
+    mirrors: Vec<Fetcher>,
     uri: http::Uri,
+    mirror_uris: Vec<http::Uri>,
     file_path: PathBuf,
     handle: DownloaderHandle,
     is_completed: Arc<AtomicBool>,
+    retry_interval: RetryInterval,
+    checksum: Option<model::Checksum>,
+    content_length: Option<u64>,
+    rate_limiter: RateLimiter,
+    seed_ratio: f64,
+    on_remote_change: model::RemoteChangePolicy,
+    verify_on_resume: bool,
+    connection_tuning: Option<ConnectionTuning>,
 }
 
 impl Downloader {
@@ -60,32 +77,60 @@ impl Downloader {
                 error::CloneFileInstanceSnafu { file_path: self.file_path.clone() }
             })?;
             let (event_sender, event_receiver) = mpsc::unbounded_channel::<Event>();
+            let verify_file = {
+                let (uri, file_path) = (self.uri.clone(), self.file_path.clone());
+                let (content_length, checksum) = (self.content_length, self.checksum.clone());
+                move |summary| Self::verify_file(summary, uri, file_path, content_length, checksum)
+            };
             let join_handle = if self.use_single_worker {
-                tokio::spawn(Self::serve_with_single_worker(
-                    self.transfer_status.clone(),
-                    sink_cloned,
-                    self.source.clone(),
-                    self.file_path.clone(),
-                    event_receiver,
-                    self.is_completed.clone(),
-                ))
+                tokio::spawn(
+                    Self::serve_with_single_worker(ServeWithSingleWorkerOptions {
+                        transfer_status: self.transfer_status.clone(),
+                        sink: sink_cloned,
+                        source: self.source.clone(),
+                        file_path: self.file_path.clone(),
+                        event_receiver,
+                        is_completed: self.is_completed.clone(),
+                        retry_interval: self.retry_interval.clone(),
+                        rate_limiter: self.rate_limiter.clone(),
+                    })
+                    .and_then(verify_file),
+                )
             } else {
+                let uris = std::iter::once(&self.uri).chain(&self.mirror_uris).cloned().collect();
                 let (control_file, transfer_status) =
-                    ControlFile::new(&self.file_path, self.uri.clone()).await?;
+                    ControlFile::new(&self.file_path, uris, &self.source, self.on_remote_change)
+                        .await?;
+                let verifies_progress = self.verify_on_resume && transfer_status.is_some();
                 if let Some(transfer_status) = transfer_status {
                     self.transfer_status = transfer_status;
                 }
-                tokio::spawn(Self::serve_with_multiple_workers(ServeWithMultipleWorkerOptions {
-                    worker_number: self.worker_number,
-                    transfer_status: self.transfer_status.clone(),
-                    sink: Arc::new(Mutex::new(sink_cloned)),
-                    source: self.source.clone(),
-                    file_path: self.file_path.clone(),
-                    event_sender: event_sender.clone(),
-                    event_receiver,
-                    control_file,
-                    is_completed: self.is_completed.clone(),
-                }))
+                // the download can be resumed even if the process is killed, the loaded
+                // checksums are kept until the progress is verified
+                if !verifies_progress {
+                    control_file.update_progress(&self.transfer_status).await?;
+                }
+                tokio::spawn(
+                    Self::serve_with_multiple_workers(ServeWithMultipleWorkerOptions {
+                        worker_number: self.worker_number,
+                        transfer_status: self.transfer_status.clone(),
+                        sink: Arc::new(Mutex::new(sink_cloned)),
+                        sources: std::iter::once(&self.source)
+                            .chain(&self.mirrors)
+                            .cloned()
+                            .collect(),
+                        file_path: self.file_path.clone(),
+                        event_sender: event_sender.clone(),
+                        event_receiver,
+                        control_file,
+                        verifies_progress,
+                        is_completed: self.is_completed.clone(),
+                        retry_interval: self.retry_interval.clone(),
+                        rate_limiter: self.rate_limiter.clone(),
+                        connection_tuning: self.connection_tuning,
+                    })
+                    .and_then(verify_file),
+                )
             };
             self.handle = Some((event_sender, join_handle));
         }
@@ -113,21 +158,42 @@ impl Downloader {
     pub async fn resume(&mut self) -> Result<(), Error> { self.start().await }
 
     /// # Errors
-    pub async fn join(mut self) -> Result<Option<(TransferStatus, DownloaderStatus)>, Error> {
+    pub async fn join(&mut self) -> Result<Option<(TransferStatus, DownloaderStatus)>, Error> {
         if let Some((_event_sender, join_handle)) = self.handle.take() {
             let transfer_status = match join_handle.await.context(error::JoinTaskSnafu)?? {
-                Summary::Completed { transfer_status } | Summary::Partial { transfer_status } => {
+                Summary::Completed { transfer_status } => {
+                    self.is_completed.store(true, Ordering::Relaxed);
                     transfer_status
                 }
+                Summary::Partial { transfer_status } => transfer_status,
             };
-            let mut progress = DownloaderStatus::from(transfer_status.clone());
-            progress.set_file_path(&self.file_path);
+            let progress = self.downloader_status(transfer_status.clone());
             Ok(Some((transfer_status, progress)))
         } else {
             Ok(None)
         }
     }
 
+    /// Start uploading the completed file to other peers, only torrents are
+    /// seeded, seeding is stopped by aborting the returned handle
+    pub fn seed(&self) -> Option<JoinHandle<()>> {
+        if self.seed_ratio > 0.0 && self.is_completed() {
+            self.source.seed(self.file_path.clone(), self.seed_ratio)
+        } else {
+            None
+        }
+    }
+
+    // the progress of torrents is reported per piece
+    fn downloader_status(&self, transfer_status: TransferStatus) -> DownloaderStatus {
+        let mut progress = DownloaderStatus::from(transfer_status);
+        progress.set_file_path(&self.file_path);
+        if let Some(piece_length) = self.source.piece_length() {
+            progress.split_into_pieces(piece_length);
+        }
+        progress
+    }
+
     pub async fn scrape_status(&self) -> Option<DownloaderStatus> {
         if let Some((event_sender, _join_handle)) = self.handle.as_ref() {
             let (send, recv) = oneshot::channel();
@@ -135,9 +201,7 @@ impl Downloader {
             recv.await
                 .map(|status| {
                     self.is_completed.store(status.is_completed(), Ordering::Relaxed);
-                    let mut progress = DownloaderStatus::from(status);
-                    progress.set_file_path(&self.file_path);
-                    progress
+                    self.downloader_status(status)
                 })
                 .ok()
         } else {
@@ -147,6 +211,20 @@ impl Downloader {
 
     pub fn is_completed(&self) -> bool { self.is_completed.load(Ordering::Relaxed) }
 
+    /// Whether the progress is kept if the downloader is paused, downloads
+    /// without range requests start over
+    pub const fn is_resumable(&self) -> bool { !self.use_single_worker }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_some_and(|(_event_sender, join_handle)| join_handle.is_finished())
+    }
+
+    pub fn speed_limit(&self) -> Option<u64> { self.rate_limiter.limit() }
+
+    /// Set the maximum transfer rate in bytes per second, `None` or `0` for
+    /// unlimited
+    pub fn set_speed_limit(&self, limit: Option<u64>) { self.rate_limiter.set_limit(limit); }
+
     pub fn add_worker(&self) {
         if let Some((event_sender, _join_handle)) = self.handle.as_ref() {
             drop(event_sender.send(Event::AddWorker));
@@ -159,65 +237,122 @@ impl Downloader {
         }
     }
 
-    async fn serve_with_single_worker(
-        mut transfer_status: TransferStatus,
-        mut sink: File,
-        mut source: Fetcher,
+    async fn verify_file(
+        summary: Summary,
+        uri: http::Uri,
         file_path: PathBuf,
-        mut event_receiver: mpsc::UnboundedReceiver<Event>,
-        is_completed: Arc<AtomicBool>,
+        content_length: Option<u64>,
+        checksum: Option<model::Checksum>,
     ) -> Result<Summary, Error> {
-        let mut stream = source.fetch_all().await?;
-        let _ = sink
-            .seek(SeekFrom::Start(0))
-            .await
-            .with_context(|_| error::SeekFileSnafu { file_path: file_path.clone() })?;
-        let mut received = 0;
-        let mut summary = Summary::Partial { transfer_status: transfer_status.clone() };
+        if !matches!(summary, Summary::Completed { .. }) {
+            return Ok(summary);
+        }
+        // the length is unknown before the transfer if range request is not supported
+        if let Some(expected) = content_length {
+            let actual = tokio::fs::metadata(&file_path)
+                .await
+                .with_context(|_| error::GetFileLengthSnafu { file_path: file_path.clone() })?
+                .len();
+            if actual != expected {
+                return Err(Error::ContentLengthMismatch { uri, expected, actual });
+            }
+        }
+        if let Some(checksum) = checksum {
+            checksum::verify(&file_path, &checksum).await?;
+        }
+        Ok(summary)
+    }
 
+    #[allow(clippy::cognitive_complexity)]
+    async fn serve_with_single_worker(
+        ServeWithSingleWorkerOptions {
+            mut transfer_status,
+            mut sink,
+            mut source,
+            file_path,
+            mut event_receiver,
+            is_completed,
+            mut retry_interval,
+            rate_limiter,
+        }: ServeWithSingleWorkerOptions,
+    ) -> Result<Summary, Error> {
         loop {
-            let new_bytes = stream.bytes();
-            let new_event = event_receiver.recv();
-            futures::pin_mut!(new_bytes);
-            futures::pin_mut!(new_event);
-
-            match future::select(new_bytes, new_event).await {
-                future::Either::Left((Ok(Some(bytes)), _)) => {
-                    let _ = sink
-                        .seek(SeekFrom::Start(received))
-                        .await
-                        .with_context(|_| error::SeekFileSnafu { file_path: file_path.clone() })?;
-                    sink.write_all(bytes)
-                        .await
-                        .with_context(|_| error::WriteFileSnafu { file_path: file_path.clone() })?;
-                    received += bytes.len() as u64;
-                    transfer_status.update_progress(0, received);
-                }
-                future::Either::Left((Ok(None), _)) => {
-                    if let Err(err) = sink.sync_all().await {
-                        tracing::warn!("Error occurs while synchronizing file, error: {err}");
+            let err = match source.fetch_all().await {
+                Ok(mut stream) => {
+                    let mut received = 0;
+                    loop {
+                        let new_bytes = async {
+                            rate_limiter.ready().await;
+                            stream.bytes().await
+                        };
+                        let new_event = event_receiver.recv();
+                        futures::pin_mut!(new_bytes);
+                        futures::pin_mut!(new_event);
+
+                        match future::select(new_bytes, new_event).await {
+                            future::Either::Left((Ok(Some(bytes)), _)) => {
+                                let _ = sink.seek(SeekFrom::Start(received)).await.with_context(
+                                    |_| error::SeekFileSnafu { file_path: file_path.clone() },
+                                )?;
+                                sink.write_all(bytes).await.with_context(|_| {
+                                    error::WriteFileSnafu { file_path: file_path.clone() }
+                                })?;
+                                rate_limiter.consume(bytes.len() as u64);
+                                received += bytes.len() as u64;
+                                transfer_status.update_progress(0, received);
+                            }
+                            future::Either::Left((Ok(None), _)) => {
+                                if let Err(err) = sink.sync_all().await {
+                                    tracing::warn!(
+                                        "Error occurs while synchronizing file, error: {err}"
+                                    );
+                                }
+                                is_completed.store(true, Ordering::Relaxed);
+                                transfer_status.mark_chunk_completed(0);
+                                return Ok(Summary::Completed { transfer_status });
+                            }
+                            future::Either::Left((Err(err), _)) => break err,
+                            future::Either::Right((Some(Event::GetStatus(sender)), _)) => {
+                                drop(sender.send(transfer_status.clone()));
+                            }
+                            future::Either::Right((Some(Event::Stop), _)) => {
+                                return Ok(Summary::Partial { transfer_status });
+                            }
+                            future::Either::Right(_) => {}
+                        }
                     }
-                    is_completed.store(true, Ordering::Relaxed);
-                    transfer_status.mark_chunk_completed(0);
-                    summary = Summary::Completed { transfer_status };
-                    break;
-                }
-                future::Either::Left((Err(err), _)) => {
-                    tracing::warn!("{err}");
-                    break;
                 }
-                future::Either::Right((Some(Event::GetStatus(sender)), _)) => {
-                    drop(sender.send(transfer_status.clone()));
-                }
-                future::Either::Right((Some(Event::Stop), _)) => {
-                    summary = Summary::Partial { transfer_status };
-                    break;
+                Err(err) => err,
+            };
+
+            let interval = match retry_interval.next() {
+                Some(interval) if err.is_retryable() => interval,
+                _ => return Err(err),
+            };
+            // the length is unknown, the transfer can only be restarted from the beginning
+            tracing::warn!("Failed to transfer file, restart in {interval:?}, error: {err}");
+            sink.set_len(0)
+                .await
+                .with_context(|_| error::ResizeFileSnafu { file_path: file_path.clone() })?;
+            transfer_status.update_progress(0, 0);
+
+            let timeout = tokio::time::sleep(interval);
+            futures::pin_mut!(timeout);
+            loop {
+                let new_event = event_receiver.recv();
+                futures::pin_mut!(new_event);
+                match future::select(timeout.as_mut(), new_event).await {
+                    future::Either::Left(_) => break,
+                    future::Either::Right((Some(Event::GetStatus(sender)), _)) => {
+                        drop(sender.send(transfer_status.clone()));
+                    }
+                    future::Either::Right((Some(Event::Stop) | None, _)) => {
+                        return Ok(Summary::Partial { transfer_status });
+                    }
+                    future::Either::Right(_) => {}
                 }
-                future::Either::Right(_) => {}
             }
         }
-
-        Ok(summary)
     }
 
     #[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
@@ -226,15 +361,36 @@ impl Downloader {
             worker_number,
             mut transfer_status,
             sink,
-            source,
+            sources,
             file_path,
             event_sender,
             mut event_receiver,
-            mut control_file,
+            control_file,
+            verifies_progress,
             is_completed,
+            retry_interval,
+            rate_limiter,
+            connection_tuning,
         }: ServeWithMultipleWorkerOptions,
     ) -> Result<Summary, Error> {
-        tracing::debug!("Start downloader with {worker_number} connection(s)");
+        if verifies_progress {
+            let verified_status = Self::verify_progress(
+                &control_file,
+                &transfer_status,
+                &sources[0],
+                &mut event_receiver,
+            )
+            .await?;
+            match verified_status {
+                Some(verified_status) => transfer_status = verified_status,
+                None => return Ok(Summary::Partial { transfer_status }),
+            }
+        }
+
+        tracing::debug!(
+            "Start downloader with {worker_number} connection(s) and {} source(s)",
+            sources.len()
+        );
         let (chunk_sender, chunk_receiver) = async_channel::unbounded::<Chunk>();
 
         let mut worker_event_senders = HashMap::new();
@@ -247,15 +403,19 @@ impl Downloader {
             let worker = Worker {
                 id,
                 sink: sink.clone(),
-                source: source.clone(),
+                sources: sources.clone(),
+                source_index: Worker::initial_source_index(id, sources.len()),
                 file_path: file_path.clone(),
                 chunk_receiver: chunk_receiver.clone(),
                 progress_updater: ProgressUpdater::from(event_sender.clone()),
                 event_receiver: worker_event_receiver,
+                retry_interval: retry_interval.clone(),
+                rate_limiter: rate_limiter.clone(),
             };
             let _handle = join_set.spawn(worker.serve());
         }
 
+        transfer_status.split_for_workers(usize::try_from(worker_number).unwrap_or(usize::MAX));
         for chunk in transfer_status.chunks() {
             drop(chunk_sender.send(chunk).await);
         }
@@ -263,8 +423,25 @@ impl Downloader {
 
         let mut next_worker_id = worker_number;
         let mut chunk_to_worker = HashMap::new();
+        // chunks whose workers are asked to stop earlier
+        let mut shrinking_chunks = HashSet::new();
+
+        let mut connection_tuner = connection_tuning.map(ConnectionTuner::new);
+        let connection_tuning_timer = connection_tuner.as_ref().map(|connection_tuner| {
+            let interval = connection_tuner.interval();
+            let event_sender = event_sender.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    if event_sender.send(Event::TuneConnections).is_err() {
+                        break;
+                    }
+                }
+            })
+        });
 
         let mut summary = Summary::Partial { transfer_status: transfer_status.clone() };
+        let mut failure = None;
         while let Some(event) = event_receiver.recv().await {
             match event {
                 Event::ChunkTransferStarted { worker_id, chunk_start } => {
@@ -273,28 +450,113 @@ impl Downloader {
                 }
                 Event::ChunkTransferCompleted { chunk_start, worker_id: _worker_id } => {
                     let _unused = chunk_to_worker.remove(&chunk_start);
+                    let _unused = shrinking_chunks.remove(&chunk_start);
                     transfer_status.mark_chunk_completed(chunk_start);
 
                     if transfer_status.is_completed() {
                         is_completed.store(true, Ordering::Relaxed);
-                        summary = Summary::Completed { transfer_status };
-                        control_file.remove().await;
+                        summary = Summary::Completed { transfer_status: transfer_status.clone() };
                         break;
                     }
+
+                    // the worker is idle if there is no chunk left in the queue
+                    if chunk_sender.is_empty() {
+                        Self::steal_chunk(
+                            &transfer_status,
+                            &chunk_to_worker,
+                            &mut shrinking_chunks,
+                            &worker_event_senders,
+                        );
+                    }
                 }
-                Event::UpdateChunkTransferProgress {
-                    start,
-                    received,
-                    worker_id: _worker_id,
-                    end: _end,
-                } => {
+                Event::ChunkShrunk { worker_id, chunk_start, end } => {
+                    let _unused = shrinking_chunks.remove(&chunk_start);
+                    if let Some(new_chunk) = transfer_status.shrink_chunk(chunk_start, end) {
+                        tracing::debug!(
+                            "Hand chunk {}-{} of worker {worker_id} over to an idle worker",
+                            new_chunk.start,
+                            new_chunk.end
+                        );
+                        let _ = chunk_sender.send(new_chunk).await;
+                        Self::persist_layout(&control_file, &transfer_status).await;
+                    }
+                }
+                Event::ChunkTransferFailed { worker_id, chunk_start, error } => {
+                    tracing::warn!(
+                        "Worker {worker_id} gave up chunk starting at {chunk_start}, error: \
+                         {error}"
+                    );
+                    let _unused = chunk_to_worker.remove(&chunk_start);
+                    let _unused = shrinking_chunks.remove(&chunk_start);
+                    let _unused = worker_event_senders.remove(&worker_id);
+                    failure = Some(error);
+                    break;
+                }
+                Event::ChunkTransferThrottled { worker_id: _worker_id } => {
+                    if let Some(connection_tuner) = connection_tuner.as_mut() {
+                        connection_tuner.throttle();
+                    }
+                }
+                Event::UpdateChunkTransferProgress { start, received, worker_id, end: _end } => {
+                    if let Some(connection_tuner) = connection_tuner.as_mut()
+                        && let Some(chunk) = transfer_status.chunks.get(&start)
+                    {
+                        connection_tuner.record(worker_id, received.saturating_sub(chunk.received));
+                    }
                     transfer_status.update_progress(start, received);
                 }
+                Event::ChunkTransferRemoved { worker_id, chunk_start, received } => {
+                    let _unused = chunk_to_worker.remove(&chunk_start);
+                    let _unused = shrinking_chunks.remove(&chunk_start);
+                    transfer_status.update_progress(chunk_start, received);
+                    if let Some(new_chunk) = transfer_status.release_chunk(chunk_start) {
+                        tracing::debug!(
+                            "Hand chunk {}-{} of removed worker {worker_id} over to other workers",
+                            new_chunk.start,
+                            new_chunk.end
+                        );
+                        let _ = chunk_sender.send(new_chunk).await;
+                        Self::persist_layout(&control_file, &transfer_status).await;
+                    }
+                }
+                Event::TuneConnections => {
+                    if let Some(connection_tuner) = connection_tuner.as_mut()
+                        && let Some(decision) =
+                            connection_tuner.tune(worker_event_senders.len() as u64)
+                    {
+                        let is_applied = match decision.action {
+                            model::ConnectionTuningAction::Increase => {
+                                drop(event_sender.send(Event::AddWorker));
+                                true
+                            }
+                            model::ConnectionTuningAction::Decrease
+                            | model::ConnectionTuningAction::BackOff => {
+                                Self::stop_worker(
+                                    &transfer_status,
+                                    &chunk_to_worker,
+                                    &shrinking_chunks,
+                                    &mut worker_event_senders,
+                                )
+                                .await
+                            }
+                        };
+                        // the decision is made again at the next interval
+                        if is_applied {
+                            tracing::info!(
+                                "Connection tuning: {}, {} connection(s), speed: {} B/s",
+                                decision.action,
+                                decision.concurrent_number,
+                                decision.speed
+                            );
+                            connection_tuner.apply();
+                            transfer_status.log_connection_tuning(decision);
+                            transfer_status.update_concurrent_number(worker_event_senders.len());
+                        }
+                    }
+                }
                 Event::GetStatus(sender) => drop(sender.send(transfer_status.clone())),
                 Event::Stop => {
-                    control_file.update_progress(&transfer_status).await?;
-                    control_file.flush().await?;
-                    summary = Summary::Partial { transfer_status };
+                    summary = Summary::Partial { transfer_status: transfer_status.clone() };
                     break;
                 }
                 Event::AddWorker => {
@@ -309,44 +571,45 @@ impl Downloader {
                             chunk_receiver: chunk_receiver.clone(),
                             progress_updater: ProgressUpdater::from(event_sender.clone()),
                             sink: sink.clone(),
-                            source: source.clone(),
+                            sources: sources.clone(),
+                            source_index: Worker::initial_source_index(
+                                next_worker_id,
+                                sources.len(),
+                            ),
                             file_path: file_path.clone(),
                             event_receiver: worker_event_receiver,
+                            retry_interval: retry_interval.clone(),
+                            rate_limiter: rate_limiter.clone(),
                         };
 
                         join_set.spawn(worker.serve())
                     };
                     next_worker_id += 1;
 
-                    if let Some((origin_chunk, new_chunk)) = transfer_status.split() {
-                        if let Some(worker_id) = chunk_to_worker.get(&origin_chunk.start)
-                            && let Some(worker) = worker_event_senders.get(worker_id)
-                        {
-                            let (sender, wait) = oneshot::channel();
-                            drop(worker.send(WorkerEvent::Stop(sender)));
-                            let _ = wait.await;
-                        }
-                        let _ = chunk_sender.send(new_chunk).await;
-                        let _ = chunk_sender.send(origin_chunk).await;
-                    }
+                    Self::steal_chunk(
+                        &transfer_status,
+                        &chunk_to_worker,
+                        &mut shrinking_chunks,
+                        &worker_event_senders,
+                    );
                     transfer_status.update_concurrent_number(worker_event_senders.len());
                 }
                 Event::RemoveWorker => {
-                    if let Some((origin_chunk, new_chunk)) = transfer_status.freeze() {
-                        if let Some(worker_id) = chunk_to_worker.get(&origin_chunk.start)
-                            && let Some(worker) = worker_event_senders.remove(worker_id)
-                        {
-                            let (sender, wait) = oneshot::channel();
-                            drop(worker.send(WorkerEvent::Remove(sender)));
-                            let _ = wait.await;
-                        }
-                        let _ = chunk_sender.send(new_chunk).await;
-                    }
+                    let _unused = Self::stop_worker(
+                        &transfer_status,
+                        &chunk_to_worker,
+                        &shrinking_chunks,
+                        &mut worker_event_senders,
+                    )
+                    .await;
                     transfer_status.update_concurrent_number(worker_event_senders.len());
                 }
             }
         }
 
+        if let Some(connection_tuning_timer) = connection_tuning_timer {
+            connection_tuning_timer.abort();
+        }
         let _ = chunk_sender.close();
 
         let mut waiters = Vec::new();
@@ -372,20 +635,155 @@ impl Downloader {
             drop(sink);
         }
 
-        Ok(summary)
+        if is_completed.load(Ordering::Relaxed) {
+            control_file.remove().await;
+        } else {
+            // keep the progress after the file is synchronized, the download can be resumed
+            // later
+            control_file.update_progress(&transfer_status).await?;
+        }
+
+        failure.map_or(Ok(summary), Err)
+    }
+
+    /// Ask the worker of the chunk with the most remaining bytes among the
+    /// chunks being transferred to stop at the end of the first half, the rest
+    /// is handed over to an idle worker once the worker replies with the end
+    /// it actually stops at
+    fn steal_chunk(
+        transfer_status: &TransferStatus,
+        chunk_to_worker: &HashMap<u64, u64>,
+        shrinking_chunks: &mut HashSet<u64>,
+        worker_event_senders: &HashMap<u64, mpsc::UnboundedSender<WorkerEvent>>,
+    ) {
+        let Some((chunk, worker_id)) = chunk_to_worker
+            .iter()
+            .filter(|(chunk_start, _)| !shrinking_chunks.contains(chunk_start))
+            .filter_map(|(chunk_start, worker_id)| {
+                transfer_status.chunks.get(chunk_start).map(|chunk| (chunk, *worker_id))
+            })
+            .max_by_key(|(chunk, _)| chunk.remaining())
+        else {
+            return;
+        };
+        // the progress of the worker may be ahead of `chunk.received`, the
+        // worker clamps the end to its actual position
+        let Some(new_chunk) = chunk.clone().split() else {
+            return;
+        };
+        if let Some(worker) = worker_event_senders.get(&worker_id) {
+            let chunk_start = chunk.start;
+            drop(worker.send(WorkerEvent::Shrink { chunk_start, end: new_chunk.start - 1 }));
+            let _unused = shrinking_chunks.insert(chunk_start);
+        }
+    }
+
+    /// Remove an idle worker, or the worker of the chunk with the most
+    /// remaining bytes among the chunks being transferred, the worker reports
+    /// the progress of its chunk so that the rest of it is handed over to the
+    /// other workers. The last worker is kept and `false` is returned if no
+    /// worker is removed
+    async fn stop_worker(
+        transfer_status: &TransferStatus,
+        chunk_to_worker: &HashMap<u64, u64>,
+        shrinking_chunks: &HashSet<u64>,
+        worker_event_senders: &mut HashMap<u64, mpsc::UnboundedSender<WorkerEvent>>,
+    ) -> bool {
+        if worker_event_senders.len() <= 1 {
+            return false;
+        }
+        let busy_workers = chunk_to_worker.values().collect::<HashSet<_>>();
+        let worker_id = worker_event_senders
+            .keys()
+            .filter(|worker_id| !busy_workers.contains(worker_id))
+            .max()
+            .copied()
+            .or_else(|| {
+                chunk_to_worker
+                    .iter()
+                    // the worker replies to the request to stop earlier first
+                    .filter(|(chunk_start, _)| !shrinking_chunks.contains(chunk_start))
+                    .filter_map(|(chunk_start, worker_id)| {
+                        transfer_status.chunks.get(chunk_start).map(|chunk| (chunk, *worker_id))
+                    })
+                    .max_by_key(|(chunk, _)| chunk.remaining())
+                    .map(|(_, worker_id)| worker_id)
+            });
+        let Some(worker) = worker_id.and_then(|worker_id| worker_event_senders.remove(&worker_id))
+        else {
+            return false;
+        };
+        let (sender, wait) = oneshot::channel();
+        drop(worker.send(WorkerEvent::Remove(sender)));
+        let _ = wait.await;
+        true
+    }
+
+    /// Check the progress loaded from the control file before the workers
+    /// start, the loaded progress is reported in the meantime and `None` is
+    /// returned if the downloader is stopped
+    async fn verify_progress(
+        control_file: &ControlFile,
+        transfer_status: &TransferStatus,
+        source: &Fetcher,
+        event_receiver: &mut mpsc::UnboundedReceiver<Event>,
+    ) -> Result<Option<TransferStatus>, Error> {
+        let mut verified_status = transfer_status.clone();
+        {
+            let mut source = source.clone();
+            let verification = control_file.verify(&mut verified_status, &mut source);
+            futures::pin_mut!(verification);
+            loop {
+                let new_event = event_receiver.recv();
+                futures::pin_mut!(new_event);
+                match future::select(verification.as_mut(), new_event).await {
+                    future::Either::Left((result, _)) => break result?,
+                    future::Either::Right((Some(Event::GetStatus(sender)), _)) => {
+                        drop(sender.send(transfer_status.clone()));
+                    }
+                    future::Either::Right((Some(Event::Stop) | None, _)) => return Ok(None),
+                    future::Either::Right(_) => {}
+                }
+            }
+        }
+        control_file.update_progress(&verified_status).await?;
+        Ok(Some(verified_status))
     }
+
+    // the download is not interrupted if the layout can not be saved, the control
+    // file is updated again when the workers are shut down
+    async fn persist_layout(control_file: &ControlFile, transfer_status: &TransferStatus) {
+        if let Err(err) = control_file.update_layout(transfer_status).await {
+            tracing::warn!("Failed to save the layout of chunks, error: {err}");
+        }
+    }
+}
+
+struct ServeWithSingleWorkerOptions {
+    transfer_status: TransferStatus,
+    sink: File,
+    source: Fetcher,
+    file_path: PathBuf,
+    event_receiver: mpsc::UnboundedReceiver<Event>,
+    is_completed: Arc<AtomicBool>,
+    retry_interval: RetryInterval,
+    rate_limiter: RateLimiter,
 }
 
 struct ServeWithMultipleWorkerOptions {
     worker_number: u64,
     transfer_status: TransferStatus,
     sink: Arc<Mutex<File>>,
-    source: Fetcher,
+    sources: Vec<Fetcher>,
     file_path: PathBuf,
     event_sender: mpsc::UnboundedSender<Event>,
     event_receiver: mpsc::UnboundedReceiver<Event>,
     control_file: ControlFile,
+    verifies_progress: bool,
     is_completed: Arc<AtomicBool>,
+    retry_interval: RetryInterval,
+    rate_limiter: RateLimiter,
+    connection_tuning: Option<ConnectionTuning>,
 }
 
 enum Event {
@@ -396,6 +794,11 @@ enum Event {
     UpdateChunkTransferProgress { worker_id: u64, start: u64, end: u64, received: u64 },
     ChunkTransferStarted { worker_id: u64, chunk_start: u64 },
     ChunkTransferCompleted { worker_id: u64, chunk_start: u64 },
+    ChunkTransferFailed { worker_id: u64, chunk_start: u64, error: Error },
+    ChunkTransferThrottled { worker_id: u64 },
+    ChunkShrunk { worker_id: u64, chunk_start: u64, end: u64 },
+    ChunkTransferRemoved { worker_id: u64, chunk_start: u64, received: u64 },
+    TuneConnections,
 }
 
 #[derive(Clone, Debug)]
@@ -403,3 +806,199 @@ enum Summary {
     Completed { transfer_status: TransferStatus },
     Partial { transfer_status: TransferStatus },
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use caracal_base::{model, utils::RetryInterval};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::{ConnectionTuning, ControlFile};
+    use crate::DownloaderFactory;
+
+    #[tokio::test]
+    async fn test_resume_after_interrupted_verification() {
+        let content = (0..4096_u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        let _server = tokio::spawn({
+            let content = content.clone();
+            async move {
+                let mut pending_streams = Vec::new();
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = vec![0; 4096];
+                    let len = stream.read(&mut request).await.unwrap();
+                    let request = String::from_utf8_lossy(&request[..len]).to_string();
+                    if request.starts_with("HEAD") {
+                        let response =
+                            "HTTP/1.1 200 OK\r\nContent-Length: 4096\r\nConnection: close\r\n\r\n";
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        continue;
+                    }
+                    let range = request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("range: bytes="))
+                        .unwrap();
+                    let (start, end) = range.split_once('-').unwrap();
+                    let (start, end) =
+                        (start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap());
+                    // the first sample of the second chunk is never answered
+                    if start == 2048 && pending_streams.is_empty() {
+                        pending_streams.push(stream);
+                        continue;
+                    }
+                    let response = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes \
+                         {start}-{end}/4096\r\nConnection: close\r\n\r\n"
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                    stream.write_all(&content[start..=end]).await.unwrap();
+                }
+            }
+        });
+
+        let directory = std::env::temp_dir()
+            .join(format!("caracal-test-interrupted-verification-{}", std::process::id()));
+        tokio::fs::create_dir_all(&directory).await.unwrap();
+        let file_path = directory.join("file.bin");
+        let mut data = content.clone();
+        data[100] = 0;
+        tokio::fs::write(&file_path, &data).await.unwrap();
+        // the first chunk has a checksum and is corrupted, the second one is
+        // compared with the source
+        let control = serde_json::json!({
+            "schema": 2,
+            "uris": [uri],
+            "fetcher": null,
+            "etag": null,
+            "last_modified": null,
+            "content_length": 4096,
+            "chunks": [
+                {
+                    "start": 0,
+                    "end": 2047,
+                    "received": 1024,
+                    "is_completed": false,
+                    "crc32": crc32fast::hash(&content[..1024]),
+                },
+                {
+                    "start": 2048,
+                    "end": 4095,
+                    "received": 1024,
+                    "is_completed": false,
+                    "crc32": null,
+                },
+            ],
+        });
+        let control_file_path = ControlFile::file_path(&file_path);
+        tokio::fs::write(&control_file_path, control.to_string()).await.unwrap();
+
+        let factory = DownloaderFactory::builder().unwrap().verify_on_resume(true).build().unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.clone()),
+            concurrent_number: Some(2),
+            ..model::CreateTask::new(uri.parse().unwrap())
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        downloader.pause().await.unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_slice(&tokio::fs::read(&control_file_path).await.unwrap()).unwrap();
+        assert_eq!(saved["chunks"][0]["crc32"], control["chunks"][0]["crc32"]);
+
+        // the corrupted chunk is still rejected and downloaded again
+        downloader.resume().await.unwrap();
+        drop(downloader.join().await.unwrap());
+        assert_eq!(tokio::fs::read(&file_path).await.unwrap(), content);
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+
+    #[tokio::test]
+    async fn test_back_off_before_chunks_start() {
+        let content = (0..1024 * 1024_u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        let _server = tokio::spawn({
+            let content = content.clone();
+            async move {
+                let mut throttled_requests = 0;
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = vec![0; 4096];
+                    let len = stream.read(&mut request).await.unwrap();
+                    let request = String::from_utf8_lossy(&request[..len]).to_string();
+                    if request.starts_with("HEAD") {
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            content.len()
+                        );
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        continue;
+                    }
+                    // every worker is asked to slow down before it receives anything
+                    if throttled_requests < 4 {
+                        throttled_requests += 1;
+                        let response = "HTTP/1.1 503 Service Unavailable\r\nContent-Length: \
+                                        0\r\nConnection: close\r\n\r\n";
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        continue;
+                    }
+                    let range = request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("range: bytes="))
+                        .unwrap();
+                    let (start, end) = range.split_once('-').unwrap();
+                    let (start, end) =
+                        (start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap());
+                    let response = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes \
+                         {start}-{end}/{}\r\nConnection: close\r\n\r\n",
+                        content.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                    stream.write_all(&content[start..=end]).await.unwrap();
+                }
+            }
+        });
+
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-back-off-{}", std::process::id()));
+        let factory = DownloaderFactory::builder()
+            .unwrap()
+            .retry_interval(RetryInterval::new(10, Duration::from_millis(500)))
+            .connection_tuning(Some(ConnectionTuning {
+                min_connections: 1,
+                max_connections: 4,
+                interval: Duration::from_millis(100),
+            }))
+            .build()
+            .unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.clone()),
+            concurrent_number: Some(4),
+            ..model::CreateTask::new(uri.parse().unwrap())
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        // the chunk of the removed worker is transferred by the other workers
+        let (transfer_status, _) = tokio::time::timeout(Duration::from_secs(10), downloader.join())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(tokio::fs::read(directory.join("file.bin")).await.unwrap(), content);
+        let decision = &transfer_status.connection_tuning_log[0];
+        assert_eq!(decision.action, model::ConnectionTuningAction::BackOff);
+        assert_eq!(decision.concurrent_number, 3);
+        assert_eq!(transfer_status.concurrent_number, 3);
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+}
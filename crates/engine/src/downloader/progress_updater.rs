@@ -1,6 +1,6 @@
 use tokio::sync::mpsc;
 
-use crate::downloader::Event;
+use crate::{downloader::Event, error::Error};
 
 #[derive(Clone)]
 pub struct ProgressUpdater(mpsc::UnboundedSender<Event>);
@@ -18,6 +18,22 @@ impl ProgressUpdater {
         drop(self.0.send(Event::ChunkTransferCompleted { worker_id, chunk_start }));
     }
 
+    pub fn signal_failed(&self, worker_id: u64, chunk_start: u64, error: Error) {
+        drop(self.0.send(Event::ChunkTransferFailed { worker_id, chunk_start, error }));
+    }
+
+    pub fn signal_shrunk(&self, worker_id: u64, chunk_start: u64, end: u64) {
+        drop(self.0.send(Event::ChunkShrunk { worker_id, chunk_start, end }));
+    }
+
+    pub fn signal_removed(&self, worker_id: u64, chunk_start: u64, received: u64) {
+        drop(self.0.send(Event::ChunkTransferRemoved { worker_id, chunk_start, received }));
+    }
+
+    pub fn signal_throttled(&self, worker_id: u64) {
+        drop(self.0.send(Event::ChunkTransferThrottled { worker_id }));
+    }
+
     pub fn update(&self, worker_id: u64, start: u64, end: u64, received: u64) {
         drop(self.0.send(Event::UpdateChunkTransferProgress { worker_id, start, end, received }));
     }
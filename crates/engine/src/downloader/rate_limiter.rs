@@ -0,0 +1,138 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Token bucket shared by the workers which should be throttled together,
+/// bytes are consumed from the bucket and its parents
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+
+    parent: Option<Box<Self>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    // bytes per second, `None` for unlimited
+    limit: Option<u64>,
+
+    // may be negative, the debt has to be paid off before transferring more bytes
+    available: f64,
+
+    last_refill: Instant,
+}
+
+impl Bucket {
+    #[allow(clippy::cast_precision_loss)]
+    fn refill(&mut self) {
+        let now = Instant::now();
+        if let Some(limit) = self.limit {
+            let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+            self.available = elapsed.mul_add(limit as f64, self.available).min(limit as f64);
+        }
+        self.last_refill = now;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn pending(&mut self) -> Duration {
+        self.refill();
+        match self.limit {
+            Some(limit) if self.available < 0.0 => {
+                Duration::from_secs_f64(-self.available / limit as f64)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(limit: Option<u64>) -> Self {
+        let limit = limit.filter(|&limit| limit > 0);
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                limit,
+                available: 0.0,
+                last_refill: Instant::now(),
+            })),
+            parent: None,
+        }
+    }
+
+    /// Create a new limiter, bytes consumed from it are also consumed from
+    /// `self`
+    #[must_use]
+    pub fn child(&self, limit: Option<u64>) -> Self {
+        Self { parent: Some(Box::new(self.clone())), ..Self::new(limit) }
+    }
+
+    #[must_use]
+    pub fn limit(&self) -> Option<u64> { self.bucket.lock().expect("lock is not poisoned").limit }
+
+    pub fn set_limit(&self, limit: Option<u64>) {
+        let mut bucket = self.bucket.lock().expect("lock is not poisoned");
+        bucket.refill();
+        bucket.limit = limit.filter(|&limit| limit > 0);
+        bucket.available = bucket.available.min(0.0);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn consume(&self, bytes: u64) {
+        {
+            let mut bucket = self.bucket.lock().expect("lock is not poisoned");
+            if bucket.limit.is_some() {
+                bucket.refill();
+                bucket.available -= bytes as f64;
+            }
+        }
+        if let Some(parent) = &self.parent {
+            parent.consume(bytes);
+        }
+    }
+
+    /// Wait until the bucket and its parents are able to transfer more bytes
+    pub async fn ready(&self) {
+        loop {
+            let pending = self.pending();
+            if pending.is_zero() {
+                return;
+            }
+            // the limit may be changed while sleeping, check it again after waking up
+            tokio::time::sleep(pending.min(Duration::from_millis(500))).await;
+        }
+    }
+
+    fn pending(&self) -> Duration {
+        let pending = self.bucket.lock().expect("lock is not poisoned").pending();
+        self.parent.as_ref().map_or(pending, |parent| pending.max(parent.pending()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RateLimiter;
+
+    #[tokio::test]
+    async fn test_pending() {
+        let global = RateLimiter::new(Some(1000));
+        let task = global.child(None);
+        assert_eq!(task.pending(), Duration::ZERO);
+
+        task.consume(500);
+        let pending = task.pending();
+        assert!(pending > Duration::from_millis(400) && pending <= Duration::from_millis(500));
+
+        global.set_limit(None);
+        assert_eq!(task.pending(), Duration::ZERO);
+
+        task.set_limit(Some(1000));
+        task.consume(500);
+        assert!(task.pending() > Duration::from_millis(400));
+        assert_eq!(global.pending(), Duration::ZERO);
+    }
+}
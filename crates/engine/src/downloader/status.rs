@@ -13,6 +13,8 @@ pub struct DownloaderStatus {
     chunks: Vec<model::ProgressChunk>,
 
     concurrent_number: usize,
+
+    connection_tuning_log: Vec<model::ConnectionTuningDecision>,
 }
 
 impl DownloaderStatus {
@@ -23,6 +25,7 @@ impl DownloaderStatus {
             content_length: 0,
             chunks: Vec::new(),
             concurrent_number: 0,
+            connection_tuning_log: Vec::new(),
         }
     }
 
@@ -36,6 +39,25 @@ impl DownloaderStatus {
             content_length: 0,
             chunks: Vec::new(),
             concurrent_number: 0,
+            connection_tuning_log: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_progress<P>(
+        file_path: P,
+        content_length: u64,
+        chunks: Vec<model::ProgressChunk>,
+    ) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            file_path: file_path.as_ref().to_path_buf(),
+            content_length,
+            chunks,
+            concurrent_number: 0,
+            connection_tuning_log: Vec::new(),
         }
     }
 
@@ -85,8 +107,30 @@ impl DownloaderStatus {
         self.file_path = file_path.as_ref().to_path_buf();
     }
 
+    /// Split the chunks at the boundaries of pieces
+    pub fn split_into_pieces(&mut self, piece_length: u64) {
+        let mut pieces = Vec::with_capacity(self.chunks.len());
+        for chunk in &self.chunks {
+            let mut start = chunk.start;
+            while start <= chunk.end {
+                let end = chunk.end.min((start / piece_length + 1) * piece_length - 1);
+                let len = end - start + 1;
+                let received = chunk.received.saturating_sub(start - chunk.start).min(len);
+                let is_completed = chunk.is_completed || received == len;
+                pieces.push(model::ProgressChunk { start, end, received, is_completed });
+                start = end + 1;
+            }
+        }
+        self.chunks = pieces;
+    }
+
     #[must_use]
     pub const fn concurrent_number(&self) -> usize { self.concurrent_number }
+
+    #[must_use]
+    pub fn connection_tuning_log(&self) -> Vec<model::ConnectionTuningDecision> {
+        self.connection_tuning_log.clone()
+    }
 }
 
 impl Default for DownloaderStatus {
@@ -101,6 +145,7 @@ impl From<TransferStatus> for DownloaderStatus {
             content_length: status.content_length(),
             chunks,
             concurrent_number: status.concurrent_number(),
+            connection_tuning_log: status.connection_tuning_log,
         }
     }
 }
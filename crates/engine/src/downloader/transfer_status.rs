@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use caracal_base::model;
+
 use crate::{downloader::Chunk, error::Error};
 
 #[derive(Clone, Debug)]
@@ -9,21 +11,26 @@ pub struct TransferStatus {
     pub chunks: HashMap<u64, Chunk>,
 
     pub concurrent_number: usize,
+
+    pub connection_tuning_log: Vec<model::ConnectionTuningDecision>,
 }
 
+// the number of decisions of the connection tuner kept in the status
+const MAX_CONNECTION_TUNING_LOG_LENGTH: usize = 32;
+
 impl TransferStatus {
     #[allow(clippy::result_large_err)]
     pub fn new(content_length: u64, chunk_size: u64) -> Result<Self, Error> {
         let chunks = InitialChunks::new(0, content_length - 1, chunk_size)?
             .map(|chunk| (chunk.start, chunk))
             .collect();
-        Ok(Self { content_length, chunks, concurrent_number: 1 })
+        Ok(Self { content_length, chunks, concurrent_number: 1, connection_tuning_log: Vec::new() })
     }
 
     pub fn unknown_length() -> Self {
         let chunks =
             HashMap::from([(0, Chunk { start: 0, end: 0, received: 0, is_completed: false })]);
-        Self { content_length: 0, chunks, concurrent_number: 1 }
+        Self { content_length: 0, chunks, concurrent_number: 1, connection_tuning_log: Vec::new() }
     }
 
     pub fn chunks(&self) -> Vec<Chunk> {
@@ -41,6 +48,14 @@ impl TransferStatus {
         }
     }
 
+    /// Discard the received data of the chunk, it is downloaded again
+    pub fn reset_progress(&mut self, id: u64) {
+        if let Some(chunk) = self.chunks.get_mut(&id) {
+            chunk.received = 0;
+            chunk.is_completed = false;
+        }
+    }
+
     pub fn mark_chunk_completed(&mut self, id: u64) {
         let _unused = self.chunks.get_mut(&id).map(|chunk| chunk.is_completed = true);
     }
@@ -56,48 +71,69 @@ impl TransferStatus {
         self.content_length.saturating_sub(total_received)
     }
 
-    pub fn split(&mut self) -> Option<(Chunk, Chunk)> {
-        if self.is_completed() {
-            None
-        } else {
-            let mut chunks: Vec<_> = self.chunks.values_mut().collect();
-            chunks.sort_unstable_by_key(|c| c.remaining());
-            let (origin_chunk, new_chunk) = if let Some(origin_chunk) = chunks.pop() {
-                let new_chunk = origin_chunk.split();
-                (origin_chunk.clone(), new_chunk)
-            } else {
-                return None;
-            };
-            if let Some(new_chunk) = new_chunk {
-                let _ = self.chunks.insert(new_chunk.start, new_chunk.clone());
-                Some((origin_chunk, new_chunk))
-            } else {
-                None
-            }
+    /// Split the remaining part of the chunk in half, the second half is added
+    /// as a new chunk and returned
+    pub fn split_chunk(&mut self, id: u64) -> Option<Chunk> {
+        let new_chunk = self.chunks.get_mut(&id)?.split()?;
+        let _unused = self.chunks.insert(new_chunk.start, new_chunk.clone());
+        Some(new_chunk)
+    }
+
+    /// Cut the chunk at `end` once its worker has agreed to stop there, the
+    /// rest of the chunk is returned as a new chunk
+    pub fn shrink_chunk(&mut self, id: u64, end: u64) -> Option<Chunk> {
+        let chunk = self.chunks.get_mut(&id)?;
+        if chunk.is_completed || end >= chunk.end {
+            return None;
         }
+        let new_chunk = Chunk { start: end + 1, end: chunk.end, received: 0, is_completed: false };
+        chunk.end = end;
+        let _unused = self.chunks.insert(new_chunk.start, new_chunk.clone());
+        Some(new_chunk)
     }
 
-    pub fn freeze(&mut self) -> Option<(Chunk, Chunk)> {
-        if self.is_completed() {
-            None
-        } else {
-            let mut chunks: Vec<_> = self.chunks.values_mut().collect();
-            chunks.sort_unstable_by_key(|c| c.remaining());
-            let (origin_chunk, new_chunk) = if let Some(origin_chunk) = chunks.pop() {
-                let new_chunk = origin_chunk.freeze();
-                (origin_chunk.clone(), new_chunk)
-            } else {
-                return None;
+    /// Split the chunks with the most remaining bytes until every worker has an
+    /// unfinished chunk to transfer
+    pub fn split_for_workers(&mut self, worker_number: usize) {
+        while self.chunks.values().filter(|chunk| !chunk.is_completed).count() < worker_number {
+            let Some(id) = self
+                .chunks
+                .values()
+                .filter(|chunk| !chunk.is_completed)
+                .max_by_key(|chunk| chunk.remaining())
+                .map(|chunk| chunk.start)
+            else {
+                break;
             };
-            if let Some(new_chunk) = new_chunk {
-                let _ = self.chunks.insert(new_chunk.start, new_chunk.clone());
-                Some((origin_chunk, new_chunk))
-            } else {
-                None
+            if self.split_chunk(id).is_none() {
+                break;
             }
         }
     }
 
+    /// Give up the rest of the chunk whose worker is removed, the received
+    /// part is kept as a completed chunk and the part left to transfer is
+    /// returned
+    pub fn release_chunk(&mut self, id: u64) -> Option<Chunk> {
+        let chunk = self.chunks.get_mut(&id)?;
+        if chunk.is_completed {
+            return None;
+        }
+        let Some(new_chunk) = chunk.freeze() else {
+            // nothing is received, the whole chunk is handed over
+            return Some(chunk.clone());
+        };
+        let _unused = self.chunks.insert(new_chunk.start, new_chunk.clone());
+        Some(new_chunk)
+    }
+
+    pub fn log_connection_tuning(&mut self, decision: model::ConnectionTuningDecision) {
+        if self.connection_tuning_log.len() >= MAX_CONNECTION_TUNING_LOG_LENGTH {
+            let _unused = self.connection_tuning_log.remove(0);
+        }
+        self.connection_tuning_log.push(decision);
+    }
+
     #[must_use]
     pub const fn concurrent_number(&self) -> usize { self.concurrent_number }
 
@@ -142,3 +178,34 @@ impl Iterator for InitialChunks {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TransferStatus;
+    use crate::downloader::Chunk;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_steal_chunk_from_worker_ahead_of_status() {
+        let mut transfer_status = TransferStatus::new(4 * MB, 4 * MB).unwrap();
+        transfer_status.update_progress(0, MB);
+
+        // the split is proposed from the stale progress, the worker is already
+        // at 3 MB when it receives the request
+        let mut chunk = transfer_status.chunks[&0].clone();
+        let end = chunk.clone().split().unwrap().start - 1;
+        assert!(chunk.shrink(0, end, 3 * MB));
+        assert_eq!(chunk.end, 3 * MB);
+
+        let new_chunk = transfer_status.shrink_chunk(0, chunk.end).unwrap();
+        assert_eq!((new_chunk.start, new_chunk.end), (3 * MB + 1, 4 * MB - 1));
+        assert_eq!(transfer_status.chunks[&0].end, 3 * MB);
+        assert_eq!(transfer_status.chunks().iter().map(Chunk::len).sum::<u64>(), 4 * MB);
+
+        // nothing is handed over if the worker can not stop earlier
+        assert!(transfer_status.shrink_chunk(0, 3 * MB).is_none());
+        transfer_status.update_progress(new_chunk.start, new_chunk.len());
+        assert!(transfer_status.shrink_chunk(new_chunk.start, new_chunk.start).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use std::{io::SeekFrom, path::PathBuf, sync::Arc};
 
+use caracal_base::utils::RetryInterval;
 use futures::future;
 use snafu::ResultExt;
 use tokio::{
@@ -9,7 +10,7 @@ use tokio::{
 };
 
 use crate::{
-    downloader::{Chunk, ProgressUpdater},
+    downloader::{Chunk, ProgressUpdater, RateLimiter},
     error,
     error::Error,
     fetcher::Fetcher,
@@ -18,26 +19,27 @@ use crate::{
 pub struct Worker {
     pub id: u64,
     pub sink: Arc<Mutex<File>>,
-    pub source: Fetcher,
+    // the origin and its mirrors, all of them serve the same content
+    pub sources: Vec<Fetcher>,
+    pub source_index: usize,
     pub file_path: PathBuf,
     pub chunk_receiver: async_channel::Receiver<Chunk>,
     pub event_receiver: mpsc::UnboundedReceiver<WorkerEvent>,
     pub progress_updater: ProgressUpdater,
+    pub retry_interval: RetryInterval,
+    pub rate_limiter: RateLimiter,
 }
 
 impl Worker {
-    pub async fn serve(self) -> Result<(), Error> {
-        let Self {
-            id,
-            mut source,
-            sink,
-            file_path,
-            chunk_receiver,
-            mut event_receiver,
-            progress_updater,
-        } = self;
-
-        while let Ok(chunk) = chunk_receiver.recv().await {
+    // spread the workers over the sources
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn initial_source_index(id: u64, source_count: usize) -> usize {
+        (id % source_count as u64) as usize
+    }
+
+    pub async fn serve(mut self) -> Result<(), Error> {
+        let id = self.id;
+        while let Some(mut chunk) = self.next_chunk().await {
             tracing::debug!(
                 "Transfer chunk in range {}-{}, received: {}, length: {}, worker: {id}",
                 chunk.start,
@@ -45,64 +47,260 @@ impl Worker {
                 chunk.received,
                 chunk.len()
             );
-            progress_updater.signal_started(id, chunk.start);
+            self.progress_updater.signal_started(id, chunk.start);
             if chunk.received >= chunk.len() {
-                progress_updater.signal_completed(id, chunk.start);
+                self.progress_updater.signal_completed(id, chunk.start);
                 continue;
             }
-            let mut received = chunk.received;
-            let mut stream = source.fetch_bytes(chunk.start + chunk.received, chunk.end).await?;
 
+            let mut received = chunk.received;
+            let mut retry_interval = self.retry_interval.clone();
+            let mut failed_sources = 0;
             loop {
-                let new_bytes = stream.bytes();
-                let new_event = event_receiver.recv();
-                futures::pin_mut!(new_bytes);
-                futures::pin_mut!(new_event);
-
-                match future::select(new_bytes, new_event).await {
-                    future::Either::Left((Ok(Some(bytes)), _)) => {
-                        {
-                            let mut sink = sink.lock().await;
-                            let _ = sink
-                                .seek(SeekFrom::Start(chunk.start + received))
-                                .await
-                                .with_context(|_| error::SeekFileSnafu {
-                                    file_path: file_path.clone(),
-                                })?;
-                            sink.write_all(bytes).await.with_context(|_| {
-                                error::WriteFileSnafu { file_path: file_path.clone() }
-                            })?;
-                            drop(sink);
-                        }
-                        received += bytes.len() as u64;
-                        progress_updater.update(id, chunk.start, chunk.end, received);
-                    }
-                    future::Either::Left((Ok(None), _)) => {
-                        progress_updater.signal_completed(id, chunk.start);
+                match self.transfer_chunk(&mut chunk, &mut received).await? {
+                    Transfer::Completed => {
+                        self.progress_updater.signal_completed(id, chunk.start);
                         break;
                     }
-                    future::Either::Left((Err(err), _)) => {
-                        tracing::warn!("{err}");
-                        break;
-                    }
-                    future::Either::Right((Some(WorkerEvent::Remove(sender)), _)) => {
-                        let _ = sender.send(());
-                        return Ok(());
+                    Transfer::Stopped => break,
+                    Transfer::Removed => return Ok(()),
+                    Transfer::Interrupted(err) if !err.is_retryable() => {
+                        failed_sources += 1;
+                        if failed_sources >= self.sources.len() {
+                            self.progress_updater.signal_failed(id, chunk.start, err);
+                            return Ok(());
+                        }
+                        self.switch_source(&chunk, &err);
                     }
-                    future::Either::Right((Some(WorkerEvent::Stop(sender)), _)) => {
-                        let _ = sender.send(());
-                        break;
+                    Transfer::Interrupted(err) => {
+                        if err.is_throttled() {
+                            self.progress_updater.signal_throttled(id);
+                        }
+                        let Some(interval) = retry_interval.next() else {
+                            self.progress_updater.signal_failed(id, chunk.start, err);
+                            return Ok(());
+                        };
+                        if self.sources.len() > 1 {
+                            self.switch_source(&chunk, &err);
+                        }
+                        tracing::warn!(
+                            "Failed to transfer chunk {}-{}, retry in {interval:?}, error: {err}",
+                            chunk.start,
+                            chunk.end
+                        );
+
+                        let timeout = tokio::time::sleep(interval);
+                        let new_event = self.event_receiver.recv();
+                        futures::pin_mut!(timeout);
+                        futures::pin_mut!(new_event);
+                        match future::select(timeout, new_event).await {
+                            future::Either::Left(_) => {}
+                            future::Either::Right((Some(WorkerEvent::Remove(sender)), _)) => {
+                                self.progress_updater.signal_removed(id, chunk.start, received);
+                                let _ = sender.send(());
+                                return Ok(());
+                            }
+                            future::Either::Right((
+                                Some(WorkerEvent::Shrink { chunk_start, end }),
+                                _,
+                            )) => {
+                                if chunk.shrink(chunk_start, end, received) {
+                                    self.progress_updater.signal_shrunk(id, chunk.start, chunk.end);
+                                }
+                            }
+                            future::Either::Right((None, _)) => break,
+                        }
                     }
-                    future::Either::Right((None, _)) => break,
                 }
             }
         }
 
         Ok(())
     }
+
+    // an idle worker can be removed while it waits for a chunk
+    async fn next_chunk(&mut self) -> Option<Chunk> {
+        loop {
+            let new_chunk = self.chunk_receiver.recv();
+            let new_event = self.event_receiver.recv();
+            futures::pin_mut!(new_chunk);
+            futures::pin_mut!(new_event);
+            match future::select(new_chunk, new_event).await {
+                future::Either::Left((chunk, _)) => return chunk.ok(),
+                future::Either::Right((Some(WorkerEvent::Remove(sender)), _)) => {
+                    let _ = sender.send(());
+                    return None;
+                }
+                // the chunk to shrink has been completed by this worker
+                future::Either::Right((Some(WorkerEvent::Shrink { .. }), _)) => {}
+                future::Either::Right((None, _)) => return None,
+            }
+        }
+    }
+
+    fn switch_source(&mut self, chunk: &Chunk, err: &Error) {
+        self.source_index = (self.source_index + 1) % self.sources.len();
+        tracing::warn!(
+            "Failed to transfer chunk {}-{}, fall back to source {}, error: {err}",
+            chunk.start,
+            chunk.end,
+            self.source_index
+        );
+    }
+
+    async fn transfer_chunk(
+        &mut self,
+        chunk: &mut Chunk,
+        received: &mut u64,
+    ) -> Result<Transfer, Error> {
+        if *received >= chunk.len() {
+            return Ok(Transfer::Completed);
+        }
+        let mut stream = match self.sources[self.source_index]
+            .fetch_bytes(chunk.start + *received, chunk.end)
+            .await
+        {
+            Ok(stream) => stream,
+            Err(err) => return Ok(Transfer::Interrupted(err)),
+        };
+
+        let rate_limiter = self.rate_limiter.clone();
+        loop {
+            let new_bytes = async {
+                rate_limiter.ready().await;
+                stream.bytes().await
+            };
+            let new_event = self.event_receiver.recv();
+            futures::pin_mut!(new_bytes);
+            futures::pin_mut!(new_event);
+
+            match future::select(new_bytes, new_event).await {
+                future::Either::Left((Ok(Some(bytes)), _)) => {
+                    // the chunk may be shrunk while the bytes of the origin range are streamed
+                    let remaining = usize::try_from(chunk.len() - *received).unwrap_or(usize::MAX);
+                    let bytes = &bytes[..bytes.len().min(remaining)];
+                    {
+                        let mut sink = self.sink.lock().await;
+                        let _ = sink
+                            .seek(SeekFrom::Start(chunk.start + *received))
+                            .await
+                            .with_context(|_| error::SeekFileSnafu {
+                                file_path: self.file_path.clone(),
+                            })?;
+                        sink.write_all(bytes).await.with_context(|_| error::WriteFileSnafu {
+                            file_path: self.file_path.clone(),
+                        })?;
+                        drop(sink);
+                    }
+                    rate_limiter.consume(bytes.len() as u64);
+                    *received += bytes.len() as u64;
+                    self.progress_updater.update(self.id, chunk.start, chunk.end, *received);
+                    if *received >= chunk.len() {
+                        return Ok(Transfer::Completed);
+                    }
+                }
+                future::Either::Left((Ok(None), _)) => {
+                    // the connection may be closed before the whole range is sent
+                    return Ok(Transfer::Interrupted(Error::UnexpectedEndOfStream {
+                        expected: chunk.len(),
+                        received: *received,
+                    }));
+                }
+                future::Either::Left((Err(err), _)) => return Ok(Transfer::Interrupted(err)),
+                future::Either::Right((Some(WorkerEvent::Remove(sender)), _)) => {
+                    self.progress_updater.signal_removed(self.id, chunk.start, *received);
+                    let _ = sender.send(());
+                    return Ok(Transfer::Removed);
+                }
+                future::Either::Right((Some(WorkerEvent::Shrink { chunk_start, end }), _)) => {
+                    if chunk.shrink(chunk_start, end, *received) {
+                        self.progress_updater.signal_shrunk(self.id, chunk.start, chunk.end);
+                    }
+                }
+                future::Either::Right((None, _)) => return Ok(Transfer::Stopped),
+            }
+        }
+    }
+}
+
+enum Transfer {
+    Completed,
+    Stopped,
+    Removed,
+    Interrupted(Error),
 }
 
 pub enum WorkerEvent {
+    // the worker reports the progress of the chunk it holds before it replies
     Remove(oneshot::Sender<()>),
-    Stop(oneshot::Sender<()>),
+    // the tail of the chunk is handed over to another worker, the worker
+    // replies with the end it actually stops at
+    Shrink { chunk_start: u64, end: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use caracal_base::model;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::DownloaderFactory;
+
+    #[tokio::test]
+    async fn test_retry_range_closed_early() {
+        let content = (0..1024_u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        let _server = tokio::spawn({
+            let content = content.clone();
+            async move {
+                let mut has_cut_response = false;
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = vec![0; 4096];
+                    let len = stream.read(&mut request).await.unwrap();
+                    let request = String::from_utf8_lossy(&request[..len]).to_string();
+                    if request.starts_with("HEAD") {
+                        let response =
+                            "HTTP/1.1 200 OK\r\nContent-Length: 1024\r\nConnection: close\r\n\r\n";
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        continue;
+                    }
+                    let range = request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("range: bytes="))
+                        .unwrap();
+                    let (start, end) = range.split_once('-').unwrap();
+                    let (start, end) =
+                        (start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap());
+                    // the body of the first response is cut in the middle of the range
+                    let end = if has_cut_response { end } else { start + (end - start) / 2 };
+                    has_cut_response = true;
+                    let response = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes \
+                         {start}-{end}/1024\r\nConnection: close\r\n\r\n"
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                    stream.write_all(&content[start..=end]).await.unwrap();
+                }
+            }
+        });
+
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-closed-early-{}", std::process::id()));
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.clone()),
+            concurrent_number: Some(1),
+            ..model::CreateTask::new(uri.parse().unwrap())
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        drop(downloader.join().await.unwrap());
+        assert_eq!(tokio::fs::read(directory.join("file.bin")).await.unwrap(), content);
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
 }
@@ -1,5 +1,6 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
+use caracal_base::model::ChecksumAlgorithm;
 use reqwest::StatusCode;
 use snafu::Snafu;
 
@@ -21,15 +22,39 @@ pub enum Error {
     #[snafu(display("Destination file `{}` already exists", file_path.display()))]
     DestinationFileExists { file_path: PathBuf },
 
+    #[snafu(display("Content length of {uri} is {actual} bytes, expected {expected} bytes"))]
+    ContentLengthMismatch { uri: http::Uri, expected: u64, actual: u64 },
+
+    #[snafu(display("Content of {uri} has been changed since the download started"))]
+    RemoteContentChanged { uri: http::Uri },
+
+    #[snafu(display("Server of {uri} responded the whole content to a range request"))]
+    RangeNotHonoured { uri: http::Uri },
+
+    #[snafu(display("Stream ended after {received} of {expected} bytes of the chunk"))]
+    UnexpectedEndOfStream { expected: u64, received: u64 },
+
     #[snafu(display("Fetching directory is not supported"))]
     FetchingDirectory,
 
+    #[snafu(display("Error occurs while listing directory `{path}`, error: {source}"))]
+    ListDirectory { path: String, source: opendal::Error },
+
+    #[snafu(display("No file in directory {uri} matches the filter"))]
+    NoFileInDirectory { uri: http::Uri },
+
+    #[snafu(display("Error occurs while creating URI of `{path}` in directory"))]
+    InvalidChildUri { path: String },
+
     #[snafu(display("The chunk size is invalid, value: {value}"))]
     BadChunkSize { value: u64 },
 
     #[snafu(display("URI {uri} is not a valid MinIO URL"))]
     InvalidMinioUrl { uri: http::Uri },
 
+    #[snafu(display("Invalid S3 URL `{uri}`, expected `s3://[profile@]bucket/key`"))]
+    InvalidS3Url { uri: http::Uri },
+
     #[snafu(display("Hostname is not a provided"))]
     HostnameNotProvided,
 
@@ -42,6 +67,18 @@ pub enum Error {
     #[snafu(display("Unknown HTTP error, status code: {status_code}"))]
     UnknownHttpError { status_code: StatusCode },
 
+    #[snafu(display("Access to {uri} is denied, status code: {status_code}"))]
+    AccessDenied { uri: http::Uri, status_code: StatusCode },
+
+    #[snafu(display("HTTP header `{name}` is not valid"))]
+    InvalidHttpHeader { name: String },
+
+    #[snafu(display("Proxy `{uri}` is not valid, error: {source}"))]
+    InvalidProxy { uri: String, source: reqwest::Error },
+
+    #[snafu(display("Proxy `{uri}` is not a valid URL"))]
+    InvalidProxyUrl { uri: String },
+
     #[snafu(display("Could not parse length from HTTP header, value: {value}, error: {source}"))]
     ParseLengthFromHttpHeader { value: String, source: std::num::ParseIntError },
 
@@ -54,12 +91,30 @@ pub enum Error {
     #[snafu(display("Error occurs while creating file `{}`, error: {source}", file_path.display()))]
     CreateFile { file_path: PathBuf, source: std::io::Error },
 
+    #[snafu(display("Error occurs while creating directory `{}`, error: {source}", directory_path.display()))]
+    CreateDirectory { directory_path: PathBuf, source: std::io::Error },
+
     #[snafu(display("Error occurs while creating control file `{}`, error: {source}", file_path.display()))]
     CreateControlFile { file_path: PathBuf, source: std::io::Error },
 
+    #[snafu(display("Error occurs while replacing control file `{}`, error: {source}", file_path.display()))]
+    ReplaceControlFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display(
+        "Control file `{}` is corrupted, {reason}, remove it to restart the download",
+        file_path.display()
+    ))]
+    CorruptedControlFile { file_path: PathBuf, reason: String },
+
+    #[snafu(display("Schema {schema} of control file `{}` is not supported", file_path.display()))]
+    UnsupportedControlFileSchema { file_path: PathBuf, schema: u32 },
+
     #[snafu(display("Error occurs while writing file `{}`, error: {source}", file_path.display()))]
     WriteFile { file_path: PathBuf, source: std::io::Error },
 
+    #[snafu(display("Error occurs while reading file `{}`, error: {source}", file_path.display()))]
+    ReadFile { file_path: PathBuf, source: std::io::Error },
+
     #[snafu(display("Error occurs while flushing file `{}`, error: {source}", file_path.display()))]
     FlushFile { file_path: PathBuf, source: std::io::Error },
 
@@ -102,15 +157,138 @@ pub enum Error {
     #[snafu(display("Error occurs while getting metadata from MinIO, error: {source}"))]
     GetMetadataFromMinio { source: opendal::Error },
 
+    #[snafu(display(
+        "Error occurs while getting metadata of `{key}` from S3 bucket `{bucket}`, error: {source}"
+    ))]
+    GetMetadataFromS3 { bucket: String, key: String, source: opendal::Error },
+
     #[snafu(display("Error occurs while getting metadata from file system, error: {source}"))]
     GetMetadataFromFileSystem { source: opendal::Error },
 
     #[snafu(display("MinIO alias `{alias}` not found"))]
     MinioAliasNotFound { alias: String },
 
+    #[snafu(display("S3 profile `{profile}` not found"))]
+    S3ProfileNotFound { profile: String },
+
     #[snafu(display("SSH configuration `{endpoint}` not found"))]
     SshConfigNotFound { endpoint: String },
 
     #[snafu(display("Error occurs while join tokio task, error: {source}"))]
     JoinTask { source: tokio::task::JoinError },
+
+    #[snafu(display("Error occurs while computing checksum of file `{}`, error: {source}", file_path.display()))]
+    ComputeChecksum { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display(
+        "The {algorithm} checksum of file `{}` does not match, expected: {expected}, actual: {actual}",
+        file_path.display()
+    ))]
+    ChecksumMismatch {
+        file_path: PathBuf,
+        algorithm: ChecksumAlgorithm,
+        expected: String,
+        actual: String,
+    },
+
+    #[snafu(display("Error occurs while connecting to FTP server `{host}`, error: {source}"))]
+    ConnectFtpServer { host: String, source: std::io::Error },
+
+    #[snafu(display(
+        "Error occurs while establishing TLS with FTP server `{host}`, error: {source}"
+    ))]
+    EstablishFtpTls { host: String, source: std::io::Error },
+
+    #[snafu(display("Error occurs while communicating with FTP server `{host}`, error: {source}"))]
+    FtpConnection { host: String, source: std::io::Error },
+
+    #[snafu(display("FTP command `{command}` failed, reply: {code} {message}"))]
+    FtpCommand { command: String, code: u16, message: String },
+
+    #[snafu(display("Reply of FTP server is not valid, {reason}"))]
+    InvalidFtpReply { reason: String },
+
+    #[snafu(display("Error occurs while reading torrent file `{}`, error: {source}", file_path.display()))]
+    ReadTorrentFile { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not decode torrent metadata, error: {source}"))]
+    DecodeTorrent { source: serde_bencode::Error },
+
+    #[snafu(display("Torrent metadata is not valid, {reason}"))]
+    InvalidTorrent { reason: String },
+
+    #[snafu(display("Torrent `{name}` contains multiple files, which is not supported"))]
+    UnsupportedMultiFileTorrent { name: String },
+
+    #[snafu(display("URI {uri} is not a valid magnet link"))]
+    InvalidMagnetUri { uri: http::Uri },
+
+    #[snafu(display("No HTTP tracker is available for the torrent"))]
+    NoTorrentTracker,
+
+    #[snafu(display("Error occurs while announcing to tracker `{tracker}`, error: {source}"))]
+    AnnounceToTracker { tracker: String, source: reqwest::Error },
+
+    #[snafu(display("Tracker `{tracker}` rejected the announce, reason: {reason}"))]
+    TrackerFailure { tracker: String, reason: String },
+
+    #[snafu(display("No peer is available for the torrent"))]
+    NoTorrentPeer,
+
+    #[snafu(display("Error occurs while communicating with peer {addr}, error: {source}"))]
+    PeerConnection { addr: SocketAddr, source: std::io::Error },
+
+    #[snafu(display("Peer {addr} violated the protocol, {reason}"))]
+    PeerProtocol { addr: SocketAddr, reason: String },
+
+    #[snafu(display("Piece {index} received from peer {addr} does not match its hash"))]
+    PieceHashMismatch { addr: SocketAddr, index: u32 },
+
+    #[snafu(display("Error occurs while listening for peers, error: {source}"))]
+    ListenForPeers { source: std::io::Error },
+}
+
+impl Error {
+    /// Whether the operation may succeed if it is tried again later
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::UnknownHttpError { status_code } => {
+                status_code.is_server_error() || *status_code == StatusCode::TOO_MANY_REQUESTS
+            }
+            // transient negative completion replies
+            Self::FtpCommand { code, .. } => (400..500).contains(code),
+            Self::ConnectionTimedOut
+            | Self::UnexpectedEndOfStream { .. }
+            | Self::CreateReader { .. }
+            | Self::ReadFromReader { .. }
+            | Self::SeekReader { .. }
+            | Self::FetchRangeFromHttp { .. }
+            | Self::FetchBytesFromHttp { .. }
+            | Self::FetchBytesFromMinio { .. }
+            | Self::FetchHttpHeader { .. }
+            | Self::GetMetadataFromSftp { .. }
+            | Self::GetMetadataFromMinio { .. }
+            | Self::GetMetadataFromS3 { .. }
+            | Self::ConnectFtpServer { .. }
+            | Self::FtpConnection { .. }
+            | Self::AnnounceToTracker { .. }
+            | Self::NoTorrentPeer
+            | Self::PeerConnection { .. }
+            | Self::PeerProtocol { .. }
+            | Self::PieceHashMismatch { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the server asks the client to slow down
+    #[must_use]
+    pub fn is_throttled(&self) -> bool {
+        matches!(
+            self,
+            Self::UnknownHttpError { status_code }
+                if *status_code == StatusCode::TOO_MANY_REQUESTS
+                    || *status_code == StatusCode::SERVICE_UNAVAILABLE
+        )
+    }
 }
@@ -7,7 +7,10 @@ use crate::{
     error,
     error::{Error, Result},
     ext::PathExt,
-    fetcher::{Metadata, generic::ByteStream},
+    fetcher::{
+        Metadata,
+        generic::{self, ByteStream},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -24,13 +27,8 @@ impl Fetcher {
     where
         P: AsRef<Path> + Send + Sync,
     {
-        let mut builder = services::Fs::default();
-        let _ = builder.root("/");
         let file_path = file_path.as_ref().to_path_buf();
-
-        let operator =
-            Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish();
-
+        let operator = build_operator()?;
         let metadata = operator
             .stat(&file_path.to_string_lossy())
             .await
@@ -44,7 +42,12 @@ impl Fetcher {
     }
 
     pub fn fetch_metadata(&self) -> Metadata {
-        Metadata { length: self.length, filename: self.file_path.file_name_or_fallback() }
+        Metadata {
+            length: self.length,
+            filename: self.file_path.file_name_or_fallback(),
+            etag: None,
+            last_modified: None,
+        }
     }
 
     pub async fn fetch_all(&self) -> Result<ByteStream> {
@@ -60,3 +63,122 @@ impl Fetcher {
             .context(error::CreateReaderSnafu)
     }
 }
+
+/// Relative paths of the files under `directory`, `None` if it is not a
+/// directory
+pub async fn list_directory<P>(directory: P) -> Result<Option<Vec<String>>>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    let directory = directory.as_ref().to_string_lossy();
+    let operator = build_operator()?;
+    match operator.stat(&directory).await {
+        Ok(metadata) if metadata.is_dir() => {
+            generic::list_files(&operator, &directory).await.map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn build_operator() -> Result<Operator> {
+    let mut builder = services::Fs::default();
+    let _ = builder.root("/");
+    Ok(Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use caracal_base::model;
+
+    use crate::{DownloaderFactory, Error};
+
+    #[tokio::test]
+    async fn test_download_directory() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-directory-{}", std::process::id()));
+        let source_directory = directory.join("source");
+        for (path, contents) in [
+            ("debian.iso", "debian"),
+            ("amd64/netinst image.iso", "netinst"),
+            ("amd64/tmp/partial.iso", "partial"),
+            ("SHA256SUMS", "checksums"),
+        ] {
+            let file_path = source_directory.join(path);
+            tokio::fs::create_dir_all(file_path.parent().unwrap()).await.unwrap();
+            tokio::fs::write(file_path, contents).await.unwrap();
+        }
+
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.join("output")),
+            ..model::CreateTask::new(format!("{}/", source_directory.display()).parse().unwrap())
+        };
+        let filter = model::PathFilter {
+            include: vec!["*.iso".to_string()],
+            exclude: vec!["**/tmp/**".to_string()],
+        };
+        let new_tasks = factory.expand_directory(&new_task, &filter).await.unwrap().unwrap();
+        assert_eq!(new_tasks.len(), 2);
+        for new_task in &new_tasks {
+            let mut downloader = factory.create_new_task(new_task).await.unwrap();
+            downloader.start().await.unwrap();
+            drop(downloader.join().await.unwrap());
+        }
+        let output_directory = directory.join("output").join("source");
+        assert_eq!(
+            tokio::fs::read_to_string(output_directory.join("debian.iso")).await.unwrap(),
+            "debian"
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(output_directory.join("amd64/netinst image.iso"))
+                .await
+                .unwrap(),
+            "netinst"
+        );
+        assert!(!output_directory.join("amd64/tmp").exists());
+        assert!(!output_directory.join("SHA256SUMS").exists());
+
+        let filter = model::PathFilter { include: vec!["*.img".to_string()], exclude: Vec::new() };
+        assert!(matches!(
+            factory.expand_directory(&new_task, &filter).await,
+            Err(Error::NoFileInDirectory { .. })
+        ));
+        let file_task = model::CreateTask {
+            uri: format!("{}/debian.iso", source_directory.display()).parse().unwrap(),
+            ..new_task
+        };
+        assert!(factory.expand_directory(&file_task, &filter).await.unwrap().is_none());
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+
+    #[tokio::test]
+    async fn test_fall_back_to_mirror() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-mirror-{}", std::process::id()));
+        let mirror_path = directory.join("mirror").join("debian.iso");
+        tokio::fs::create_dir_all(mirror_path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&mirror_path, "debian").await.unwrap();
+
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let new_task = model::CreateTask {
+            mirrors: vec![mirror_path.display().to_string().parse().unwrap()],
+            output_directory: Some(directory.join("output")),
+            ..model::CreateTask::new(
+                format!("{}/origin/debian.iso", directory.display()).parse().unwrap(),
+            )
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        drop(downloader.join().await.unwrap());
+        assert_eq!(
+            tokio::fs::read_to_string(directory.join("output/debian.iso")).await.unwrap(),
+            "debian"
+        );
+
+        let new_task = model::CreateTask { mirrors: Vec::new(), ..new_task };
+        assert!(factory.create_new_task(&new_task).await.is_err());
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+}
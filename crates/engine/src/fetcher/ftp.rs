@@ -0,0 +1,555 @@
+use std::{
+    borrow::Cow,
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, LazyLock},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::BytesMut;
+use caracal_base::model;
+use snafu::{OptionExt, ResultExt};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{TlsConnector, client::TlsStream, rustls};
+
+use crate::{
+    error,
+    error::{Error, Result},
+    ext::UriExt,
+    fetcher::Metadata,
+};
+
+const DEFAULT_PORT: u16 = 21;
+
+const DEFAULT_IMPLICIT_TLS_PORT: u16 = 990;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+const MAX_BUFFER_SIZE: usize = 1 << 16;
+
+static TLS_CONFIG: LazyLock<Arc<rustls::ClientConfig>> = LazyLock::new(|| {
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    Arc::new(
+        rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_store)
+            .with_no_client_auth(),
+    )
+});
+
+/// `ftp://` is plain FTP, `ftpes://` upgrades the connection with `AUTH TLS` and `ftps://`
+/// starts with TLS
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Security {
+    None,
+    ExplicitTls,
+    ImplicitTls,
+}
+
+#[derive(Clone, Debug)]
+pub struct Fetcher {
+    host: String,
+    port: u16,
+    security: Security,
+    username: String,
+    password: String,
+    path: String,
+    metadata: Metadata,
+    supports_range_request: bool,
+}
+
+impl Fetcher {
+    pub async fn new(uri: http::Uri, basic_auth: Option<model::BasicAuth>) -> Result<Self> {
+        let security = match uri.scheme_str() {
+            Some("ftps") => Security::ImplicitTls,
+            Some("ftpes") => Security::ExplicitTls,
+            _ => Security::None,
+        };
+        let host = uri
+            .host()
+            .context(error::HostnameNotProvidedSnafu)?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = uri.port_u16().unwrap_or(if security == Security::ImplicitTls {
+            DEFAULT_IMPLICIT_TLS_PORT
+        } else {
+            DEFAULT_PORT
+        });
+        // credential of the task takes precedence over the one in the URI
+        let (username, password) = match basic_auth {
+            Some(model::BasicAuth { username, password }) => {
+                (username, password.unwrap_or_default())
+            }
+            None => credential_from_uri(&uri),
+        };
+        // like curl, the path is relative to the login directory, `ftp://host//path` is an
+        // absolute path
+        let path = uri.path().strip_prefix('/').unwrap_or_else(|| uri.path());
+        let path = urlencoding::decode(path).map_or_else(|_| path.to_string(), Cow::into_owned);
+
+        let mut fetcher = Self {
+            host,
+            port,
+            security,
+            username,
+            password,
+            path,
+            metadata: Metadata {
+                length: 0,
+                filename: uri.guess_filename(),
+                etag: None,
+                last_modified: None,
+            },
+            supports_range_request: false,
+        };
+        let mut control = fetcher.login().await?;
+        fetcher.supports_range_request = control.supports_rest_stream().await?;
+        fetcher.metadata.length = match control.command(&format!("SIZE {}", fetcher.path)).await? {
+            (213, message) => message.trim().parse().ok().context(error::InvalidFtpReplySnafu {
+                reason: format!("size `{message}` is not a number"),
+            })?,
+            (550, _) => return Err(Error::NotFound { uri }),
+            (code, message) => {
+                return Err(Error::FtpCommand { command: "SIZE".to_string(), code, message });
+            }
+        };
+        control.quit().await;
+        Ok(fetcher)
+    }
+
+    #[inline]
+    pub const fn supports_range_request(&self) -> bool {
+        self.supports_range_request && self.metadata.length != 0
+    }
+
+    pub fn fetch_metadata(&self) -> Metadata { self.metadata.clone() }
+
+    pub async fn fetch_bytes(&self, start: u64, end: u64) -> Result<ByteStream> {
+        self.retrieve(start, Some(end - start + 1)).await
+    }
+
+    pub async fn fetch_all(&self) -> Result<ByteStream> { self.retrieve(0, None).await }
+
+    async fn retrieve(&self, offset: u64, len: Option<u64>) -> Result<ByteStream> {
+        let mut control = self.login().await?;
+        let data = control.open_data_connection().await?;
+        if offset > 0 {
+            let _ = control.expect(&format!("REST {offset}"), &[350]).await?;
+        }
+        let _ = control.expect(&format!("RETR {}", self.path), &[125, 150]).await?;
+        // the TLS handshake of the data connection starts after the transfer is
+        // accepted
+        let data = if control.is_protected {
+            Stream::connect_tls(data, &self.host).await?
+        } else {
+            Stream::Plain(data)
+        };
+        Ok(ByteStream {
+            host: self.host.clone(),
+            _control: control,
+            data,
+            remaining: len,
+            buffer: BytesMut::new(),
+        })
+    }
+
+    async fn login(&self) -> Result<ControlConnection> {
+        let tcp = with_timeout(TcpStream::connect((self.host.as_str(), self.port)))
+            .await
+            .with_context(|_| error::ConnectFtpServerSnafu { host: self.host.clone() })?;
+        let peer_ip = tcp
+            .peer_addr()
+            .with_context(|_| error::ConnectFtpServerSnafu { host: self.host.clone() })?
+            .ip();
+        let stream = if self.security == Security::ImplicitTls {
+            Stream::connect_tls(tcp, &self.host).await?
+        } else {
+            Stream::Plain(tcp)
+        };
+
+        let mut control = ControlConnection {
+            stream: BufReader::new(stream),
+            host: self.host.clone(),
+            peer_ip,
+            is_protected: false,
+        };
+        let (code, message) = control.reply().await?;
+        if code != 220 {
+            return Err(Error::FtpCommand { command: "CONNECT".to_string(), code, message });
+        }
+        if self.security == Security::ExplicitTls {
+            let _ = control.expect("AUTH TLS", &[234]).await?;
+            control = control.upgrade().await?;
+        }
+
+        if control.expect(&format!("USER {}", self.username), &[230, 331]).await? == 331 {
+            let _ = control.expect(&format!("PASS {}", self.password), &[202, 230]).await?;
+        }
+        if self.security != Security::None {
+            let _ = control.expect("PBSZ 0", &[200]).await?;
+            let _ = control.expect("PROT P", &[200]).await?;
+            control.is_protected = true;
+        }
+        let _ = control.expect("TYPE I", &[200]).await?;
+        Ok(control)
+    }
+}
+
+struct ControlConnection {
+    stream: BufReader<Stream>,
+    host: String,
+    peer_ip: IpAddr,
+    is_protected: bool,
+}
+
+impl ControlConnection {
+    async fn upgrade(self) -> Result<Self> {
+        let Self { stream, host, peer_ip, is_protected } = self;
+        let Stream::Plain(tcp) = stream.into_inner() else {
+            return Err(Error::InvalidFtpReply { reason: "TLS is already enabled".to_string() });
+        };
+        let stream = BufReader::new(Stream::connect_tls(tcp, &host).await?);
+        Ok(Self { stream, host, peer_ip, is_protected })
+    }
+
+    async fn reply(&mut self) -> Result<(u16, String)> {
+        let mut line = self.read_line().await?;
+        let code = line
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .with_context(|| error::InvalidFtpReplySnafu { reason: format!("`{line}`") })?;
+        // a multi-line reply ends with a line starting with the code and a space
+        let mut message = line.get(4..).unwrap_or_default().to_string();
+        if line.as_bytes().get(3) == Some(&b'-') {
+            let last_line_prefix = format!("{code} ");
+            loop {
+                line = self.read_line().await?;
+                message.push('\n');
+                if let Some(last_line) = line.strip_prefix(&last_line_prefix) {
+                    message.push_str(last_line);
+                    break;
+                }
+                message.push_str(&line);
+            }
+        }
+        Ok((code, message))
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let len = with_timeout(self.stream.read_line(&mut line))
+            .await
+            .with_context(|_| error::FtpConnectionSnafu { host: self.host.clone() })?;
+        if len == 0 {
+            return Err(Error::FtpConnection {
+                host: self.host.clone(),
+                source: io::ErrorKind::UnexpectedEof.into(),
+            });
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    async fn command(&mut self, command: &str) -> Result<(u16, String)> {
+        let stream = self.stream.get_mut();
+        with_timeout(async {
+            stream.write_all(format!("{command}\r\n").as_bytes()).await?;
+            stream.flush().await
+        })
+        .await
+        .with_context(|_| error::FtpConnectionSnafu { host: self.host.clone() })?;
+        self.reply().await
+    }
+
+    async fn expect(&mut self, command: &str, expected: &[u16]) -> Result<u16> {
+        let (code, message) = self.command(command).await?;
+        if expected.contains(&code) {
+            Ok(code)
+        } else {
+            // the arguments are left out, they may contain the password
+            let command = command.split(' ').next().unwrap_or_default().to_string();
+            Err(Error::FtpCommand { command, code, message })
+        }
+    }
+
+    async fn supports_rest_stream(&mut self) -> Result<bool> {
+        let (code, message) = self.command("FEAT").await?;
+        Ok(code == 211
+            && message.lines().any(|line| line.trim().eq_ignore_ascii_case("REST STREAM")))
+    }
+
+    async fn open_data_connection(&mut self) -> Result<TcpStream> {
+        let port = if let (229, message) = self.command("EPSV").await? {
+            parse_epsv_port(&message)
+        } else {
+            match self.command("PASV").await? {
+                (227, message) => parse_pasv_port(&message),
+                (code, message) => {
+                    return Err(Error::FtpCommand { command: "PASV".to_string(), code, message });
+                }
+            }
+        }
+        .context(error::InvalidFtpReplySnafu { reason: "passive port is not provided" })?;
+
+        // the address in the reply of `PASV` is ignored, servers behind NAT often
+        // advertise their private address
+        with_timeout(TcpStream::connect(SocketAddr::new(self.peer_ip, port)))
+            .await
+            .with_context(|_| error::ConnectFtpServerSnafu { host: self.host.clone() })
+    }
+
+    async fn quit(mut self) {
+        if let Err(err) = self.command("QUIT").await {
+            tracing::debug!("{err}");
+        }
+    }
+}
+
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Stream {
+    async fn connect_tls(tcp: TcpStream, host: &str) -> Result<Self> {
+        let server_name = rustls::ServerName::try_from(host)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+            .with_context(|_| error::EstablishFtpTlsSnafu { host: host.to_string() })?;
+        let stream = with_timeout(TlsConnector::from(TLS_CONFIG.clone()).connect(server_name, tcp))
+            .await
+            .with_context(|_| error::EstablishFtpTlsSnafu { host: host.to_string() })?;
+        Ok(Self::Tls(Box::new(stream)))
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+pub struct ByteStream {
+    host: String,
+
+    // the transfer is aborted by the server once the control connection is closed
+    _control: ControlConnection,
+
+    data: Stream,
+
+    remaining: Option<u64>,
+
+    buffer: BytesMut,
+}
+
+impl ByteStream {
+    pub async fn bytes(&mut self) -> Result<Option<&[u8]>> {
+        if self.remaining == Some(0) {
+            return Ok(None);
+        }
+
+        self.buffer.clear();
+        self.buffer.reserve(MAX_BUFFER_SIZE);
+        let n = with_timeout(self.data.read_buf(&mut self.buffer))
+            .await
+            .with_context(|_| error::FtpConnectionSnafu { host: self.host.clone() })?;
+        if n == 0 {
+            return Ok(None);
+        }
+
+        // `RETR` sends the rest of the file, the bytes after the range are dropped
+        if let Some(remaining) = self.remaining.as_mut() {
+            let len = usize::try_from(*remaining).unwrap_or(usize::MAX).min(self.buffer.len());
+            self.buffer.truncate(len);
+            *remaining -= len as u64;
+        }
+        Ok(Some(self.buffer.as_ref()))
+    }
+}
+
+// anonymous login is used if the URI does not carry a user
+fn credential_from_uri(uri: &http::Uri) -> (String, String) {
+    let user_info = uri
+        .authority()
+        .and_then(|authority| authority.as_str().rsplit_once('@'))
+        .map(|(user_info, _)| user_info);
+    let decode = |s: &str| urlencoding::decode(s).map_or_else(|_| s.to_string(), Cow::into_owned);
+    match user_info.map(|user_info| user_info.split_once(':').unwrap_or((user_info, ""))) {
+        Some((username, password)) => (decode(username), decode(password)),
+        None => ("anonymous".to_string(), "anonymous@".to_string()),
+    }
+}
+
+// e.g. `229 Entering Extended Passive Mode (|||6446|)`
+fn parse_epsv_port(message: &str) -> Option<u16> {
+    let start = message.find("(|||")? + 4;
+    let end = start + message[start..].find('|')?;
+    message[start..end].parse().ok()
+}
+
+// e.g. `227 Entering Passive Mode (192,168,1,2,25,46)`
+fn parse_pasv_port(message: &str) -> Option<u16> {
+    let start = message.find('(')? + 1;
+    let end = start + message[start..].find(')')?;
+    let numbers = message[start..end]
+        .split(',')
+        .map(|n| n.trim().parse::<u8>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+    match numbers[..] {
+        [_, _, _, _, high, low] => Some(u16::from_be_bytes([high, low])),
+        _ => None,
+    }
+}
+
+async fn with_timeout<T, F>(future: F) -> io::Result<T>
+where
+    F: Future<Output = io::Result<T>> + Send,
+{
+    tokio::time::timeout(TIMEOUT, future)
+        .await
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use caracal_base::model;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::{parse_epsv_port, parse_pasv_port};
+    use crate::DownloaderFactory;
+
+    // a passive-mode FTP server serving `file.bin`
+    async fn serve_ftp_session(stream: TcpStream, data: Arc<Vec<u8>>) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut passive = None;
+        let mut offset = 0;
+        writer.write_all(b"220 ready\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let (command, argument) = line.split_once(' ').unwrap_or((&line, ""));
+            let reply = match command {
+                "USER" => "331 password required".to_string(),
+                "PASS" => "230 logged in".to_string(),
+                "TYPE" => "200 binary mode".to_string(),
+                "FEAT" => "211-features\r\n SIZE\r\n REST STREAM\r\n211 end".to_string(),
+                "SIZE" if argument == "file.bin" => format!("213 {}", data.len()),
+                "SIZE" => "550 not found".to_string(),
+                "EPSV" => {
+                    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                    let port = listener.local_addr().unwrap().port();
+                    passive = Some(listener);
+                    format!("229 entering extended passive mode (|||{port}|)")
+                }
+                "REST" => {
+                    offset = argument.parse().unwrap();
+                    format!("350 restarting at {offset}")
+                }
+                "RETR" => {
+                    writer.write_all(b"150 opening data connection\r\n").await.unwrap();
+                    let (mut data_stream, _) = passive.take().unwrap().accept().await.unwrap();
+                    // the client closes the data connection once its range is received
+                    drop(data_stream.write_all(&data[offset..]).await);
+                    offset = 0;
+                    "226 transfer complete".to_string()
+                }
+                "QUIT" => "221 bye".to_string(),
+                _ => "502 not implemented".to_string(),
+            };
+            if writer.write_all(format!("{reply}\r\n").as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_passive_port() {
+        assert_eq!(parse_epsv_port("Entering Extended Passive Mode (|||6446|)"), Some(6446));
+        assert_eq!(parse_pasv_port("Entering Passive Mode (192,168,1,2,25,46)"), Some(6446));
+        assert_eq!(parse_pasv_port("Entering Passive Mode"), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_from_ftp() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-ftp-{}", std::process::id()));
+        let data =
+            Arc::new((0..300_000_u32).map(|i| u8::try_from(i % 251).unwrap()).collect::<Vec<_>>());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let uri = format!("ftp://user:password@{}/file.bin", listener.local_addr().unwrap());
+        let server = {
+            let data = data.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    drop(tokio::spawn(serve_ftp_session(stream, data.clone())));
+                }
+            })
+        };
+
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.clone()),
+            concurrent_number: Some(3),
+            ..model::CreateTask::new(uri.parse().unwrap())
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        drop(downloader.join().await.unwrap());
+        assert_eq!(tokio::fs::read(directory.join("file.bin")).await.unwrap(), *data);
+
+        server.abort();
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+}
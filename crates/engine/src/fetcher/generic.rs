@@ -1,4 +1,5 @@
 use bytes::BytesMut;
+use opendal::Operator;
 use snafu::ResultExt;
 use tokio::io::AsyncReadExt;
 
@@ -6,6 +7,41 @@ use crate::{error, error::Result};
 
 const MAX_BUFFER_SIZE: usize = 1 << 16;
 
+/// Paths of the files under `directory`, relative to `directory`
+pub async fn list_files(operator: &Operator, directory: &str) -> Result<Vec<String>> {
+    let directory = format!("{}/", directory.trim_matches('/'));
+    let entries = operator
+        .list_with(&directory)
+        .recursive(true)
+        .await
+        .with_context(|_| error::ListDirectorySnafu { path: directory.clone() })?;
+
+    let prefix = directory.trim_start_matches('/');
+    let mut files = entries
+        .iter()
+        .filter(|entry| entry.metadata().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(prefix))
+        // skip the entries which would be written outside the output directory
+        .filter(|path| !path.is_empty() && !path.split('/').any(|segment| segment == ".."))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// Object storages have no directories, `key` is a prefix if it ends with `/`
+/// or other objects are under it
+pub async fn list_prefix(operator: &Operator, key: &str) -> Result<Option<Vec<String>>> {
+    if key.is_empty() || key.ends_with('/') {
+        return list_files(operator, key).await.map(Some);
+    }
+    // `key` is treated as an object if the bucket can not be listed
+    match list_files(operator, key).await {
+        Ok(files) if !files.is_empty() => Ok(Some(files)),
+        _ => Ok(None),
+    }
+}
+
 pub struct ByteStream {
     reader: opendal::Reader,
 
@@ -1,5 +1,11 @@
+use std::path::PathBuf;
+
 use bytes::Bytes;
-use reqwest::{StatusCode, header};
+use caracal_base::model;
+use reqwest::{
+    StatusCode,
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+};
 use snafu::ResultExt;
 
 use crate::{
@@ -13,27 +19,66 @@ use crate::{
 pub struct Fetcher {
     client: reqwest::Client,
     uri: http::Uri,
+    headers: HeaderMap,
+    basic_auth: Option<model::BasicAuth>,
     metadata: Metadata,
 }
 
 impl Fetcher {
+    pub async fn new(
+        client: reqwest::Client,
+        uri: http::Uri,
+        headers: &[model::HttpHeader],
+        basic_auth: Option<model::BasicAuth>,
+    ) -> Result<Self> {
+        let headers = headers
+            .iter()
+            .map(|model::HttpHeader { name, value }| {
+                HeaderName::from_bytes(name.as_bytes())
+                    .ok()
+                    .zip(HeaderValue::from_str(value).ok())
+                    .ok_or_else(|| Error::InvalidHttpHeader { name: name.clone() })
+            })
+            .collect::<Result<HeaderMap>>()?;
+
+        let mut fetcher = Self {
+            client,
+            uri,
+            headers,
+            basic_auth,
+            metadata: Metadata {
+                length: 0,
+                filename: PathBuf::new(),
+                etag: None,
+                last_modified: None,
+            },
+        };
+        fetcher.metadata = fetcher.fetch_remote_metadata().await?;
+        Ok(fetcher)
+    }
+
     #[allow(clippy::cognitive_complexity)]
-    pub async fn new(client: reqwest::Client, uri: http::Uri) -> Result<Self> {
-        let resp =
-            client.head(uri.to_string()).send().await.context(error::FetchHttpHeaderSnafu)?;
+    async fn fetch_remote_metadata(&self) -> Result<Metadata> {
+        let uri = &self.uri;
+        let resp = self
+            .request(reqwest::Method::HEAD)
+            .send()
+            .await
+            .context(error::FetchHttpHeaderSnafu)?;
         tracing::debug!("Response code: {}", resp.status());
         tracing::debug!("Received HEAD response: {:?}", resp.headers());
 
-        let metadata = if resp.status().is_success() {
+        if resp.status().is_success() {
             let length = resp.headers().get(header::CONTENT_LENGTH).map_or(0, |len_str| {
                 len_str.to_str().map_or(0, |len_str| len_str.parse::<u64>().unwrap_or_default())
             });
 
             let filename = resp.filename().unwrap_or_else(|| uri.guess_filename());
-            Metadata { length, filename }
+            let (etag, last_modified) = validators(resp.headers());
+            Ok(Metadata { length, filename, etag, last_modified })
         } else {
-            let resp = client
-                .get(uri.to_string())
+            let resp = self
+                .request(reqwest::Method::GET)
                 .header(header::RANGE, "0-0")
                 .send()
                 .await
@@ -45,16 +90,30 @@ impl Fetcher {
 
                 let length = resp.content_length().unwrap_or(0);
                 let filename = resp.filename().unwrap_or_else(|| uri.guess_filename());
-                Metadata { length, filename }
+                let (etag, last_modified) = validators(resp.headers());
+                Ok(Metadata { length, filename, etag, last_modified })
             } else {
-                return match resp_status {
+                match resp_status {
                     StatusCode::NOT_FOUND => Err(Error::NotFound { uri: uri.clone() }),
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                        Err(Error::AccessDenied { uri: uri.clone(), status_code: resp_status })
+                    }
                     _ => Err(Error::UnknownHttpError { status_code: resp_status }),
-                };
+                }
             }
-        };
+        }
+    }
 
-        Ok(Self { client, uri, metadata })
+    // every request of a task carries the same headers and credential
+    fn request(&self, method: reqwest::Method) -> reqwest::RequestBuilder {
+        let request =
+            self.client.request(method, self.uri.to_string()).headers(self.headers.clone());
+        match self.basic_auth {
+            Some(model::BasicAuth { ref username, ref password }) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            None => request,
+        }
     }
 
     #[inline]
@@ -62,25 +121,58 @@ impl Fetcher {
 
     pub fn fetch_metadata(&self) -> Metadata { self.metadata.clone() }
 
+    /// The range is served only if the content is unchanged since the
+    /// metadata was fetched, the whole content is responded otherwise
+    fn if_range(&self) -> Option<&str> {
+        // weak entity tags can not be used in `If-Range`
+        self.metadata
+            .etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.metadata.last_modified.as_deref())
+    }
+
     pub async fn fetch_bytes(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let mut request = self
+            .request(reqwest::Method::GET)
+            .header(header::RANGE, format!("bytes={start}-{end}"));
+        let if_range = self.if_range();
+        if let Some(if_range) = if_range {
+            request = request.header(header::IF_RANGE, if_range);
+        }
+        let resp = request.send().await.context(error::FetchRangeFromHttpSnafu)?;
+        // only the requested range can be written at the start of the chunk
+        match resp.status() {
+            StatusCode::PARTIAL_CONTENT => Ok(ByteStream::from(resp)),
+            StatusCode::OK if if_range.is_some() => {
+                Err(Error::RemoteContentChanged { uri: self.uri.clone() })
+            }
+            StatusCode::OK => Err(Error::RangeNotHonoured { uri: self.uri.clone() }),
+            // the body of an error response must not be written to the file
+            status_code => Err(Error::UnknownHttpError { status_code }),
+        }
+    }
+
+    pub async fn fetch_all(&self) -> Result<ByteStream> {
         let resp = self
-            .client
-            .get(self.uri.to_string())
-            .header(header::RANGE, format!("bytes={start}-{end}"))
+            .request(reqwest::Method::GET)
             .send()
             .await
             .context(error::FetchRangeFromHttpSnafu)?;
+        if !resp.status().is_success() {
+            return Err(Error::UnknownHttpError { status_code: resp.status() });
+        }
         Ok(ByteStream::from(resp))
     }
+}
 
-    pub async fn fetch_all(&self) -> Result<ByteStream> {
-        self.client
-            .get(self.uri.to_string())
-            .send()
-            .await
-            .context(error::FetchRangeFromHttpSnafu)
-            .map(ByteStream::from)
-    }
+/// `ETag` and `Last-Modified` of the response
+fn validators(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let value = |name| headers.get(name).and_then(|value: &HeaderValue| value.to_str().ok());
+    (
+        value(header::ETAG).map(ToString::to_string),
+        value(header::LAST_MODIFIED).map(ToString::to_string),
+    )
 }
 
 #[derive(Debug)]
@@ -105,3 +197,56 @@ impl ByteStream {
 impl From<reqwest::Response> for ByteStream {
     fn from(response: reqwest::Response) -> Self { Self { response, buffer: Bytes::new() } }
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::Fetcher;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_reject_error_response_to_range_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let uri = |path| format!("http://{addr}/{path}").parse().unwrap();
+        let _server = tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let len = stream.read(&mut request).await.unwrap();
+                let request = &request[..len];
+                let response: &[u8] = if request.starts_with(b"HEAD") {
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 1024\r\nConnection: close\r\n\r\n"
+                } else if request.starts_with(b"GET /whole.bin") {
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nwhole"
+                } else {
+                    b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 5\r\nConnection: \
+                      close\r\n\r\nerror"
+                };
+                stream.write_all(response).await.unwrap();
+            }
+        });
+
+        let fetcher =
+            Fetcher::new(reqwest::Client::new(), uri("file.bin"), &[], None).await.unwrap();
+        assert!(fetcher.supports_range_request());
+        assert!(matches!(
+            fetcher.fetch_bytes(0, 511).await,
+            Err(Error::UnknownHttpError { status_code: StatusCode::INTERNAL_SERVER_ERROR })
+        ));
+        assert!(matches!(
+            fetcher.fetch_all().await,
+            Err(Error::UnknownHttpError { status_code: StatusCode::INTERNAL_SERVER_ERROR })
+        ));
+
+        // the whole content is not written as the requested range
+        let fetcher =
+            Fetcher::new(reqwest::Client::new(), uri("whole.bin"), &[], None).await.unwrap();
+        assert!(matches!(fetcher.fetch_bytes(0, 511).await, Err(Error::RangeNotHonoured { .. })));
+    }
+}
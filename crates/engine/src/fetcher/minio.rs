@@ -6,7 +6,10 @@ use snafu::ResultExt;
 use crate::{
     error,
     error::Result,
-    fetcher::{Metadata, generic::ByteStream},
+    fetcher::{
+        Metadata,
+        generic::{self, ByteStream},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -34,16 +37,7 @@ impl Fetcher {
         W: fmt::Display + Send + Sync,
     {
         let filename = filename.to_string();
-        let mut builder = services::S3::default();
-        let _ = builder
-            .region("auto")
-            .endpoint(endpoint_url.to_string().as_str())
-            .bucket(bucket.to_string().as_str())
-            .access_key_id(access_key.to_string().as_str())
-            .secret_access_key(secret_key.to_string().as_str());
-
-        let operator =
-            Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish();
+        let operator = build_operator(endpoint_url, access_key, secret_key, bucket)?;
 
         let metadata =
             operator.stat(&filename).await.with_context(|_| error::GetMetadataFromMinioSnafu)?;
@@ -62,6 +56,8 @@ impl Fetcher {
                     },
                     PathBuf::from,
                 ),
+                etag: metadata.etag().map(ToString::to_string),
+                last_modified: None,
             },
         })
     }
@@ -82,3 +78,44 @@ impl Fetcher {
             .context(error::CreateReaderSnafu)
     }
 }
+
+/// Relative paths of the objects under `prefix`, `None` if it is an object
+pub async fn list_prefix<S, T, U, V, W>(
+    endpoint_url: S,
+    access_key: T,
+    secret_key: U,
+    bucket: V,
+    prefix: W,
+) -> Result<Option<Vec<String>>>
+where
+    S: fmt::Display + Send + Sync,
+    T: fmt::Display + Send + Sync,
+    U: fmt::Display + Send + Sync,
+    V: fmt::Display + Send + Sync,
+    W: fmt::Display + Send + Sync,
+{
+    let operator = build_operator(endpoint_url, access_key, secret_key, bucket)?;
+    generic::list_prefix(&operator, &prefix.to_string()).await
+}
+
+fn build_operator<S, T, U, V>(
+    endpoint_url: S,
+    access_key: T,
+    secret_key: U,
+    bucket: V,
+) -> Result<Operator>
+where
+    S: fmt::Display,
+    T: fmt::Display,
+    U: fmt::Display,
+    V: fmt::Display,
+{
+    let mut builder = services::S3::default();
+    let _ = builder
+        .region("auto")
+        .endpoint(endpoint_url.to_string().as_str())
+        .bucket(bucket.to_string().as_str())
+        .access_key_id(access_key.to_string().as_str())
+        .secret_access_key(secret_key.to_string().as_str());
+    Ok(Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish())
+}
@@ -1,15 +1,23 @@
 mod fs;
+mod ftp;
 mod generic;
 mod http;
 mod minio;
+mod s3;
 mod sftp;
+mod torrent;
 
 use std::{
     fmt,
     path::{Path, PathBuf},
 };
 
+use caracal_base::{
+    model,
+    profile::s3::{S3Path, S3Profile},
+};
 use hyper_http::Uri;
+use tokio::task::JoinHandle;
 
 use crate::error::Result;
 
@@ -18,14 +26,75 @@ pub struct Metadata {
     pub length: u64,
 
     pub filename: PathBuf,
+
+    /// Validators of the remote content, they tell whether the content has
+    /// been changed since the download started
+    pub etag: Option<String>,
+
+    pub last_modified: Option<String>,
+}
+
+/// Relative paths of the files under a directory of the local file system,
+/// `None` if it is not a directory
+pub async fn list_file_directory<P>(directory: P) -> Result<Option<Vec<String>>>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    fs::list_directory(directory).await
+}
+
+pub async fn list_sftp_directory<S, T, U, V>(
+    endpoint: S,
+    user: T,
+    identity_file: U,
+    directory: V,
+) -> Result<Option<Vec<String>>>
+where
+    S: fmt::Display + Send + Sync,
+    T: fmt::Display + Send + Sync,
+    U: fmt::Display + Send + Sync,
+    V: fmt::Display + Send + Sync,
+{
+    sftp::list_directory(endpoint, user, identity_file, directory).await
+}
+
+pub async fn list_minio_prefix<S, T, U, V, W>(
+    endpoint_url: S,
+    access_key: T,
+    secret_key: U,
+    bucket: V,
+    prefix: W,
+) -> Result<Option<Vec<String>>>
+where
+    S: fmt::Display + Send + Sync,
+    T: fmt::Display + Send + Sync,
+    U: fmt::Display + Send + Sync,
+    V: fmt::Display + Send + Sync,
+    W: fmt::Display + Send + Sync,
+{
+    minio::list_prefix(endpoint_url, access_key, secret_key, bucket, prefix).await
+}
+
+pub async fn list_s3_prefix(profile: &S3Profile, path: &S3Path) -> Result<Option<Vec<String>>> {
+    s3::list_prefix(profile, path).await
+}
+
+pub async fn check_torrent_file<P>(file_path: P) -> Result<()>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    torrent::check_torrent_file(file_path).await
 }
 
 #[derive(Clone, Debug)]
 pub enum Fetcher {
     FileSystem(fs::Fetcher),
+    Ftp(ftp::Fetcher),
     Http(http::Fetcher),
     Minio(minio::Fetcher),
+    S3(s3::Fetcher),
     Sftp(sftp::Fetcher),
+    Torrent(torrent::Fetcher),
 }
 
 impl Fetcher {
@@ -36,8 +105,17 @@ impl Fetcher {
         Ok(Self::FileSystem(fs::Fetcher::new(file_path).await?))
     }
 
-    pub async fn new_http(client: reqwest::Client, uri: Uri) -> Result<Self> {
-        Ok(Self::Http(http::Fetcher::new(client, uri).await?))
+    pub async fn new_http(
+        client: reqwest::Client,
+        uri: Uri,
+        headers: &[model::HttpHeader],
+        basic_auth: Option<model::BasicAuth>,
+    ) -> Result<Self> {
+        Ok(Self::Http(http::Fetcher::new(client, uri, headers, basic_auth).await?))
+    }
+
+    pub async fn new_ftp(uri: Uri, basic_auth: Option<model::BasicAuth>) -> Result<Self> {
+        Ok(Self::Ftp(ftp::Fetcher::new(uri, basic_auth).await?))
     }
 
     pub async fn new_sftp<S, T, U, V>(
@@ -74,20 +152,85 @@ impl Fetcher {
         ))
     }
 
+    pub async fn new_s3(uri: Uri, profile: &S3Profile, path: S3Path) -> Result<Self> {
+        Ok(Self::S3(s3::Fetcher::new(uri, profile, path).await?))
+    }
+
+    pub async fn new_torrent_file<P>(client: reqwest::Client, file_path: P) -> Result<Self>
+    where
+        P: AsRef<Path> + Send + Sync,
+    {
+        Ok(Self::Torrent(torrent::Fetcher::from_torrent_file(client, file_path).await?))
+    }
+
+    pub async fn new_magnet(client: reqwest::Client, uri: &Uri) -> Result<Self> {
+        Ok(Self::Torrent(torrent::Fetcher::from_magnet(client, uri).await?))
+    }
+
+    /// Name of the protocol, it is recorded in the control file
+    #[inline]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::FileSystem(_) => "file",
+            Self::Ftp(_) => "ftp",
+            Self::Http(_) => "http",
+            Self::Minio(_) => "minio",
+            Self::S3(_) => "s3",
+            Self::Sftp(_) => "sftp",
+            Self::Torrent(_) => "torrent",
+        }
+    }
+
     #[inline]
     pub const fn supports_range_request(&self) -> bool {
         match self {
+            Self::Ftp(client) => client.supports_range_request(),
             Self::Http(client) => client.supports_range_request(),
-            Self::FileSystem(_) | Self::Minio(_) | Self::Sftp(_) => true,
+            Self::FileSystem(_)
+            | Self::Minio(_)
+            | Self::S3(_)
+            | Self::Sftp(_)
+            | Self::Torrent(_) => true,
         }
     }
 
     pub fn fetch_metadata(&self) -> Metadata {
         match self {
             Self::FileSystem(client) => client.fetch_metadata(),
+            Self::Ftp(client) => client.fetch_metadata(),
             Self::Http(client) => client.fetch_metadata(),
             Self::Minio(client) => client.fetch_metadata(),
+            Self::S3(client) => client.fetch_metadata(),
             Self::Sftp(client) => client.fetch_metadata(),
+            Self::Torrent(client) => client.fetch_metadata(),
+        }
+    }
+
+    /// Chunks of torrents are aligned to pieces, which are the unit of
+    /// verification
+    pub fn piece_length(&self) -> Option<u64> {
+        match self {
+            Self::Torrent(client) => Some(client.piece_length()),
+            Self::FileSystem(_)
+            | Self::Ftp(_)
+            | Self::Http(_)
+            | Self::Minio(_)
+            | Self::S3(_)
+            | Self::Sftp(_) => None,
+        }
+    }
+
+    /// Start uploading the downloaded file to other peers, only torrents are
+    /// seeded
+    pub fn seed(&self, file_path: PathBuf, ratio: f64) -> Option<JoinHandle<()>> {
+        match self {
+            Self::Torrent(client) => Some(client.seed(file_path, ratio)),
+            Self::FileSystem(_)
+            | Self::Ftp(_)
+            | Self::Http(_)
+            | Self::Minio(_)
+            | Self::S3(_)
+            | Self::Sftp(_) => None,
         }
     }
 
@@ -97,32 +240,42 @@ impl Fetcher {
             Self::FileSystem(client) => {
                 client.fetch_bytes(start, end).await.map(ByteStream::Generic)
             }
+            Self::Ftp(client) => client.fetch_bytes(start, end).await.map(ByteStream::Ftp),
             Self::Http(client) => client.fetch_bytes(start, end).await.map(ByteStream::Http),
             Self::Minio(client) => client.fetch_bytes(start, end).await.map(ByteStream::Generic),
+            Self::S3(client) => client.fetch_bytes(start, end).await.map(ByteStream::Generic),
             Self::Sftp(client) => client.fetch_bytes(start, end).await.map(ByteStream::Generic),
+            Self::Torrent(client) => client.fetch_bytes(start, end).await.map(ByteStream::Torrent),
         }
     }
 
     pub async fn fetch_all(&mut self) -> Result<ByteStream> {
         match self {
             Self::FileSystem(client) => client.fetch_all().await.map(ByteStream::Generic),
+            Self::Ftp(client) => client.fetch_all().await.map(ByteStream::Ftp),
             Self::Http(client) => client.fetch_all().await.map(ByteStream::Http),
             Self::Minio(client) => client.fetch_all().await.map(ByteStream::Generic),
+            Self::S3(client) => client.fetch_all().await.map(ByteStream::Generic),
             Self::Sftp(client) => client.fetch_all().await.map(ByteStream::Generic),
+            Self::Torrent(client) => client.fetch_all().await.map(ByteStream::Torrent),
         }
     }
 }
 
 pub enum ByteStream {
+    Ftp(ftp::ByteStream),
     Http(http::ByteStream),
     Generic(generic::ByteStream),
+    Torrent(torrent::ByteStream),
 }
 
 impl ByteStream {
     pub async fn bytes(&mut self) -> Result<Option<&[u8]>> {
         match self {
+            Self::Ftp(stream) => stream.bytes().await,
             Self::Http(stream) => stream.bytes().await,
             Self::Generic(stream) => stream.bytes().await,
+            Self::Torrent(stream) => stream.bytes().await,
         }
     }
 }
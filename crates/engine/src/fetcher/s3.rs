@@ -0,0 +1,230 @@
+use caracal_base::profile::s3::{S3Path, S3Profile};
+use opendal::{Operator, services};
+use snafu::ResultExt;
+
+use crate::{
+    error,
+    error::{Error, Result},
+    ext::PathExt,
+    fetcher::{
+        Metadata,
+        generic::{self, ByteStream},
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct Fetcher {
+    operator: Operator,
+
+    key: String,
+
+    metadata: Metadata,
+}
+
+impl Fetcher {
+    /// Credentials which are not provided by `profile` are loaded from the
+    /// environment variables and the shared credentials file of AWS
+    pub async fn new(
+        uri: http::Uri,
+        profile: &S3Profile,
+        S3Path { bucket, key, .. }: S3Path,
+    ) -> Result<Self> {
+        let operator = build_operator(profile, &bucket)?;
+        let metadata = match operator.stat(&key).await {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == opendal::ErrorKind::NotFound => {
+                return Err(Error::NotFound { uri });
+            }
+            Err(source) => return Err(Error::GetMetadataFromS3 { bucket, key, source }),
+        };
+
+        let filename = std::path::Path::new(&key).file_name_or_fallback();
+        Ok(Self {
+            operator,
+            key,
+            metadata: Metadata {
+                length: metadata.content_length(),
+                filename,
+                etag: metadata.etag().map(ToString::to_string),
+                last_modified: None,
+            },
+        })
+    }
+
+    pub fn fetch_metadata(&self) -> Metadata { self.metadata.clone() }
+
+    pub async fn fetch_all(&self) -> Result<ByteStream> {
+        self.operator
+            .reader(self.key.as_str())
+            .await
+            .map(ByteStream::from)
+            .context(error::CreateReaderSnafu)
+    }
+
+    pub async fn fetch_bytes(&self, start: u64, end: u64) -> Result<ByteStream> {
+        self.operator
+            .reader_with(self.key.as_str())
+            .range(start..=end)
+            .await
+            .map(ByteStream::from)
+            .context(error::CreateReaderSnafu)
+    }
+}
+
+/// Relative paths of the objects under `key`, `None` if it is an object
+pub async fn list_prefix(
+    profile: &S3Profile,
+    S3Path { bucket, key, .. }: &S3Path,
+) -> Result<Option<Vec<String>>> {
+    let operator = build_operator(profile, bucket)?;
+    generic::list_prefix(&operator, key).await
+}
+
+fn build_operator(profile: &S3Profile, bucket: &str) -> Result<Operator> {
+    let S3Profile {
+        region,
+        endpoint_url,
+        force_path_style,
+        access_key,
+        secret_key,
+        session_token,
+        anonymous,
+    } = profile;
+
+    let mut builder = services::S3::default();
+    let _ = builder.bucket(bucket);
+    // requests are not signed silently if the credentials are missing
+    if *anonymous {
+        let _ = builder.allow_anonymous();
+    }
+    if let Some(region) = region {
+        let _ = builder.region(region);
+    }
+    if let Some(endpoint_url) = endpoint_url {
+        let _ = builder.endpoint(endpoint_url.to_string().trim_end_matches('/'));
+    }
+    if !force_path_style {
+        let _ = builder.enable_virtual_host_style();
+    }
+    if let Some(access_key) = access_key {
+        let _ = builder.access_key_id(access_key);
+    }
+    if let Some(secret_key) = secret_key {
+        let _ = builder.secret_access_key(secret_key);
+    }
+    if let Some(session_token) = session_token {
+        let _ = builder.security_token(session_token);
+    }
+
+    Ok(Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use caracal_base::{model, profile::s3::S3Profile};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::DownloaderFactory;
+
+    // a path-style S3 endpoint serving `bucket/file.bin` to signed requests
+    async fn serve_s3_request(stream: TcpStream, data: Arc<Vec<u8>>) {
+        let mut stream = BufReader::new(stream);
+        let mut request_line = String::new();
+        let _ = stream.read_line(&mut request_line).await.unwrap();
+        let mut range = None;
+        let mut signed = false;
+        loop {
+            let mut line = String::new();
+            let _ = stream.read_line(&mut line).await.unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(": ").unwrap();
+            match name.to_ascii_lowercase().as_str() {
+                "authorization" => signed = value.starts_with("AWS4-HMAC-SHA256 "),
+                "range" => {
+                    let (start, end) =
+                        value.strip_prefix("bytes=").unwrap().split_once('-').unwrap();
+                    range = Some((start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap()));
+                }
+                _ => {}
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+        let (status, body) = if !signed {
+            ("403 Forbidden", &[][..])
+        } else if !path.starts_with("/bucket/file.bin") {
+            ("404 Not Found", &[][..])
+        } else if let Some((start, end)) = range {
+            ("206 Partial Content", &data[start..=end])
+        } else {
+            ("200 OK", &data[..])
+        };
+        let content_range = range.map_or_else(String::new, |(start, end)| {
+            format!("Content-Range: bytes {start}-{end}/{}\r\n", data.len())
+        });
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\n{content_range}Connection: close\r\n\r\n",
+            body.len()
+        );
+        let stream = stream.get_mut();
+        stream.write_all(response.as_bytes()).await.unwrap();
+        if method != "HEAD" {
+            stream.write_all(body).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_from_s3() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-s3-{}", std::process::id()));
+        let data =
+            Arc::new((0..300_000_u32).map(|i| u8::try_from(i % 251).unwrap()).collect::<Vec<_>>());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = {
+            let data = data.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    drop(tokio::spawn(serve_s3_request(stream, data.clone())));
+                }
+            })
+        };
+
+        let profile = S3Profile {
+            region: Some("us-east-1".to_string()),
+            endpoint_url: Some(endpoint_url.parse().unwrap()),
+            force_path_style: true,
+            access_key: Some("access_key".to_string()),
+            secret_key: Some("secret_key".to_string()),
+            session_token: None,
+            anonymous: false,
+        };
+        let factory = DownloaderFactory::builder()
+            .unwrap()
+            .s3_profiles(HashMap::from([("local".to_string(), profile)]))
+            .build()
+            .unwrap();
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.clone()),
+            concurrent_number: Some(3),
+            ..model::CreateTask::new("s3://local@bucket/file.bin".parse().unwrap())
+        };
+        let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+        downloader.start().await.unwrap();
+        drop(downloader.join().await.unwrap());
+        assert_eq!(tokio::fs::read(directory.join("file.bin")).await.unwrap(), *data);
+
+        server.abort();
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+}
@@ -7,7 +7,10 @@ use crate::{
     error,
     error::{Error, Result},
     ext::PathExt,
-    fetcher::{Metadata, generic::ByteStream},
+    fetcher::{
+        Metadata,
+        generic::{self, ByteStream},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -32,18 +35,8 @@ impl Fetcher {
         U: fmt::Display + Send + Sync,
         V: fmt::Display + Send + Sync,
     {
-        let mut builder = services::Sftp::default();
-        let _ = builder
-            .root("/")
-            .endpoint(endpoint.to_string().as_str())
-            .user(user.to_string().as_str())
-            .key(identity_file.to_string().as_str())
-            .known_hosts_strategy("Accept");
         let file_path = PathBuf::from(file_path.to_string());
-
-        let operator =
-            Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish();
-
+        let operator = build_operator(endpoint, user, identity_file)?;
         let metadata = operator
             .stat(&file_path.to_string_lossy())
             .await
@@ -57,7 +50,12 @@ impl Fetcher {
     }
 
     pub fn fetch_metadata(&self) -> Metadata {
-        Metadata { length: self.length, filename: self.file_path.file_name_or_fallback() }
+        Metadata {
+            length: self.length,
+            filename: self.file_path.file_name_or_fallback(),
+            etag: None,
+            last_modified: None,
+        }
     }
 
     pub async fn fetch_all(&self) -> Result<ByteStream> {
@@ -73,3 +71,43 @@ impl Fetcher {
             .context(error::CreateReaderSnafu)
     }
 }
+
+/// Relative paths of the files under `directory`, `None` if it is not a
+/// directory
+pub async fn list_directory<S, T, U, V>(
+    endpoint: S,
+    user: T,
+    identity_file: U,
+    directory: V,
+) -> Result<Option<Vec<String>>>
+where
+    S: fmt::Display + Send + Sync,
+    T: fmt::Display + Send + Sync,
+    U: fmt::Display + Send + Sync,
+    V: fmt::Display + Send + Sync,
+{
+    let directory = directory.to_string();
+    let operator = build_operator(endpoint, user, identity_file)?;
+    match operator.stat(&directory).await {
+        Ok(metadata) if metadata.is_dir() => {
+            generic::list_files(&operator, &directory).await.map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn build_operator<S, T, U>(endpoint: S, user: T, identity_file: U) -> Result<Operator>
+where
+    S: fmt::Display,
+    T: fmt::Display,
+    U: fmt::Display,
+{
+    let mut builder = services::Sftp::default();
+    let _ = builder
+        .root("/")
+        .endpoint(endpoint.to_string().as_str())
+        .user(user.to_string().as_str())
+        .key(identity_file.to_string().as_str())
+        .known_hosts_strategy("Accept");
+    Ok(Operator::new(builder).with_context(|_| error::BuildOpenDALOperatorSnafu)?.finish())
+}
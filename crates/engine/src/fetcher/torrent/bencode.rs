@@ -0,0 +1,46 @@
+// nested lists and dictionaries deeper than this are rejected
+const MAX_DEPTH: usize = 64;
+
+/// Length of the bencoded value at the beginning of `data`
+pub fn value_len(data: &[u8]) -> Option<usize> { value_len_with_depth(data, 0) }
+
+/// Raw bytes of the value of `key` in the bencoded dictionary `data`, the info
+/// hash of a torrent is computed from the raw bytes of the info dictionary
+pub fn dict_value<'a>(data: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    if *data.first()? != b'd' {
+        return None;
+    }
+    let mut offset = 1;
+    while *data.get(offset)? != b'e' {
+        let key_len = value_len(&data[offset..])?;
+        let raw_key = &data[offset..offset + key_len];
+        offset += key_len;
+        let value_len = value_len(&data[offset..])?;
+        let colon = raw_key.iter().position(|&b| b == b':')?;
+        if &raw_key[colon + 1..] == key {
+            return Some(&data[offset..offset + value_len]);
+        }
+        offset += value_len;
+    }
+    None
+}
+
+fn value_len_with_depth(data: &[u8], depth: usize) -> Option<usize> {
+    match data.first()? {
+        b'i' => Some(data.iter().position(|&b| b == b'e')? + 1),
+        b'l' | b'd' if depth < MAX_DEPTH => {
+            let mut offset = 1;
+            while *data.get(offset)? != b'e' {
+                offset += value_len_with_depth(&data[offset..], depth + 1)?;
+            }
+            Some(offset + 1)
+        }
+        b'0'..=b'9' => {
+            let colon = data.iter().position(|&b| b == b':')?;
+            let len = std::str::from_utf8(&data[..colon]).ok()?.parse::<usize>().ok()?;
+            let end = colon.checked_add(1)?.checked_add(len)?;
+            (end <= data.len()).then_some(end)
+        }
+        _ => None,
+    }
+}
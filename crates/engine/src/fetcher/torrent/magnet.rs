@@ -0,0 +1,33 @@
+use snafu::OptionExt;
+
+use crate::{error, error::Result};
+
+/// Magnet link, rewritten as `magnet://<hex info hash>/?xt=...` by
+/// `caracal_base::utils::parse_uri`
+#[derive(Clone, Debug)]
+pub struct Magnet {
+    pub info_hash: [u8; 20],
+
+    pub trackers: Vec<String>,
+}
+
+impl Magnet {
+    pub fn parse(uri: &http::Uri) -> Result<Self> {
+        let info_hash = uri
+            .host()
+            .and_then(|host| hex::decode(host).ok())
+            .and_then(|info_hash| <[u8; 20]>::try_from(info_hash).ok())
+            .context(error::InvalidMagnetUriSnafu { uri: uri.clone() })?;
+
+        let trackers = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|param| param.strip_prefix("tr="))
+            .filter_map(|tracker| urlencoding::decode(tracker).ok())
+            .map(std::borrow::Cow::into_owned)
+            .collect();
+
+        Ok(Self { info_hash, trackers })
+    }
+}
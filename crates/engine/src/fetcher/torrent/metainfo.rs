@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use sha1::{Digest, Sha1};
+use snafu::{OptionExt, ResultExt};
+
+use crate::{error, error::Result, fetcher::torrent::bencode};
+
+/// Metadata of a single-file torrent
+#[derive(Clone, Debug)]
+pub struct Torrent {
+    pub info_hash: [u8; 20],
+
+    pub name: String,
+
+    pub length: u64,
+
+    pub piece_length: u64,
+
+    pub piece_hashes: Vec<[u8; 20]>,
+
+    /// Bencoded info dictionary, it is served to the peers which join with a
+    /// magnet link
+    pub info: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct MetaInfoFile {
+    announce: Option<String>,
+
+    #[serde(rename = "announce-list", default)]
+    announce_list: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    name: String,
+
+    #[serde(rename = "piece length")]
+    piece_length: u64,
+
+    pieces: ByteBuf,
+
+    length: Option<u64>,
+
+    files: Option<serde_bencode::value::Value>,
+}
+
+impl Torrent {
+    /// Parse the content of a `.torrent` file, the torrent and its trackers are
+    /// returned
+    pub fn from_torrent_file(data: &[u8]) -> Result<(Self, Vec<String>)> {
+        let MetaInfoFile { announce, announce_list } =
+            serde_bencode::from_bytes(data).context(error::DecodeTorrentSnafu)?;
+        let info = bencode::dict_value(data, b"info")
+            .context(error::InvalidTorrentSnafu { reason: "info dictionary is missing" })?;
+
+        let mut trackers = Vec::new();
+        for tracker in announce_list.into_iter().flatten().chain(announce) {
+            if !trackers.contains(&tracker) {
+                trackers.push(tracker);
+            }
+        }
+        Ok((Self::from_info(info)?, trackers))
+    }
+
+    /// Parse the bencoded info dictionary
+    pub fn from_info(info: &[u8]) -> Result<Self> {
+        let Info { name, piece_length, pieces, length, files } =
+            serde_bencode::from_bytes(info).context(error::DecodeTorrentSnafu)?;
+        let (Some(length), None) = (length, files) else {
+            return Err(error::Error::UnsupportedMultiFileTorrent { name });
+        };
+
+        // the name is used as file name, it must not escape the output directory
+        if Path::new(&name).file_name().is_none_or(|file_name| file_name != name.as_str()) {
+            return Err(error::Error::InvalidTorrent {
+                reason: format!("name `{name}` is invalid"),
+            });
+        }
+        if piece_length == 0 || !pieces.len().is_multiple_of(20) {
+            return Err(error::Error::InvalidTorrent { reason: "pieces are invalid".to_string() });
+        }
+        let piece_hashes = pieces.as_chunks::<20>().0.to_vec();
+        if piece_hashes.len() as u64 != length.div_ceil(piece_length)
+            || u32::try_from(piece_hashes.len()).is_err()
+        {
+            return Err(error::Error::InvalidTorrent {
+                reason: "number of pieces does not match the length".to_string(),
+            });
+        }
+
+        Ok(Self {
+            info_hash: Sha1::digest(info).into(),
+            name,
+            length,
+            piece_length,
+            piece_hashes,
+            info: info.to_vec(),
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn piece_count(&self) -> u32 {
+        // the number of pieces is checked while parsing
+        self.piece_hashes.len() as u32
+    }
+
+    pub const fn piece_offset(&self, index: u32) -> u64 { index as u64 * self.piece_length }
+
+    /// Length of the piece, the last piece may be shorter than the others
+    pub fn piece_len(&self, index: u32) -> u64 {
+        self.piece_length.min(self.length.saturating_sub(self.piece_offset(index)))
+    }
+}
@@ -0,0 +1,498 @@
+mod bencode;
+mod magnet;
+mod metainfo;
+mod peer;
+mod seeder;
+mod tracker;
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::future;
+use sha1::{Digest, Sha1};
+use snafu::ResultExt;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use self::{
+    magnet::Magnet,
+    metainfo::Torrent,
+    peer::{Connection, Message},
+    seeder::Seeder,
+    tracker::{Announce, AnnounceResponse},
+};
+use crate::{
+    error,
+    error::{Error, Result},
+    fetcher::Metadata,
+};
+
+#[derive(Clone, Debug)]
+pub struct Fetcher {
+    torrent: Arc<Torrent>,
+
+    swarm: Arc<Swarm>,
+}
+
+/// Parse the `.torrent` file, the trackers are not contacted
+pub async fn check_torrent_file<P>(file_path: P) -> Result<()>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    read_torrent_file(file_path).await.map(|_| ())
+}
+
+async fn read_torrent_file<P>(file_path: P) -> Result<(Torrent, Vec<String>)>
+where
+    P: AsRef<Path> + Send + Sync,
+{
+    let file_path = file_path.as_ref();
+    let data = tokio::fs::read(file_path)
+        .await
+        .with_context(|_| error::ReadTorrentFileSnafu { file_path: file_path.to_path_buf() })?;
+    Torrent::from_torrent_file(&data)
+}
+
+impl Fetcher {
+    pub async fn from_torrent_file<P>(http_client: reqwest::Client, file_path: P) -> Result<Self>
+    where
+        P: AsRef<Path> + Send + Sync,
+    {
+        let (torrent, trackers) = read_torrent_file(file_path).await?;
+        let swarm = Swarm::new(http_client, trackers, torrent.info_hash).await?;
+        Ok(Self { torrent: Arc::new(torrent), swarm: Arc::new(swarm) })
+    }
+
+    pub async fn from_magnet(http_client: reqwest::Client, uri: &http::Uri) -> Result<Self> {
+        let Magnet { info_hash, trackers } = Magnet::parse(uri)?;
+        let swarm = Swarm::new(http_client, trackers, info_hash).await?;
+
+        // the length is unknown before the metadata is fetched, a non-zero `left` marks
+        // us as a leecher
+        let peers = swarm.announce(swarm.port, 0, 0, 1, Some("started")).await?.peers;
+        let mut last_error = Error::NoTorrentPeer;
+        for addr in peers {
+            let info = match Connection::connect(addr, &info_hash, &swarm.peer_id).await {
+                Ok(mut connection) => connection.fetch_metadata(&info_hash).await,
+                Err(err) => Err(err),
+            };
+            match info {
+                Ok(info) => {
+                    let torrent = Torrent::from_info(&info)?;
+                    return Ok(Self { torrent: Arc::new(torrent), swarm: Arc::new(swarm) });
+                }
+                Err(err) => {
+                    tracing::warn!("Could not fetch metadata of torrent from peer {addr}, {err}");
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    pub fn fetch_metadata(&self) -> Metadata {
+        Metadata {
+            length: self.torrent.length,
+            filename: PathBuf::from(&self.torrent.name),
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    pub fn piece_length(&self) -> u64 { self.torrent.piece_length }
+
+    pub async fn fetch_bytes(&self, start: u64, end: u64) -> Result<ByteStream> {
+        let addr = self.swarm.next_peer(self.torrent.length).await?;
+        let connection = match self.open_connection(addr).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                self.swarm.forget_peer(addr);
+                return Err(err);
+            }
+        };
+        let next_piece = u32::try_from(start / self.torrent.piece_length).unwrap_or(u32::MAX);
+        Ok(ByteStream {
+            connection,
+            torrent: self.torrent.clone(),
+            swarm: self.swarm.clone(),
+            next_piece,
+            start,
+            end,
+            piece: Vec::new(),
+        })
+    }
+
+    pub async fn fetch_all(&self) -> Result<ByteStream> {
+        self.fetch_bytes(0, self.torrent.length.saturating_sub(1)).await
+    }
+
+    async fn open_connection(&self, addr: SocketAddr) -> Result<Connection> {
+        let mut connection =
+            Connection::connect(addr, &self.torrent.info_hash, &self.swarm.peer_id).await?;
+        connection.send(&Message::Interested).await?;
+        loop {
+            if connection.recv().await? == Message::Unchoke {
+                return Ok(connection);
+            }
+        }
+    }
+
+    /// Upload the downloaded file to other peers in background until `ratio`
+    /// times of its length is uploaded, seeding is stopped by aborting the
+    /// returned handle
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    pub fn seed(&self, file_path: PathBuf, ratio: f64) -> JoinHandle<()> {
+        // the upload limit does not need to be precise
+        let upload_limit = (self.torrent.length as f64 * ratio) as u64;
+        let seeder = Arc::new(Seeder::new(
+            self.torrent.clone(),
+            self.swarm.clone(),
+            file_path,
+            upload_limit,
+        ));
+        let listener = self.swarm.listener.lock().expect("lock is not poisoned").take();
+        tokio::spawn(async move {
+            let listener = match listener {
+                Some(listener) => Ok(listener),
+                None => bind_peer_listener().await,
+            };
+            let result = match listener {
+                Ok(listener) => seeder.serve(listener).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                tracing::warn!("{err}");
+            }
+        })
+    }
+}
+
+/// Peers of a torrent, which are discovered from its trackers
+#[derive(Debug)]
+struct Swarm {
+    http_client: reqwest::Client,
+
+    trackers: Vec<String>,
+
+    info_hash: [u8; 20],
+
+    peer_id: [u8; 20],
+
+    // the port announced to trackers, connections to it are accepted once the
+    // torrent is seeded
+    port: u16,
+
+    listener: Mutex<Option<TcpListener>>,
+
+    peers: Mutex<Vec<SocketAddr>>,
+
+    next_peer: AtomicUsize,
+}
+
+impl Swarm {
+    async fn new(
+        http_client: reqwest::Client,
+        trackers: Vec<String>,
+        info_hash: [u8; 20],
+    ) -> Result<Self> {
+        let listener = bind_peer_listener().await?;
+        let port = listener.local_addr().context(error::ListenForPeersSnafu)?.port();
+        Ok(Self {
+            http_client,
+            trackers,
+            info_hash,
+            peer_id: generate_peer_id(),
+            port,
+            listener: Mutex::new(Some(listener)),
+            peers: Mutex::default(),
+            next_peer: AtomicUsize::new(0),
+        })
+    }
+
+    /// Announce to all supported trackers, the peers from all trackers are
+    /// merged
+    async fn announce(
+        &self,
+        port: u16,
+        uploaded: u64,
+        downloaded: u64,
+        left: u64,
+        event: Option<&'static str>,
+    ) -> Result<AnnounceResponse> {
+        let announce = Announce {
+            info_hash: &self.info_hash,
+            peer_id: &self.peer_id,
+            port,
+            uploaded,
+            downloaded,
+            left,
+            event,
+        };
+        let responses = future::join_all(
+            self.trackers
+                .iter()
+                .filter(|tracker| tracker::is_supported(tracker))
+                .map(|tracker| tracker::announce(&self.http_client, tracker, &announce)),
+        )
+        .await;
+
+        let mut merged: Option<AnnounceResponse> = None;
+        let mut last_error = Error::NoTorrentTracker;
+        for response in responses {
+            match (response, &mut merged) {
+                (Ok(response), None) => merged = Some(response),
+                (Ok(AnnounceResponse { interval, peers }), Some(merged)) => {
+                    merged.interval = merged.interval.min(interval);
+                    merged.peers.extend(peers);
+                }
+                (Err(err), _) => {
+                    tracing::warn!("{err}");
+                    last_error = err;
+                }
+            }
+        }
+        let mut response = merged.ok_or(last_error)?;
+        response.peers.sort_unstable();
+        response.peers.dedup();
+        Ok(response)
+    }
+
+    /// Pick peers in turn, the trackers are asked again once all known peers
+    /// are gone
+    async fn next_peer(&self, left: u64) -> Result<SocketAddr> {
+        if self.peers.lock().expect("lock is not poisoned").is_empty() {
+            let response = self.announce(self.port, 0, 0, left, None).await?;
+            let mut peers = self.peers.lock().expect("lock is not poisoned");
+            if peers.is_empty() {
+                *peers = response.peers;
+            }
+        }
+
+        let peers = self.peers.lock().expect("lock is not poisoned");
+        if peers.is_empty() {
+            return Err(Error::NoTorrentPeer);
+        }
+        Ok(peers[self.next_peer.fetch_add(1, Ordering::Relaxed) % peers.len()])
+    }
+
+    fn forget_peer(&self, addr: SocketAddr) {
+        self.peers.lock().expect("lock is not poisoned").retain(|peer| *peer != addr);
+    }
+}
+
+pub struct ByteStream {
+    connection: Connection,
+
+    torrent: Arc<Torrent>,
+
+    swarm: Arc<Swarm>,
+
+    next_piece: u32,
+
+    start: u64,
+
+    end: u64,
+
+    piece: Vec<u8>,
+}
+
+impl ByteStream {
+    pub async fn bytes(&mut self) -> Result<Option<&[u8]>> {
+        let piece_offset = self.torrent.piece_offset(self.next_piece);
+        if self.next_piece >= self.torrent.piece_count() || piece_offset > self.end {
+            return Ok(None);
+        }
+
+        self.piece = match self.connection.download_piece(&self.torrent, self.next_piece).await {
+            Ok(piece) => piece,
+            Err(err) => {
+                self.swarm.forget_peer(self.connection.addr());
+                return Err(err);
+            }
+        };
+        self.next_piece += 1;
+
+        // only the requested range of the piece is returned
+        let len = self.piece.len();
+        let from = usize::try_from(self.start.saturating_sub(piece_offset)).unwrap_or(len).min(len);
+        let to = usize::try_from(self.end + 1 - piece_offset).unwrap_or(len).min(len);
+        Ok(Some(&self.piece[from..to]))
+    }
+}
+
+async fn bind_peer_listener() -> Result<TcpListener> {
+    TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)))
+        .await
+        .context(error::ListenForPeersSnafu)
+}
+
+fn generate_peer_id() -> [u8; 20] {
+    const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    let mut peer_id = *b"-CR0000-000000000000";
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let seed = Sha1::digest(format!("{nanos}-{}", std::process::id()));
+    for (byte, random) in peer_id[8..].iter_mut().zip(seed) {
+        *byte = CHARS[usize::from(random) % CHARS.len()];
+    }
+    peer_id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+    use caracal_base::{model, utils::parse_uri};
+    use serde_bencode::value::Value;
+    use sha1::{Digest, Sha1};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::{Swarm, metainfo::Torrent, seeder::Seeder, tracker};
+    use crate::{DownloaderFactory, Error};
+
+    const PIECE_LENGTH: usize = 32 * 1024;
+
+    fn create_torrent_file(name: &str, data: &[u8], tracker: &str) -> Vec<u8> {
+        let pieces = data.chunks(PIECE_LENGTH).flat_map(Sha1::digest).collect();
+        let info = HashMap::from([
+            (b"name".to_vec(), Value::Bytes(name.into())),
+            (b"piece length".to_vec(), Value::Int(i64::try_from(PIECE_LENGTH).unwrap())),
+            (b"pieces".to_vec(), Value::Bytes(pieces)),
+            (b"length".to_vec(), Value::Int(i64::try_from(data.len()).unwrap())),
+        ]);
+        let torrent = HashMap::from([
+            (b"announce".to_vec(), Value::Bytes(tracker.into())),
+            (b"info".to_vec(), Value::Dict(info)),
+        ]);
+        serde_bencode::to_bytes(&Value::Dict(torrent)).unwrap()
+    }
+
+    // a tracker which answers every announce with the seeder
+    async fn serve_tracker(listener: TcpListener, seeder: SocketAddr) {
+        let response = tracker::encode_response(&[seeder]);
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut buf = [0; 1024];
+                let len = stream.read(&mut buf).await.unwrap();
+                if len == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..len]);
+            }
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                response.len()
+            );
+            stream.write_all(&[header.as_bytes(), &response].concat()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_torrent() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-torrent-{}", std::process::id()));
+        let seed_file_path = directory.join("seed").join("file.bin");
+        tokio::fs::create_dir_all(seed_file_path.parent().unwrap()).await.unwrap();
+
+        let data = (0..300_000_u32).map(|i| u8::try_from(i % 251).unwrap()).collect::<Vec<_>>();
+        let tracker_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tracker_uri = format!("http://{}/announce", tracker_listener.local_addr().unwrap());
+        let torrent_file = create_torrent_file("file.bin", &data, &tracker_uri);
+        let torrent_file_path = directory.join("file.torrent");
+        tokio::fs::write(&torrent_file_path, &torrent_file).await.unwrap();
+        tokio::fs::write(&seed_file_path, &data).await.unwrap();
+
+        let (torrent, trackers) = Torrent::from_torrent_file(&torrent_file).unwrap();
+        let info_hash = hex::encode(torrent.info_hash);
+        let swarm = Arc::new(
+            Swarm::new(reqwest::Client::new(), trackers, torrent.info_hash).await.unwrap(),
+        );
+        let seeder_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let seeder_addr = seeder_listener.local_addr().unwrap();
+        let seeder = Arc::new(Seeder::new(Arc::new(torrent), swarm, seed_file_path, u64::MAX));
+        let _seeder = tokio::spawn(seeder.serve(seeder_listener));
+        let _tracker = tokio::spawn(serve_tracker(tracker_listener, seeder_addr));
+
+        let factory =
+            DownloaderFactory::builder().unwrap().torrent_seed_ratio(1.0).build().unwrap();
+        let magnet =
+            format!("magnet:?xt=urn:btih:{info_hash}&tr={}", urlencoding::encode(&tracker_uri));
+        for (uri, output_directory) in
+            [(torrent_file_path.display().to_string(), "from-file"), (magnet, "from-magnet")]
+        {
+            let output_directory = directory.join(output_directory);
+            let new_task = model::CreateTask {
+                output_directory: Some(output_directory.clone()),
+                concurrent_number: Some(3),
+                ..model::CreateTask::new(parse_uri(&uri).unwrap())
+            };
+            let mut downloader = factory.create_new_task(&new_task).await.unwrap();
+            downloader.start().await.unwrap();
+            let (_, status) = downloader.join().await.unwrap().unwrap();
+            assert_eq!(tokio::fs::read(output_directory.join("file.bin")).await.unwrap(), data);
+
+            // the progress is reported per piece
+            assert_eq!(status.total_chunk_count(), data.len().div_ceil(PIECE_LENGTH));
+            assert_eq!(status.completed_chunk_count(), status.total_chunk_count());
+
+            let seeder = downloader.seed().unwrap();
+            assert!(!seeder.is_finished());
+            seeder.abort();
+        }
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+
+    #[tokio::test]
+    async fn test_check_torrent_file() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-check-torrent-{}", std::process::id()));
+        tokio::fs::create_dir_all(&directory).await.unwrap();
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+
+        let torrent_file_path = directory.join("file.torrent");
+        let torrent_file = create_torrent_file("file.bin", &[0; 1024], "http://127.0.0.1/announce");
+        tokio::fs::write(&torrent_file_path, &torrent_file).await.unwrap();
+        let new_task =
+            model::CreateTask::new(parse_uri(&torrent_file_path.display().to_string()).unwrap());
+        factory.check_task(&new_task).await.unwrap();
+
+        let file = HashMap::from([
+            (b"length".to_vec(), Value::Int(1024)),
+            (b"path".to_vec(), Value::List(vec![Value::Bytes(b"file.bin".to_vec())])),
+        ]);
+        let info = HashMap::from([
+            (b"name".to_vec(), Value::Bytes(b"dataset".to_vec())),
+            (b"piece length".to_vec(), Value::Int(i64::try_from(PIECE_LENGTH).unwrap())),
+            (b"pieces".to_vec(), Value::Bytes(Sha1::digest([0; 1024]).to_vec())),
+            (b"files".to_vec(), Value::List(vec![Value::Dict(file)])),
+        ]);
+        let torrent = HashMap::from([(b"info".to_vec(), Value::Dict(info))]);
+        let torrent_file_path = directory.join("dataset.torrent");
+        tokio::fs::write(
+            &torrent_file_path,
+            serde_bencode::to_bytes(&Value::Dict(torrent)).unwrap(),
+        )
+        .await
+        .unwrap();
+        let new_task =
+            model::CreateTask::new(parse_uri(&torrent_file_path.display().to_string()).unwrap());
+        assert!(matches!(
+            factory.check_task(&new_task).await,
+            Err(Error::UnsupportedMultiFileTorrent { name }) if name == "dataset"
+        ));
+
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
+}
@@ -0,0 +1,465 @@
+use std::{collections::HashMap, future::Future, io, net::SocketAddr, time::Duration};
+
+use serde_bencode::value::Value;
+use sha1::{Digest, Sha1};
+use snafu::ResultExt;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    error,
+    error::{Error, Result},
+    fetcher::torrent::{bencode, metainfo::Torrent},
+};
+
+const PROTOCOL: &[u8; 19] = b"BitTorrent protocol";
+
+const HANDSHAKE_LEN: usize = 68;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// a piece message carries at most one block, the bitfield of huge torrents is
+// the longest message in practice
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+const MAX_METADATA_LEN: usize = 16 << 20;
+
+// requests sent to a peer without waiting for the blocks
+const MAX_PENDING_REQUESTS: usize = 32;
+
+pub const BLOCK_SIZE: usize = 16 * 1024;
+
+/// Id of the `ut_metadata` extension (BEP 9) on our side
+pub const UT_METADATA_ID: u8 = 1;
+
+pub const METADATA_PIECE_SIZE: usize = 16 * 1024;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Message {
+    KeepAlive,
+    Choke,
+    Unchoke,
+    Interested,
+    NotInterested,
+    Have(u32),
+    Bitfield(Vec<u8>),
+    Request { index: u32, begin: u32, length: u32 },
+    Piece { index: u32, begin: u32, block: Vec<u8> },
+    Cancel { index: u32, begin: u32, length: u32 },
+    Extended { id: u8, payload: Vec<u8> },
+    Unknown(u8),
+}
+
+impl Message {
+    fn encode(&self) -> Vec<u8> {
+        let (id, payload) = match self {
+            Self::KeepAlive => return vec![0; 4],
+            Self::Choke => (0, Vec::new()),
+            Self::Unchoke => (1, Vec::new()),
+            Self::Interested => (2, Vec::new()),
+            Self::NotInterested => (3, Vec::new()),
+            Self::Have(index) => (4, index.to_be_bytes().to_vec()),
+            Self::Bitfield(bitfield) => (5, bitfield.clone()),
+            Self::Request { index, begin, length } => (6, encode_u32s(&[*index, *begin, *length])),
+            Self::Piece { index, begin, block } => {
+                (7, [&encode_u32s(&[*index, *begin])[..], block].concat())
+            }
+            Self::Cancel { index, begin, length } => (8, encode_u32s(&[*index, *begin, *length])),
+            Self::Extended { id, payload } => (20, [&[*id][..], payload].concat()),
+            Self::Unknown(id) => (*id, Vec::new()),
+        };
+        let len = u32::try_from(payload.len() + 1).expect("message is shorter than 4 GiB; qed");
+        [&len.to_be_bytes()[..], &[id], &payload].concat()
+    }
+
+    fn decode(payload: &[u8]) -> Option<Self> {
+        let (&id, payload) = payload.split_first()?;
+        let u32_at = |offset: usize| {
+            payload.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        };
+        let message = match id {
+            0 => Self::Choke,
+            1 => Self::Unchoke,
+            2 => Self::Interested,
+            3 => Self::NotInterested,
+            4 => Self::Have(u32_at(0)?),
+            5 => Self::Bitfield(payload.to_vec()),
+            6 => Self::Request { index: u32_at(0)?, begin: u32_at(4)?, length: u32_at(8)? },
+            7 => Self::Piece {
+                index: u32_at(0)?,
+                begin: u32_at(4)?,
+                block: payload.get(8..)?.to_vec(),
+            },
+            8 => Self::Cancel { index: u32_at(0)?, begin: u32_at(4)?, length: u32_at(8)? },
+            20 => {
+                let (&id, payload) = payload.split_first()?;
+                Self::Extended { id, payload: payload.to_vec() }
+            }
+            id => Self::Unknown(id),
+        };
+        Some(message)
+    }
+}
+
+/// Message of the `ut_metadata` extension
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MetadataMessage {
+    Request { piece: usize },
+    Data { piece: usize, total_size: usize, data: Vec<u8> },
+    Reject { piece: usize },
+}
+
+impl MetadataMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let (msg_type, piece, total_size) = match self {
+            Self::Request { piece } => (0, piece, None),
+            Self::Data { piece, total_size, .. } => (1, piece, Some(total_size)),
+            Self::Reject { piece } => (2, piece, None),
+        };
+        let mut dict = HashMap::from([
+            (b"msg_type".to_vec(), Value::Int(msg_type)),
+            (b"piece".to_vec(), Value::Int(int(*piece))),
+        ]);
+        if let Some(total_size) = total_size {
+            let _unused = dict.insert(b"total_size".to_vec(), Value::Int(int(*total_size)));
+        }
+        let mut payload = encode_value(dict);
+        if let Self::Data { data, .. } = self {
+            payload.extend_from_slice(data);
+        }
+        payload
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        // the data of a piece follows the dictionary
+        let dict_len = bencode::value_len(payload)?;
+        let Ok(Value::Dict(dict)) = serde_bencode::from_bytes(&payload[..dict_len]) else {
+            return None;
+        };
+        let get = |key: &[u8]| match dict.get(key) {
+            Some(Value::Int(value)) => usize::try_from(*value).ok(),
+            _ => None,
+        };
+        let piece = get(b"piece")?;
+        match get(b"msg_type")? {
+            0 => Some(Self::Request { piece }),
+            1 => Some(Self::Data {
+                piece,
+                total_size: get(b"total_size")?,
+                data: payload[dict_len..].to_vec(),
+            }),
+            2 => Some(Self::Reject { piece }),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of the handshake of the extension protocol (BEP 10)
+pub fn extended_handshake(metadata_size: Option<usize>) -> Message {
+    let mut dict = HashMap::from([(
+        b"m".to_vec(),
+        Value::Dict(HashMap::from([(
+            b"ut_metadata".to_vec(),
+            Value::Int(i64::from(UT_METADATA_ID)),
+        )])),
+    )]);
+    if let Some(metadata_size) = metadata_size {
+        let _unused = dict.insert(b"metadata_size".to_vec(), Value::Int(int(metadata_size)));
+    }
+    Message::Extended { id: 0, payload: encode_value(dict) }
+}
+
+/// Id of `ut_metadata` on the side of the peer and the size of the metadata
+pub fn parse_extended_handshake(payload: &[u8]) -> (Option<u8>, Option<usize>) {
+    let Ok(Value::Dict(dict)) = serde_bencode::from_bytes::<Value>(payload) else {
+        return (None, None);
+    };
+    let ut_metadata = match dict.get(&b"m"[..]) {
+        Some(Value::Dict(m)) => match m.get(&b"ut_metadata"[..]) {
+            Some(Value::Int(id)) => u8::try_from(*id).ok().filter(|&id| id != 0),
+            _ => None,
+        },
+        _ => None,
+    };
+    let metadata_size = match dict.get(&b"metadata_size"[..]) {
+        Some(Value::Int(size)) => usize::try_from(*size).ok(),
+        _ => None,
+    };
+    (ut_metadata, metadata_size)
+}
+
+/// Bitfield of a peer which has all pieces
+pub fn full_bitfield(piece_count: u32) -> Vec<u8> {
+    let mut bitfield = vec![0xff; piece_count.div_ceil(8) as usize];
+    if let Some(last) = bitfield.last_mut() {
+        *last <<= (8 - piece_count % 8) % 8;
+    }
+    bitfield
+}
+
+#[derive(Debug)]
+pub struct Connection {
+    stream: TcpStream,
+
+    addr: SocketAddr,
+
+    supports_extension: bool,
+
+    // pieces announced by the peer, unknown before the peer sends its bitfield
+    pieces: Option<Vec<u8>>,
+}
+
+impl Connection {
+    pub async fn connect(
+        addr: SocketAddr,
+        info_hash: &[u8; 20],
+        peer_id: &[u8; 20],
+    ) -> Result<Self> {
+        let stream = with_timeout(addr, TcpStream::connect(addr)).await?;
+        let mut connection = Self { stream, addr, supports_extension: false, pieces: None };
+        connection.send_handshake(info_hash, peer_id).await?;
+        connection.recv_handshake(info_hash).await?;
+        Ok(connection)
+    }
+
+    pub async fn accept(
+        stream: TcpStream,
+        addr: SocketAddr,
+        info_hash: &[u8; 20],
+        peer_id: &[u8; 20],
+    ) -> Result<Self> {
+        let mut connection = Self { stream, addr, supports_extension: false, pieces: None };
+        connection.recv_handshake(info_hash).await?;
+        connection.send_handshake(info_hash, peer_id).await?;
+        Ok(connection)
+    }
+
+    pub const fn addr(&self) -> SocketAddr { self.addr }
+
+    pub const fn supports_extension(&self) -> bool { self.supports_extension }
+
+    pub fn has_piece(&self, index: u32) -> bool {
+        self.pieces.as_ref().is_none_or(|pieces| {
+            pieces.get(index as usize / 8).is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+        })
+    }
+
+    async fn send_handshake(&mut self, info_hash: &[u8; 20], peer_id: &[u8; 20]) -> Result<()> {
+        let mut reserved = [0; 8];
+        // support the extension protocol
+        reserved[5] |= 0x10;
+        let handshake = [&[19][..], PROTOCOL, &reserved, info_hash, peer_id].concat();
+        with_timeout(self.addr, self.stream.write_all(&handshake)).await
+    }
+
+    async fn recv_handshake(&mut self, info_hash: &[u8; 20]) -> Result<()> {
+        let mut handshake = [0; HANDSHAKE_LEN];
+        let _ = with_timeout(self.addr, self.stream.read_exact(&mut handshake)).await?;
+        if handshake[0] != 19 || &handshake[1..20] != PROTOCOL {
+            return Err(self.protocol_error("handshake is invalid"));
+        }
+        if &handshake[28..48] != info_hash {
+            return Err(self.protocol_error("info hash does not match"));
+        }
+        self.supports_extension = handshake[25] & 0x10 != 0;
+        Ok(())
+    }
+
+    pub async fn send(&mut self, message: &Message) -> Result<()> {
+        with_timeout(self.addr, self.stream.write_all(&message.encode())).await
+    }
+
+    pub async fn recv(&mut self) -> Result<Message> {
+        let mut len = [0; 4];
+        let _ = with_timeout(self.addr, self.stream.read_exact(&mut len)).await?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 {
+            return Ok(Message::KeepAlive);
+        }
+        if len > MAX_MESSAGE_LEN {
+            return Err(self.protocol_error("message is too long"));
+        }
+
+        let mut payload = vec![0; len];
+        let _ = with_timeout(self.addr, self.stream.read_exact(&mut payload)).await?;
+        let message =
+            Message::decode(&payload).ok_or_else(|| self.protocol_error("message is invalid"))?;
+        match &message {
+            Message::Bitfield(bitfield) => self.pieces = Some(bitfield.clone()),
+            Message::Have(index) => {
+                if let Some(byte) =
+                    self.pieces.as_mut().and_then(|pieces| pieces.get_mut(*index as usize / 8))
+                {
+                    *byte |= 0x80 >> (index % 8);
+                }
+            }
+            _ => {}
+        }
+        Ok(message)
+    }
+
+    /// Download and verify a piece from the peer, the peer must have unchoked
+    /// us
+    pub async fn download_piece(&mut self, torrent: &Torrent, index: u32) -> Result<Vec<u8>> {
+        if !self.has_piece(index) {
+            return Err(self.protocol_error(&format!("peer does not have piece {index}")));
+        }
+
+        let piece_len = usize::try_from(torrent.piece_len(index))
+            .map_err(|_| self.protocol_error("piece is too long"))?;
+        let block_count = piece_len.div_ceil(BLOCK_SIZE);
+        let mut piece = vec![0; piece_len];
+        let mut received = vec![false; block_count];
+        let mut remaining = block_count;
+        let mut next_block = 0;
+        while remaining > 0 {
+            while next_block < block_count
+                && next_block - (block_count - remaining) < MAX_PENDING_REQUESTS
+            {
+                let begin = next_block * BLOCK_SIZE;
+                let length = BLOCK_SIZE.min(piece_len - begin);
+                self.send(&Message::Request {
+                    index,
+                    begin: u32::try_from(begin).unwrap_or(u32::MAX),
+                    length: u32::try_from(length).unwrap_or(u32::MAX),
+                })
+                .await?;
+                next_block += 1;
+            }
+
+            match self.recv().await? {
+                Message::Piece { index: piece_index, begin, block } if piece_index == index => {
+                    let begin = begin as usize;
+                    let block_index = begin / BLOCK_SIZE;
+                    // ignore unexpected and duplicated blocks
+                    if !begin.is_multiple_of(BLOCK_SIZE)
+                        || received.get(block_index) != Some(&false)
+                        || block.len() != BLOCK_SIZE.min(piece_len - begin)
+                    {
+                        continue;
+                    }
+                    piece[begin..begin + block.len()].copy_from_slice(&block);
+                    received[block_index] = true;
+                    remaining -= 1;
+                }
+                Message::Choke => return Err(self.protocol_error("peer choked the connection")),
+                _ => {}
+            }
+        }
+
+        if Sha1::digest(&piece)[..] != torrent.piece_hashes[index as usize] {
+            return Err(Error::PieceHashMismatch { addr: self.addr, index });
+        }
+        Ok(piece)
+    }
+
+    /// Fetch the info dictionary of a torrent from the peer with the
+    /// `ut_metadata` extension
+    pub async fn fetch_metadata(&mut self, info_hash: &[u8; 20]) -> Result<Vec<u8>> {
+        if !self.supports_extension {
+            return Err(self.protocol_error("extension protocol is not supported"));
+        }
+        self.send(&extended_handshake(None)).await?;
+        let (ut_metadata, metadata_size) = loop {
+            if let Message::Extended { id: 0, payload } = self.recv().await? {
+                break parse_extended_handshake(&payload);
+            }
+        };
+        let (Some(ut_metadata), Some(metadata_size)) = (ut_metadata, metadata_size) else {
+            return Err(self.protocol_error("ut_metadata extension is not supported"));
+        };
+        if metadata_size == 0 || metadata_size > MAX_METADATA_LEN {
+            return Err(self.protocol_error("size of metadata is invalid"));
+        }
+
+        let piece_count = metadata_size.div_ceil(METADATA_PIECE_SIZE);
+        for piece in 0..piece_count {
+            let payload = MetadataMessage::Request { piece }.encode();
+            self.send(&Message::Extended { id: ut_metadata, payload }).await?;
+        }
+
+        let mut metadata = vec![0; metadata_size];
+        let mut received = vec![false; piece_count];
+        let mut remaining = piece_count;
+        while remaining > 0 {
+            let Message::Extended { id: UT_METADATA_ID, payload } = self.recv().await? else {
+                continue;
+            };
+            match MetadataMessage::decode(&payload) {
+                Some(MetadataMessage::Data { piece, data, .. }) if piece < piece_count => {
+                    let begin = piece * METADATA_PIECE_SIZE;
+                    let end = metadata_size.min(begin + METADATA_PIECE_SIZE);
+                    if data.len() != end - begin {
+                        return Err(self.protocol_error("metadata piece is invalid"));
+                    }
+                    if !received[piece] {
+                        metadata[begin..end].copy_from_slice(&data);
+                        received[piece] = true;
+                        remaining -= 1;
+                    }
+                }
+                Some(MetadataMessage::Reject { .. }) => {
+                    return Err(self.protocol_error("metadata request is rejected"));
+                }
+                _ => {}
+            }
+        }
+
+        if Sha1::digest(&metadata)[..] != info_hash[..] {
+            return Err(self.protocol_error("metadata does not match the info hash"));
+        }
+        Ok(metadata)
+    }
+
+    pub fn protocol_error(&self, reason: &str) -> Error {
+        Error::PeerProtocol { addr: self.addr, reason: reason.to_string() }
+    }
+}
+
+async fn with_timeout<T, F>(addr: SocketAddr, future: F) -> Result<T>
+where
+    F: Future<Output = io::Result<T>> + Send,
+{
+    tokio::time::timeout(TIMEOUT, future)
+        .await
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+        .context(error::PeerConnectionSnafu { addr })
+}
+
+fn encode_u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_be_bytes()).collect()
+}
+
+fn encode_value(dict: HashMap<Vec<u8>, Value>) -> Vec<u8> {
+    serde_bencode::to_bytes(&Value::Dict(dict)).expect("value is serializable; qed")
+}
+
+fn int(value: usize) -> i64 { i64::try_from(value).unwrap_or(i64::MAX) }
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, MetadataMessage, full_bitfield};
+
+    #[test]
+    fn test_message() {
+        let messages = [
+            Message::Interested,
+            Message::Have(7),
+            Message::Request { index: 1, begin: 16384, length: 16384 },
+            Message::Piece { index: 1, begin: 0, block: vec![1, 2, 3] },
+            Message::Extended { id: 1, payload: b"de".to_vec() },
+        ];
+        for message in messages {
+            let encoded = message.encode();
+            assert_eq!(Message::decode(&encoded[4..]), Some(message));
+        }
+
+        let message = MetadataMessage::Data { piece: 0, total_size: 3, data: vec![1, 2, 3] };
+        assert_eq!(MetadataMessage::decode(&message.encode()), Some(message));
+
+        assert_eq!(full_bitfield(10), vec![0xff, 0xc0]);
+        assert_eq!(full_bitfield(16), vec![0xff, 0xff]);
+    }
+}
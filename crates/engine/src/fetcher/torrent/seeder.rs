@@ -0,0 +1,189 @@
+use std::{
+    io::SeekFrom,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use snafu::ResultExt;
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+    task::JoinSet,
+    time::Instant,
+};
+
+use crate::{
+    error,
+    error::Result,
+    fetcher::torrent::{
+        Swarm,
+        metainfo::Torrent,
+        peer::{self, Connection, METADATA_PIECE_SIZE, Message, MetadataMessage, UT_METADATA_ID},
+    },
+};
+
+const ANNOUNCE_RETRY_INTERVAL: Duration = Duration::from_mins(5);
+
+// longest block requested by common clients
+const MAX_REQUEST_LEN: u32 = 128 * 1024;
+
+/// Upload a completed torrent to other peers until the upload limit is reached
+#[derive(Debug)]
+pub struct Seeder {
+    torrent: Arc<Torrent>,
+
+    swarm: Arc<Swarm>,
+
+    file_path: PathBuf,
+
+    upload_limit: u64,
+
+    uploaded: AtomicU64,
+
+    finished: Notify,
+}
+
+impl Seeder {
+    pub fn new(
+        torrent: Arc<Torrent>,
+        swarm: Arc<Swarm>,
+        file_path: PathBuf,
+        upload_limit: u64,
+    ) -> Self {
+        Self {
+            torrent,
+            swarm,
+            file_path,
+            upload_limit,
+            uploaded: AtomicU64::new(0),
+            finished: Notify::new(),
+        }
+    }
+
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        let port = listener.local_addr().context(error::ListenForPeersSnafu)?.port();
+        tracing::info!(
+            "Seeding torrent `{}` on port {port}, upload limit: {} bytes",
+            self.torrent.name,
+            self.upload_limit
+        );
+
+        let mut connections = JoinSet::new();
+        let mut event = Some("completed");
+        let mut next_announce = Instant::now();
+        loop {
+            tokio::select! {
+                () = self.finished.notified() => break,
+                () = tokio::time::sleep_until(next_announce) => {
+                    let interval = match self.announce(port, event).await {
+                        Ok(interval) => {
+                            event = None;
+                            interval
+                        }
+                        Err(err) => {
+                            tracing::debug!("{err}");
+                            ANNOUNCE_RETRY_INTERVAL
+                        }
+                    };
+                    next_announce = Instant::now() + interval;
+                }
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, addr)) => {
+                        let seeder = self.clone();
+                        let _handle = connections.spawn(async move {
+                            if let Err(err) = seeder.serve_peer(stream, addr).await {
+                                tracing::debug!("{err}");
+                            }
+                        });
+                    }
+                    Err(err) => tracing::warn!("Error occurs while accepting peer, error: {err}"),
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+
+        connections.abort_all();
+        if let Err(err) = self.announce(port, Some("stopped")).await {
+            tracing::debug!("{err}");
+        }
+        tracing::info!("Stop seeding torrent `{}`", self.torrent.name);
+        Ok(())
+    }
+
+    async fn announce(&self, port: u16, event: Option<&'static str>) -> Result<Duration> {
+        let uploaded = self.uploaded.load(Ordering::Relaxed);
+        let response = self.swarm.announce(port, uploaded, self.torrent.length, 0, event).await?;
+        Ok(response.interval)
+    }
+
+    async fn serve_peer(&self, stream: TcpStream, addr: SocketAddr) -> Result<()> {
+        let torrent = &self.torrent;
+        let mut connection =
+            Connection::accept(stream, addr, &torrent.info_hash, &self.swarm.peer_id).await?;
+        connection.send(&Message::Bitfield(peer::full_bitfield(torrent.piece_count()))).await?;
+        if connection.supports_extension() {
+            connection.send(&peer::extended_handshake(Some(torrent.info.len()))).await?;
+        }
+
+        let mut file = File::open(&self.file_path)
+            .await
+            .with_context(|_| error::OpenFileSnafu { file_path: self.file_path.clone() })?;
+        let mut peer_ut_metadata = None;
+        loop {
+            match connection.recv().await? {
+                Message::Interested => connection.send(&Message::Unchoke).await?,
+                Message::Request { index, begin, length } => {
+                    if index >= torrent.piece_count()
+                        || length > MAX_REQUEST_LEN
+                        || u64::from(begin) + u64::from(length) > torrent.piece_len(index)
+                    {
+                        return Err(connection.protocol_error("request is out of range"));
+                    }
+                    let offset = torrent.piece_offset(index) + u64::from(begin);
+                    let mut block = vec![0; length as usize];
+                    let _ = file.seek(SeekFrom::Start(offset)).await.with_context(|_| {
+                        error::SeekFileSnafu { file_path: self.file_path.clone() }
+                    })?;
+                    let _ = file.read_exact(&mut block).await.with_context(|_| {
+                        error::ReadFileSnafu { file_path: self.file_path.clone() }
+                    })?;
+                    connection.send(&Message::Piece { index, begin, block }).await?;
+
+                    let uploaded = self.uploaded.fetch_add(u64::from(length), Ordering::Relaxed);
+                    if uploaded + u64::from(length) >= self.upload_limit {
+                        self.finished.notify_one();
+                    }
+                }
+                Message::Extended { id: 0, payload } => {
+                    peer_ut_metadata = peer::parse_extended_handshake(&payload).0;
+                }
+                Message::Extended { id: UT_METADATA_ID, payload } => {
+                    if let (Some(id), Some(MetadataMessage::Request { piece })) =
+                        (peer_ut_metadata, MetadataMessage::decode(&payload))
+                    {
+                        let payload = metadata_response(&torrent.info, piece).encode();
+                        connection.send(&Message::Extended { id, payload }).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn metadata_response(info: &[u8], piece: usize) -> MetadataMessage {
+    let begin = piece.saturating_mul(METADATA_PIECE_SIZE);
+    if begin < info.len() {
+        let end = info.len().min(begin + METADATA_PIECE_SIZE);
+        MetadataMessage::Data { piece, total_size: info.len(), data: info[begin..end].to_vec() }
+    } else {
+        MetadataMessage::Reject { piece }
+    }
+}
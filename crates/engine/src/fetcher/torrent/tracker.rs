@@ -0,0 +1,168 @@
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use serde_bencode::value::Value;
+use snafu::ResultExt;
+
+use crate::{error, error::Result};
+
+const DEFAULT_ANNOUNCE_INTERVAL: Duration = Duration::from_mins(30);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Announce<'a> {
+    pub info_hash: &'a [u8; 20],
+
+    pub peer_id: &'a [u8; 20],
+
+    pub port: u16,
+
+    pub uploaded: u64,
+
+    pub downloaded: u64,
+
+    pub left: u64,
+
+    pub event: Option<&'static str>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnnounceResponse {
+    pub interval: Duration,
+
+    pub peers: Vec<SocketAddr>,
+}
+
+/// Only HTTP trackers are supported
+pub fn is_supported(tracker: &str) -> bool {
+    tracker.starts_with("http://") || tracker.starts_with("https://")
+}
+
+pub async fn announce(
+    client: &reqwest::Client,
+    tracker: &str,
+    announce: &Announce<'_>,
+) -> Result<AnnounceResponse> {
+    let Announce { info_hash, peer_id, port, uploaded, downloaded, left, event } = *announce;
+    let separator = if tracker.contains('?') { '&' } else { '?' };
+    let mut url = format!(
+        "{tracker}{separator}info_hash={}&peer_id={}&port={port}&uploaded={uploaded}&\
+         downloaded={downloaded}&left={left}&compact=1",
+        urlencoding::encode_binary(info_hash),
+        urlencoding::encode_binary(peer_id),
+    );
+    if let Some(event) = event {
+        url.push_str("&event=");
+        url.push_str(event);
+    }
+
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(error::AnnounceToTrackerSnafu { tracker })?
+        .bytes()
+        .await
+        .context(error::AnnounceToTrackerSnafu { tracker })?;
+    parse_response(tracker, &body)
+}
+
+fn parse_response(tracker: &str, body: &[u8]) -> Result<AnnounceResponse> {
+    let invalid_response = || error::Error::TrackerFailure {
+        tracker: tracker.to_string(),
+        reason: "response is not a bencoded dictionary".to_string(),
+    };
+    let Ok(Value::Dict(response)) = serde_bencode::from_bytes::<Value>(body) else {
+        return Err(invalid_response());
+    };
+
+    if let Some(Value::Bytes(reason)) = response.get(&b"failure reason"[..]) {
+        return Err(error::Error::TrackerFailure {
+            tracker: tracker.to_string(),
+            reason: String::from_utf8_lossy(reason).into_owned(),
+        });
+    }
+
+    let interval = match response.get(&b"interval"[..]) {
+        Some(Value::Int(interval)) => u64::try_from(*interval).map(Duration::from_secs).ok(),
+        _ => None,
+    }
+    .unwrap_or(DEFAULT_ANNOUNCE_INTERVAL);
+
+    let mut peers = match response.get(&b"peers"[..]) {
+        Some(Value::Bytes(peers)) => peers
+            .as_chunks::<6>()
+            .0
+            .iter()
+            .map(|peer| {
+                let ip = Ipv4Addr::new(peer[0], peer[1], peer[2], peer[3]);
+                SocketAddr::from((ip, u16::from_be_bytes([peer[4], peer[5]])))
+            })
+            .collect(),
+        Some(Value::List(peers)) => peers.iter().filter_map(parse_peer_dict).collect(),
+        _ => Vec::new(),
+    };
+    if let Some(Value::Bytes(peers6)) = response.get(&b"peers6"[..]) {
+        peers.extend(peers6.as_chunks::<18>().0.iter().map(|peer| {
+            let (ip, port) = peer.split_at(16);
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(ip).expect("16 bytes; qed"));
+            SocketAddr::from((ip, u16::from_be_bytes([port[0], port[1]])))
+        }));
+    }
+
+    Ok(AnnounceResponse { interval, peers })
+}
+
+fn parse_peer_dict(peer: &Value) -> Option<SocketAddr> {
+    let Value::Dict(peer) = peer else { return None };
+    let get = |key: &[u8]| peer.get(key);
+    let (Some(Value::Bytes(ip)), Some(Value::Int(port))) = (get(b"ip"), get(b"port")) else {
+        return None;
+    };
+    let ip = std::str::from_utf8(ip).ok()?.parse().ok()?;
+    Some(SocketAddr::new(ip, u16::try_from(*port).ok()?))
+}
+
+/// Build an announce response, used by the tracker of tests
+#[cfg(test)]
+pub fn encode_response(peers: &[SocketAddr]) -> Vec<u8> {
+    use std::collections::HashMap;
+
+    let compact = peers
+        .iter()
+        .filter_map(|peer| match peer {
+            SocketAddr::V4(peer) => {
+                Some([&peer.ip().octets()[..], &peer.port().to_be_bytes()].concat())
+            }
+            SocketAddr::V6(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .concat();
+    let response = HashMap::from([
+        (b"interval".to_vec(), Value::Int(1800)),
+        (b"peers".to_vec(), Value::Bytes(compact)),
+    ]);
+    serde_bencode::to_bytes(&Value::Dict(response)).expect("value is serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::{encode_response, parse_response};
+
+    #[test]
+    fn test_parse_response() {
+        let peer = SocketAddr::from(([127, 0, 0, 1], 6881));
+        let response = parse_response("http://tracker", &encode_response(&[peer])).unwrap();
+        assert_eq!(response.peers, vec![peer]);
+        assert_eq!(response.interval.as_secs(), 1800);
+
+        let response = b"d5:peersld2:ip9:127.0.0.14:porti6881eeee";
+        assert_eq!(parse_response("http://tracker", response).unwrap().peers, vec![peer]);
+
+        assert!(parse_response("http://tracker", b"d14:failure reason4:denye").is_err());
+    }
+}
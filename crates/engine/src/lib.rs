@@ -7,7 +7,9 @@ mod fetcher;
 mod task_scheduler;
 
 pub use self::{
-    downloader::{Downloader, DownloaderFactory, DownloaderStatus, MINIMUM_CHUNK_SIZE},
+    downloader::{
+        ConnectionTuning, Downloader, DownloaderFactory, DownloaderStatus, MINIMUM_CHUNK_SIZE,
+    },
     error::Error,
-    task_scheduler::TaskScheduler,
+    task_scheduler::{Error as TaskSchedulerError, TaskScheduler},
 };
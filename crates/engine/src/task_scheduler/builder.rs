@@ -0,0 +1,151 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use caracal_base::{
+    model::{DownloadSchedule, TaskHooks},
+    utils::RetryInterval,
+};
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+
+use crate::{
+    downloader::DownloaderFactory,
+    task_scheduler::{TaskScheduler, hook::HookRunner, store::TaskStore, worker::Worker},
+};
+
+// subscribers which fall behind more than this number of events miss the oldest
+// ones
+const TASK_EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct Builder {
+    pub factory: DownloaderFactory,
+
+    pub max_concurrent_task_number: usize,
+
+    pub task_store_file_path: Option<PathBuf>,
+
+    pub retry_interval: RetryInterval,
+
+    pub preemption: bool,
+
+    pub schedule: DownloadSchedule,
+
+    pub hooks: TaskHooks,
+
+    pub hook_timeout: Duration,
+
+    pub allow_task_commands: bool,
+}
+
+impl Builder {
+    pub fn new(factory: DownloaderFactory) -> Self {
+        Self {
+            factory,
+            max_concurrent_task_number: 10,
+            task_store_file_path: None,
+            retry_interval: RetryInterval::recommended(),
+            preemption: false,
+            schedule: DownloadSchedule::default(),
+            hooks: TaskHooks::default(),
+            hook_timeout: Duration::from_secs(30),
+            allow_task_commands: false,
+        }
+    }
+
+    pub const fn max_concurrent_task_number(mut self, max_concurrent_task_number: usize) -> Self {
+        self.max_concurrent_task_number = max_concurrent_task_number;
+        self
+    }
+
+    pub fn task_store_file_path<P>(mut self, task_store_file_path: Option<P>) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.task_store_file_path = task_store_file_path.map(|path| path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn retry_interval(mut self, retry_interval: RetryInterval) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Pause the resumable downloading task of the lowest priority if a task
+    /// of higher priority is pending while all slots are taken
+    pub const fn preemption(mut self, preemption: bool) -> Self {
+        self.preemption = preemption;
+        self
+    }
+
+    /// Only download within the windows of the schedule, downloading tasks
+    /// are paused when a window closes and resumed when the next one opens
+    pub fn schedule(mut self, schedule: DownloadSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Run after every task is completed or failed, in addition to the hooks
+    /// of the task
+    pub fn hooks(mut self, hooks: TaskHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Commands and webhooks which run longer than this are aborted
+    pub const fn hook_timeout(mut self, hook_timeout: Duration) -> Self {
+        self.hook_timeout = hook_timeout;
+        self
+    }
+
+    /// Run the commands in the hooks of tasks, they are skipped by default
+    /// because they run as the user of the daemon
+    pub const fn allow_task_commands(mut self, allow_task_commands: bool) -> Self {
+        self.allow_task_commands = allow_task_commands;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> (TaskScheduler, JoinHandle<()>) {
+        let Self {
+            factory,
+            max_concurrent_task_number,
+            task_store_file_path,
+            retry_interval,
+            preemption,
+            schedule,
+            hooks,
+            hook_timeout,
+            allow_task_commands,
+        } = self;
+
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (task_event_sender, _) = broadcast::channel(TASK_EVENT_CHANNEL_CAPACITY);
+        let join_handle = tokio::spawn({
+            let factory = factory.clone();
+            let event_sender = event_sender.clone();
+            let task_event_sender = task_event_sender.clone();
+            async move {
+                Worker {
+                    factory,
+                    event_sender,
+                    event_receiver,
+                    task_event_sender,
+                    max_concurrent_task_number,
+                    task_store: task_store_file_path.map(TaskStore::new),
+                    retry_interval,
+                    preemption,
+                    schedule,
+                    hook_runner: HookRunner::new(hooks, hook_timeout, allow_task_commands),
+                }
+                .serve()
+                .await;
+            }
+        });
+        (TaskScheduler { factory, event_sender, task_event_sender }, join_handle)
+    }
+}
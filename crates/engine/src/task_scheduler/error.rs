@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use snafu::Snafu;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -7,4 +9,28 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[snafu(display("Task scheduler is closed"))]
     TaskSchedulerClosed,
+
+    #[snafu(display("Error occurs while expanding directory, error: {source}"))]
+    ExpandDirectory { source: crate::Error },
+
+    #[snafu(display("Task can not be downloaded, error: {source}"))]
+    CheckTask { source: crate::Error },
+
+    #[snafu(display("Task group {group_id} does not exist"))]
+    GroupNotFound { group_id: u64 },
+
+    #[snafu(display("Error occurs while reading task store `{}`, error: {source}", file_path.display()))]
+    ReadTaskStore { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Error occurs while parsing task store `{}`, error: {source}", file_path.display()))]
+    ParseTaskStore { file_path: PathBuf, source: serde_json::Error },
+
+    #[snafu(display("Schema {schema} of task store `{}` is not supported", file_path.display()))]
+    UnsupportedTaskStoreSchema { file_path: PathBuf, schema: u32 },
+
+    #[snafu(display("Error occurs while creating directory `{}`, error: {source}", file_path.display()))]
+    CreateTaskStoreDirectory { file_path: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Error occurs while writing task store `{}`, error: {source}", file_path.display()))]
+    WriteTaskStore { file_path: PathBuf, source: std::io::Error },
 }
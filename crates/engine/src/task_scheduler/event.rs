@@ -1,12 +1,40 @@
 use caracal_base::model;
-use tokio::sync::oneshot;
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use crate::{DownloaderStatus, Error};
 
 #[derive(Debug)]
 pub enum Event {
     Shutdown,
     TryStartTask,
     CheckProgress,
-    AddUri { new_task: model::CreateTask, start_immediately: bool, sender: oneshot::Sender<u64> },
+    CheckSchedule,
+    PersistTasks,
+    AddUri {
+        new_task: Box<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<u64>,
+    },
+    AddUris {
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<Vec<u64>>,
+    },
+    AddGroup {
+        name: String,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<(u64, Vec<u64>)>,
+    },
+    AddGroupTasks {
+        group_id: u64,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<Option<Vec<u64>>>,
+    },
+    PauseGroup { group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>> },
+    ResumeGroup { group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>> },
+    RemoveGroup { group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>> },
     RemoveTask { task_id: u64, sender: oneshot::Sender<Option<u64>> },
     PauseTask { task_id: u64, sender: oneshot::Sender<Option<u64>> },
     PauseAllTasks,
@@ -15,12 +43,50 @@ pub enum Event {
     GetAllTasks { sender: oneshot::Sender<Vec<u64>> },
     GetTaskStatus { task_id: u64, sender: oneshot::Sender<Option<model::TaskStatus>> },
     GetAllTaskStatuses { sender: oneshot::Sender<Vec<model::TaskStatus>> },
+    GetGroupStatus { group_id: u64, sender: oneshot::Sender<Option<model::TaskGroupStatus>> },
+    GetAllGroupStatuses { sender: oneshot::Sender<Vec<model::TaskGroupStatus>> },
     GetPendingTasks { sender: oneshot::Sender<Vec<u64>> },
     GetDownloadingTasks { sender: oneshot::Sender<Vec<u64>> },
     GetPausedTasks { sender: oneshot::Sender<Vec<u64>> },
     GetCompletedTasks { sender: oneshot::Sender<Vec<u64>> },
     GetCanceledTasks { sender: oneshot::Sender<Vec<u64>> },
     TaskCompleted { task_id: u64 },
+    TaskJoined {
+        task_id: u64,
+        result: Result<Option<DownloaderStatus>, Error>,
+        seeder: Option<JoinHandle<()>>,
+    },
+    RetryTask { task_id: u64 },
     IncreaseConcurrentNumber { task_id: u64 },
     DecreaseConcurrentNumber { task_id: u64 },
+    SetGlobalSpeedLimit { limit: Option<u64> },
+    SetTaskSpeedLimit { task_id: u64, limit: Option<u64>, sender: oneshot::Sender<Option<u64>> },
+    SetPriority { task_id: u64, priority: model::Priority, sender: oneshot::Sender<Option<u64>> },
+}
+
+impl Event {
+    /// Whether handling this event changes the task table, starting tasks and
+    /// following the schedule mark the table as modified themselves
+    pub const fn modifies_tasks(&self) -> bool {
+        matches!(
+            self,
+            Self::AddUri { .. }
+                | Self::AddUris { .. }
+                | Self::AddGroup { .. }
+                | Self::AddGroupTasks { .. }
+                | Self::PauseGroup { .. }
+                | Self::ResumeGroup { .. }
+                | Self::RemoveGroup { .. }
+                | Self::RemoveTask { .. }
+                | Self::PauseTask { .. }
+                | Self::PauseAllTasks
+                | Self::ResumeTask { .. }
+                | Self::ResumeAllTasks
+                | Self::TaskCompleted { .. }
+                | Self::TaskJoined { .. }
+                | Self::RetryTask { .. }
+                | Self::SetTaskSpeedLimit { .. }
+                | Self::SetPriority { .. }
+        )
+    }
 }
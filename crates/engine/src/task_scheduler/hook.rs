@@ -0,0 +1,179 @@
+use std::{process::Stdio, time::Duration};
+
+use caracal_base::model;
+
+use crate::ext::UriExt;
+
+#[derive(Clone, Debug)]
+pub struct HookRunner {
+    hooks: model::TaskHooks,
+
+    timeout: Duration,
+
+    allow_task_commands: bool,
+
+    client: reqwest::Client,
+}
+
+impl HookRunner {
+    pub fn new(hooks: model::TaskHooks, timeout: Duration, allow_task_commands: bool) -> Self {
+        Self { hooks, timeout, allow_task_commands, client: reqwest::Client::new() }
+    }
+
+    /// Run the hooks of the daemon and the task in background, failures are
+    /// logged only
+    pub fn run(&self, uri: &http::Uri, task_hooks: &model::TaskHooks, status: &model::TaskStatus) {
+        if self.hooks.is_empty() && task_hooks.is_empty() {
+            return;
+        }
+
+        let task_commands = if self.allow_task_commands {
+            task_hooks.commands.as_slice()
+        } else {
+            if !task_hooks.commands.is_empty() {
+                tracing::warn!("Commands of task {} are not allowed, skipped", status.id);
+            }
+            &[]
+        };
+        let envs = environment_variables(uri, status);
+        for command in self.hooks.commands.iter().chain(task_commands) {
+            let _handle =
+                tokio::spawn(run_command(status.id, command.clone(), envs.clone(), self.timeout));
+        }
+        for webhook in self.hooks.webhooks.iter().chain(&task_hooks.webhooks) {
+            let _handle = tokio::spawn(post_webhook(
+                self.client.clone(),
+                webhook.to_string(),
+                status.clone(),
+                self.timeout,
+            ));
+        }
+    }
+}
+
+fn environment_variables(uri: &http::Uri, status: &model::TaskStatus) -> Vec<(String, String)> {
+    let mut envs = vec![
+        ("CARACAL_TASK_ID".to_string(), status.id.to_string()),
+        ("CARACAL_TASK_STATE".to_string(), status.state.to_string()),
+        ("CARACAL_TASK_URI".to_string(), uri.without_userinfo().to_string()),
+        ("CARACAL_FILE_PATH".to_string(), status.file_path.to_string_lossy().to_string()),
+        ("CARACAL_CONTENT_LENGTH".to_string(), status.content_length.to_string()),
+    ];
+    if let Some(ref error) = status.last_error {
+        envs.push(("CARACAL_TASK_ERROR".to_string(), error.clone()));
+    }
+    envs
+}
+
+async fn run_command(
+    task_id: u64,
+    command: String,
+    envs: Vec<(String, String)>,
+    timeout: Duration,
+) {
+    tracing::info!("Running hook of task {task_id}, command: {command}");
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .envs(envs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::warn!(
+                "Failed to run hook of task {task_id}, command: {command}, error: {err}"
+            );
+            return;
+        }
+    };
+
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            tracing::info!("Hook of task {task_id} exited successfully, command: {command}");
+        }
+        Ok(Ok(output)) => tracing::warn!(
+            "Hook of task {task_id} failed with {status}, command: {command}, stderr: {stderr}",
+            status = output.status,
+            stderr = String::from_utf8_lossy(&output.stderr).trim_end()
+        ),
+        Ok(Err(err)) => {
+            tracing::warn!(
+                "Failed to wait for hook of task {task_id}, command: {command}, error: {err}"
+            );
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Hook of task {task_id} is killed after {timeout:?}, command: {command}"
+            );
+        }
+    }
+}
+
+async fn post_webhook(
+    client: reqwest::Client,
+    uri: String,
+    status: model::TaskStatus,
+    timeout: Duration,
+) {
+    let task_id = status.id;
+    tracing::info!("Posting status of task {task_id} to webhook {uri}");
+    match client.post(&uri).timeout(timeout).json(&status).send().await {
+        Ok(response) if response.status().is_success() => {
+            tracing::info!("Posted status of task {task_id} to webhook {uri}");
+        }
+        Ok(response) => tracing::warn!(
+            "Webhook {uri} responded {status} to status of task {task_id}",
+            status = response.status()
+        ),
+        Err(err) => {
+            tracing::warn!(
+                "Failed to post status of task {task_id} to webhook {uri}, error: {err}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use caracal_base::model;
+    use time::OffsetDateTime;
+
+    use super::run_command;
+
+    #[tokio::test]
+    async fn test_run_command() {
+        let dir = std::env::temp_dir().join(format!("caracal-hook-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let output = dir.join("output");
+        let status = model::TaskStatus {
+            id: 7,
+            file_path: dir.join("data.bin"),
+            content_length: 1024,
+            chunks: Vec::new(),
+            concurrent_number: 0,
+            state: model::TaskState::Completed,
+            priority: model::Priority::Normal,
+            creation_timestamp: OffsetDateTime::now_utc(),
+            attempts: 1,
+            last_error: None,
+            connection_tuning_log: Vec::new(),
+            seeding: false,
+        };
+        let envs = super::environment_variables(&"/tmp/data.bin".parse().unwrap(), &status);
+        let command = format!(
+            "echo \"$CARACAL_TASK_ID $CARACAL_TASK_STATE $CARACAL_CONTENT_LENGTH\" > {}",
+            output.display()
+        );
+        run_command(status.id, command, envs, Duration::from_secs(5)).await;
+
+        let contents = tokio::fs::read_to_string(&output).await.unwrap();
+        assert_eq!(contents.trim_end(), "7 Completed 1024");
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
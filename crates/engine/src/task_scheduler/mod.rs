@@ -1,21 +1,31 @@
+mod builder;
 mod error;
 mod event;
+mod hook;
+mod store;
 mod worker;
 
 use caracal_base::model;
-use snafu::OptionExt;
+use snafu::{OptionExt, ResultExt};
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot},
     task::JoinHandle,
 };
 
-pub use self::error::{Error, Result};
-use self::{event::Event, worker::Worker};
+use self::event::Event;
+pub use self::{
+    builder::Builder,
+    error::{Error, Result},
+};
 use crate::downloader::DownloaderFactory;
 
 #[derive(Clone, Debug)]
 pub struct TaskScheduler {
+    factory: DownloaderFactory,
+
     event_sender: mpsc::UnboundedSender<Event>,
+
+    task_event_sender: broadcast::Sender<model::TaskEvent>,
 }
 
 impl TaskScheduler {
@@ -24,16 +34,16 @@ impl TaskScheduler {
         factory: DownloaderFactory,
         max_concurrent_task_number: usize,
     ) -> (Self, JoinHandle<()>) {
-        let (event_sender, event_receiver) = mpsc::unbounded_channel();
-        let join_handle = tokio::spawn({
-            let event_sender = event_sender.clone();
-            async move {
-                Worker { factory, event_sender, event_receiver, max_concurrent_task_number }
-                    .serve()
-                    .await;
-            }
-        });
-        (Self { event_sender }, join_handle)
+        Self::builder(factory).max_concurrent_task_number(max_concurrent_task_number).build()
+    }
+
+    #[must_use]
+    pub fn builder(factory: DownloaderFactory) -> Builder { Builder::new(factory) }
+
+    /// Subscribe to lifecycle and progress events of tasks
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<model::TaskEvent> {
+        self.task_event_sender.subscribe()
     }
 
     /// # Errors
@@ -43,6 +53,7 @@ impl TaskScheduler {
         start_immediately: bool,
     ) -> Result<u64> {
         let (sender, receiver) = oneshot::channel();
+        let new_task = Box::new(new_task);
         if self.event_sender.send(Event::AddUri { new_task, start_immediately, sender }).is_err() {
             return Err(Error::TaskSchedulerClosed);
         }
@@ -50,6 +61,131 @@ impl TaskScheduler {
         receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
     }
 
+    /// Add all of the tasks at once, the IDs of the tasks are returned in order
+    ///
+    /// # Errors
+    pub async fn add_uris(
+        &self,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+    ) -> Result<Vec<u64>> {
+        let (sender, receiver) = oneshot::channel();
+        let event = Event::AddUris { new_tasks, start_immediately, sender };
+        if self.event_sender.send(event).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// Check whether `new_task` can be downloaded before it is added
+    ///
+    /// # Errors
+    pub async fn check_task(&self, new_task: &model::CreateTask) -> Result<()> {
+        self.factory.check_task(new_task).await.context(error::CheckTaskSnafu)
+    }
+
+    /// Add the files under the directory or the prefix which `new_task` points
+    /// to as a group of tasks, the tasks join the group `group_id` if it is
+    /// provided. The ID of the group and the IDs of the tasks are returned,
+    /// `None` is returned if `new_task` does not point to a directory.
+    ///
+    /// # Errors
+    pub async fn add_directory(
+        &self,
+        new_task: model::CreateTask,
+        filter: &model::PathFilter,
+        group_id: Option<u64>,
+        start_immediately: bool,
+    ) -> Result<Option<(u64, Vec<u64>)>> {
+        let Some(new_tasks) = self
+            .factory
+            .expand_directory(&new_task, filter)
+            .await
+            .context(error::ExpandDirectorySnafu)?
+        else {
+            return Ok(None);
+        };
+
+        if let Some(group_id) = group_id {
+            let task_ids = self
+                .add_group_tasks(group_id, new_tasks, start_immediately)
+                .await?
+                .context(error::GroupNotFoundSnafu { group_id })?;
+            Ok(Some((group_id, task_ids)))
+        } else {
+            self.add_group(new_task.uri.to_string(), new_tasks, start_immediately).await.map(Some)
+        }
+    }
+
+    /// Add tasks as a new group, the ID of the group and the IDs of the tasks
+    /// are returned
+    ///
+    /// # Errors
+    pub async fn add_group(
+        &self,
+        name: String,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+    ) -> Result<(u64, Vec<u64>)> {
+        let (sender, receiver) = oneshot::channel();
+        let event = Event::AddGroup { name, new_tasks, start_immediately, sender };
+        if self.event_sender.send(event).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// Add tasks to an existing group, `None` is returned if the group does not
+    /// exist
+    ///
+    /// # Errors
+    pub async fn add_group_tasks(
+        &self,
+        group_id: u64,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+    ) -> Result<Option<Vec<u64>>> {
+        let (sender, receiver) = oneshot::channel();
+        let event = Event::AddGroupTasks { group_id, new_tasks, start_immediately, sender };
+        if self.event_sender.send(event).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// Pause the pending and downloading tasks of a group, the IDs of the
+    /// paused tasks are returned
+    ///
+    /// # Errors
+    pub async fn pause_group(&self, group_id: u64) -> Result<Option<Vec<u64>>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::PauseGroup { group_id, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// # Errors
+    pub async fn resume_group(&self, group_id: u64) -> Result<Option<Vec<u64>>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::ResumeGroup { group_id, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// Cancel the unfinished tasks of a group and remove the group, the IDs of
+    /// the canceled tasks are returned
+    ///
+    /// # Errors
+    pub async fn remove_group(&self, group_id: u64) -> Result<Option<Vec<u64>>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::RemoveGroup { group_id, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
     /// # Errors
     pub async fn pause_task(&self, task_id: u64) -> Result<Option<u64>> {
         let (sender, receiver) = oneshot::channel();
@@ -102,6 +238,24 @@ impl TaskScheduler {
         receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
     }
 
+    /// # Errors
+    pub async fn get_group_status(&self, group_id: u64) -> Result<Option<model::TaskGroupStatus>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::GetGroupStatus { group_id, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// # Errors
+    pub async fn get_all_group_statuses(&self) -> Result<Vec<model::TaskGroupStatus>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::GetAllGroupStatuses { sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
     /// # Errors
     pub async fn get_all_tasks(&self) -> Result<Vec<u64>> {
         let (sender, receiver) = oneshot::channel();
@@ -188,4 +342,230 @@ impl TaskScheduler {
         }
         Ok(())
     }
+
+    /// Set the maximum transfer rate of all tasks in bytes per second, `None`
+    /// for unlimited
+    ///
+    /// # Errors
+    pub fn set_global_speed_limit(&self, limit: Option<u64>) -> Result<()> {
+        if self.event_sender.send(Event::SetGlobalSpeedLimit { limit }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        Ok(())
+    }
+
+    /// Set the maximum transfer rate of a task in bytes per second, `None` for
+    /// unlimited
+    ///
+    /// # Errors
+    pub async fn set_task_speed_limit(
+        &self,
+        task_id: u64,
+        limit: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::SetTaskSpeedLimit { task_id, limit, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+
+    /// Change the priority of a task which is not finished yet, a pending task
+    /// is reordered in the queue immediately
+    ///
+    /// # Errors
+    pub async fn set_priority(
+        &self,
+        task_id: u64,
+        priority: model::Priority,
+    ) -> Result<Option<u64>> {
+        let (sender, receiver) = oneshot::channel();
+        if self.event_sender.send(Event::SetPriority { task_id, priority, sender }).is_err() {
+            return Err(Error::TaskSchedulerClosed);
+        }
+        receiver.await.ok().context(error::TaskSchedulerClosedSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use caracal_base::{model, utils::RetryInterval};
+    use time::OffsetDateTime;
+
+    use crate::{DownloaderFactory, TaskScheduler, task_scheduler::store::TaskStore};
+
+    fn new_task(path: &str) -> model::CreateTask { model::CreateTask::new(path.parse().unwrap()) }
+
+    #[tokio::test]
+    async fn test_task_group() {
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let (task_scheduler, handle) = TaskScheduler::new(factory, 1);
+
+        let (group_id, task_ids) = task_scheduler
+            .add_group(String::new(), vec![new_task("/tmp/a.iso"), new_task("/tmp/b.iso")], false)
+            .await
+            .unwrap();
+        assert_eq!(task_ids.len(), 2);
+
+        let group_status = task_scheduler.get_group_status(group_id).await.unwrap().unwrap();
+        assert_eq!(group_status.name, format!("Group {group_id}"));
+        assert_eq!(group_status.state, model::TaskState::Paused);
+
+        let added =
+            task_scheduler.add_group_tasks(group_id, vec![new_task("/tmp/c.iso")], false).await;
+        assert_eq!(added.unwrap().unwrap().len(), 1);
+        assert!(
+            task_scheduler
+                .add_group_tasks(group_id + 1, Vec::new(), false)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let group_statuses = task_scheduler.get_all_group_statuses().await.unwrap();
+        assert_eq!(group_statuses.len(), 1);
+        assert_eq!(group_statuses[0].tasks.len(), 3);
+
+        let removed = task_scheduler.remove_group(group_id).await.unwrap().unwrap();
+        assert_eq!(removed.len(), 3);
+        for task_id in removed {
+            let task_status = task_scheduler.get_task_status(task_id).await.unwrap().unwrap();
+            assert_eq!(task_status.state, model::TaskState::Canceled);
+        }
+        assert!(task_scheduler.get_group_status(group_id).await.unwrap().is_none());
+        assert!(task_scheduler.pause_group(group_id).await.unwrap().is_none());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_persist_tasks() {
+        let file_path = std::env::temp_dir()
+            .join(format!("caracal-test-persist-tasks-{}", std::process::id()))
+            .join("tasks.json");
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let (task_scheduler, handle) =
+            TaskScheduler::builder(factory).task_store_file_path(Some(&file_path)).build();
+        let task_store = TaskStore::new(&file_path);
+
+        let new_tasks = (0..300).map(|i| new_task(&format!("/tmp/{i}.iso"))).collect();
+        let _unused = task_scheduler.add_group(String::new(), new_tasks, false).await.unwrap();
+        // the changes are written together once they settle
+        assert!(task_store.load().await.unwrap().is_none());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(task_store.load().await.unwrap().unwrap().tasks.len(), 300);
+
+        let _unused = task_scheduler.add_uri(new_task("/tmp/300.iso"), false).await.unwrap();
+        task_scheduler.shutdown().unwrap();
+        handle.await.unwrap();
+        assert_eq!(task_store.load().await.unwrap().unwrap().tasks.len(), 301);
+
+        drop(tokio::fs::remove_dir_all(file_path.parent().unwrap()).await);
+    }
+
+    #[tokio::test]
+    async fn test_pause_group_waiting_for_retry() {
+        // nothing listens on the port, the task fails and waits for a retry
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        drop(listener);
+
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let (task_scheduler, handle) = TaskScheduler::builder(factory)
+            .max_concurrent_task_number(1)
+            .retry_interval(RetryInterval::new(3, Duration::from_secs(60)))
+            .build();
+        let (group_id, task_ids) =
+            task_scheduler.add_group(String::new(), vec![new_task(&uri)], true).await.unwrap();
+        let task_id = task_ids[0];
+        while task_scheduler.get_task_status(task_id).await.unwrap().unwrap().last_error.is_none() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let paused = task_scheduler.pause_group(group_id).await.unwrap().unwrap();
+        assert_eq!(paused, task_ids);
+        let task_status = task_scheduler.get_task_status(task_id).await.unwrap().unwrap();
+        assert_eq!(task_status.state, model::TaskState::Paused);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_set_priority() {
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        // no task is started so that all tasks stay in the queue
+        let (task_scheduler, handle) = TaskScheduler::new(factory, 0);
+
+        let (group_id, task_ids) = task_scheduler
+            .add_group(String::new(), vec![new_task("/tmp/a.iso")], true)
+            .await
+            .unwrap();
+        let task_id = task_ids[0];
+        let updated = task_scheduler.set_priority(task_id, model::Priority::Highest).await;
+        assert_eq!(updated.unwrap(), Some(task_id));
+
+        let task_status = task_scheduler.get_task_status(task_id).await.unwrap().unwrap();
+        assert_eq!(task_status.priority, model::Priority::Highest);
+        assert_eq!(task_status.state, model::TaskState::Pending);
+
+        let _unused = task_scheduler.remove_group(group_id).await.unwrap();
+        let updated = task_scheduler.set_priority(task_id, model::Priority::Low).await;
+        assert_eq!(updated.unwrap(), None);
+        let updated = task_scheduler.set_priority(task_id + 1, model::Priority::Low).await;
+        assert_eq!(updated.unwrap(), None);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_not_before() {
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let (task_scheduler, handle) = TaskScheduler::new(factory, 1);
+
+        let new_task = model::CreateTask {
+            not_before: Some(OffsetDateTime::now_utc() + time::Duration::days(1)),
+            ..new_task("/tmp/a.iso")
+        };
+        let task_id = task_scheduler.add_uri(new_task, true).await.unwrap();
+        let task_status = task_scheduler.get_task_status(task_id).await.unwrap().unwrap();
+        assert_eq!(task_status.state, model::TaskState::Pending);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_start_task_once_due() {
+        let directory =
+            std::env::temp_dir().join(format!("caracal-test-not-before-{}", std::process::id()));
+        tokio::fs::create_dir_all(&directory).await.unwrap();
+        let source_path = directory.join("source.iso");
+        tokio::fs::write(&source_path, "debian").await.unwrap();
+
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        let (task_scheduler, handle) = TaskScheduler::new(factory, 1);
+        let new_task = model::CreateTask {
+            output_directory: Some(directory.join("output")),
+            not_before: Some(OffsetDateTime::now_utc() + Duration::from_millis(500)),
+            ..new_task(&source_path.display().to_string())
+        };
+        let task_id = task_scheduler.add_uri(new_task, true).await.unwrap();
+        let task_status = task_scheduler.get_task_status(task_id).await.unwrap().unwrap();
+        assert_eq!(task_status.state, model::TaskState::Pending);
+
+        // the task is started by the timer of the schedule, no other event is sent
+        let mut state = task_status.state;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            state = task_scheduler.get_task_status(task_id).await.unwrap().unwrap().state;
+            if state == model::TaskState::Completed {
+                break;
+            }
+        }
+        assert_eq!(state, model::TaskState::Completed);
+
+        handle.abort();
+        drop(tokio::fs::remove_dir_all(&directory).await);
+    }
 }
@@ -0,0 +1,244 @@
+use std::{
+    fs::Permissions,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use caracal_base::model;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, ensure};
+use tokio::io::AsyncWriteExt;
+
+use crate::task_scheduler::{error, error::Result};
+
+const SCHEMA_VERSION: u32 = 1;
+
+// tasks carry credentials, e.g. `Authorization` headers, basic auth and proxies
+const FILE_MODE: u32 = 0o600;
+
+#[derive(Clone, Debug)]
+pub struct TaskStore {
+    file_path: PathBuf,
+}
+
+impl TaskStore {
+    pub fn new<P>(file_path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self { file_path: file_path.as_ref().to_path_buf() }
+    }
+
+    pub fn file_path(&self) -> &Path { &self.file_path }
+
+    pub async fn load(&self) -> Result<Option<Snapshot>> {
+        if !tokio::fs::try_exists(&self.file_path).await.unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let contents = tokio::fs::read(&self.file_path)
+            .await
+            .with_context(|_| error::ReadTaskStoreSnafu { file_path: self.file_path.clone() })?;
+        // check the schema first, stores of other schemas may not be parsed as a
+        // snapshot
+        let SchemaVersion { schema } = serde_json::from_slice(&contents)
+            .with_context(|_| error::ParseTaskStoreSnafu { file_path: self.file_path.clone() })?;
+        ensure!(
+            schema == SCHEMA_VERSION,
+            error::UnsupportedTaskStoreSchemaSnafu { file_path: self.file_path.clone(), schema }
+        );
+        serde_json::from_slice(&contents)
+            .map(Some)
+            .with_context(|_| error::ParseTaskStoreSnafu { file_path: self.file_path.clone() })
+    }
+
+    pub async fn save(&self, snapshot: &Snapshot) -> Result<()> {
+        if let Some(parent) = self.file_path.parent() {
+            tokio::fs::create_dir_all(parent).await.with_context(|_| {
+                error::CreateTaskStoreDirectorySnafu { file_path: parent.to_path_buf() }
+            })?;
+        }
+
+        // write to a temporary file first, a crash while writing never corrupts the
+        // store
+        let tmp_file_path = self.file_path.with_extension("tmp");
+        let contents = serde_json::to_vec(snapshot).expect("Snapshot is serializable; qed");
+        async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(FILE_MODE)
+                .open(&tmp_file_path)
+                .await?;
+            // the mode is only applied to new files
+            file.set_permissions(Permissions::from_mode(FILE_MODE)).await?;
+            file.write_all(&contents).await?;
+            file.sync_all().await
+        }
+        .await
+        .with_context(|_| error::WriteTaskStoreSnafu { file_path: tmp_file_path.clone() })?;
+        tokio::fs::rename(&tmp_file_path, &self.file_path)
+            .await
+            .with_context(|_| error::WriteTaskStoreSnafu { file_path: self.file_path.clone() })
+    }
+}
+
+#[derive(Deserialize)]
+struct SchemaVersion {
+    schema: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub schema: u32,
+
+    pub next_task_id: u64,
+
+    pub tasks: Vec<TaskRecord>,
+
+    #[serde(default)]
+    pub next_group_id: u64,
+
+    #[serde(default)]
+    pub groups: Vec<GroupRecord>,
+}
+
+impl Snapshot {
+    pub const fn new(
+        next_task_id: u64,
+        tasks: Vec<TaskRecord>,
+        next_group_id: u64,
+        groups: Vec<GroupRecord>,
+    ) -> Self {
+        Self { schema: SCHEMA_VERSION, next_task_id, tasks, next_group_id, groups }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TaskRecord {
+    pub id: u64,
+
+    pub state: model::TaskState,
+
+    pub task: model::CreateTask,
+
+    pub file_path: PathBuf,
+
+    pub content_length: u64,
+
+    pub chunks: Vec<model::ProgressChunk>,
+
+    #[serde(default)]
+    pub attempts: usize,
+
+    #[serde(default)]
+    pub last_error: Option<String>,
+
+    #[serde(default)]
+    pub speed_limit: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GroupRecord {
+    pub id: u64,
+
+    pub name: String,
+
+    pub task_ids: Vec<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, time::Duration};
+
+    use caracal_base::model;
+
+    use super::{GroupRecord, Snapshot, TaskRecord, TaskStore};
+    use crate::task_scheduler::Error;
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let file_path = std::env::temp_dir()
+            .join(format!("caracal-test-task-store-{}", std::process::id()))
+            .join("tasks.json");
+        let task_store = TaskStore::new(&file_path);
+        assert!(task_store.load().await.unwrap().is_none());
+
+        let task = model::CreateTask {
+            mirrors: vec![http::Uri::from_static("sftp://mirror/file.tar.gz")],
+            output_directory: Some("/tmp".into()),
+            concurrent_number: Some(3),
+            connection_timeout: Some(Duration::from_secs(10)),
+            priority: model::Priority::High,
+            checksum: Some("md5=d41d8cd98f00b204e9800998ecf8427e".parse().unwrap()),
+            headers: vec![model::HttpHeader::new("Authorization", "Bearer token")],
+            content_length: Some(1024),
+            ..model::CreateTask::new(http::Uri::from_static("https://www.example.com/file.tar.gz"))
+        };
+        let snapshot = Snapshot::new(
+            8,
+            vec![TaskRecord {
+                id: 7,
+                state: model::TaskState::Paused,
+                task: task.clone(),
+                file_path: "/tmp/file.tar.gz".into(),
+                content_length: 1024,
+                chunks: vec![model::ProgressChunk {
+                    start: 0,
+                    end: 1023,
+                    received: 512,
+                    is_completed: false,
+                }],
+                attempts: 2,
+                last_error: Some("Connection timed out".to_string()),
+                speed_limit: Some(1024),
+            }],
+            1,
+            vec![GroupRecord { id: 0, name: "sftp://mirror/".to_string(), task_ids: vec![7] }],
+        );
+        task_store.save(&snapshot).await.unwrap();
+        let metadata = tokio::fs::metadata(&file_path).await.unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+        let loaded = task_store.load().await.unwrap().unwrap();
+        assert_eq!(loaded.next_task_id, 8);
+        assert_eq!(loaded.tasks.len(), 1);
+        let record = &loaded.tasks[0];
+        assert_eq!(record.id, 7);
+        assert_eq!(record.state, model::TaskState::Paused);
+        assert_eq!(record.task.uri, task.uri);
+        assert_eq!(record.task.mirrors, task.mirrors);
+        assert_eq!(record.task.priority, task.priority);
+        assert_eq!(record.task.concurrent_number, task.concurrent_number);
+        assert_eq!(record.task.creation_timestamp, task.creation_timestamp);
+        assert_eq!(record.task.checksum, task.checksum);
+        assert_eq!(record.task.headers, task.headers);
+        assert_eq!(record.chunks[0].received, 512);
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.last_error.as_deref(), Some("Connection timed out"));
+        assert_eq!(record.speed_limit, Some(1024));
+        assert_eq!(loaded.next_group_id, 1);
+        assert_eq!(loaded.groups[0].name, "sftp://mirror/");
+        assert_eq!(loaded.groups[0].task_ids, vec![7]);
+
+        drop(std::fs::remove_dir_all(file_path.parent().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_load_unsupported_schema() {
+        let file_path = std::env::temp_dir()
+            .join(format!("caracal-test-task-store-schema-{}", std::process::id()))
+            .join("tasks.json");
+        let task_store = TaskStore::new(&file_path);
+        tokio::fs::create_dir_all(file_path.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&file_path, r#"{"schema":2,"entries":[]}"#).await.unwrap();
+
+        assert!(matches!(
+            task_store.load().await,
+            Err(Error::UnsupportedTaskStoreSchema { schema: 2, .. })
+        ));
+
+        drop(std::fs::remove_dir_all(file_path.parent().unwrap()));
+    }
+}
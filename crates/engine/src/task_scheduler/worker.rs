@@ -4,14 +4,31 @@ use std::{
     time::Duration,
 };
 
-use caracal_base::model;
+use caracal_base::{model, utils::RetryInterval};
 use futures::{FutureExt, future};
-use tokio::sync::{mpsc, oneshot};
+use tokio::{
+    sync::{broadcast, mpsc, oneshot},
+    task::JoinHandle,
+};
 
 use crate::{
-    Downloader, DownloaderStatus, downloader::DownloaderFactory, ext::UriExt, task_scheduler::Event,
+    Downloader, DownloaderStatus, Error,
+    downloader::DownloaderFactory,
+    ext::UriExt,
+    task_scheduler::{
+        Event,
+        hook::HookRunner,
+        store::{GroupRecord, Snapshot, TaskRecord, TaskStore},
+    },
 };
 
+// a task whose remote content keeps changing is failed after this number of
+// restarts
+const MAX_REMOTE_CHANGE_RESTARTS: usize = 3;
+
+// changes made within this delay are written to the task store at once
+const PERSIST_DELAY: Duration = Duration::from_millis(500);
+
 #[derive(Debug)]
 pub struct Worker {
     pub factory: DownloaderFactory,
@@ -20,48 +37,67 @@ pub struct Worker {
 
     pub event_receiver: mpsc::UnboundedReceiver<Event>,
 
+    pub task_event_sender: broadcast::Sender<model::TaskEvent>,
+
     pub max_concurrent_task_number: usize,
+
+    pub task_store: Option<TaskStore>,
+
+    pub retry_interval: RetryInterval,
+
+    pub preemption: bool,
+
+    pub schedule: model::DownloadSchedule,
+
+    pub hook_runner: HookRunner,
 }
 
 impl Worker {
-    #[allow(clippy::cognitive_complexity)]
+    #[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
     pub async fn serve(self) {
         tracing::info!("Starting Task scheduler");
-        let Self { factory, event_sender, mut event_receiver, max_concurrent_task_number } = self;
+        let Self {
+            factory,
+            event_sender,
+            mut event_receiver,
+            task_event_sender,
+            max_concurrent_task_number,
+            task_store,
+            retry_interval,
+            preemption,
+            schedule,
+            hook_runner,
+        } = self;
 
-        let mut event_handler = EventHandler {
+        let mut event_handler = EventHandler::new(
             factory,
-            event_sender: event_sender.clone(),
+            event_sender.clone(),
+            task_event_sender,
             max_concurrent_task_number,
-            next_task_id: 0,
-            tasks: HashMap::new(),
-            pending_tasks: BinaryHeap::new(),
-            downloaders: HashMap::new(),
-            completed_tasks: HashSet::new(),
-            failed_tasks: HashSet::new(),
-            paused_tasks: HashSet::new(),
-            canceled_tasks: HashSet::new(),
-            download_progresses: HashMap::new(),
-        };
+            task_store,
+            retry_interval,
+            preemption,
+            schedule,
+            hook_runner,
+        );
+        event_handler.restore_tasks().await;
+        drop(event_sender.send(Event::CheckSchedule));
 
-        let timer = tokio::spawn({
-            async move {
-                let mut interval = tokio::time::interval(Duration::from_millis(200));
-                loop {
-                    let _ = interval.tick().await;
-                    if event_sender.send(Event::CheckProgress).is_err() {
-                        break;
-                    }
-                }
-            }
-        });
+        let timer = spawn_progress_timer(event_sender);
 
         tracing::info!("Started Task scheduler");
         while let Some(event) = event_receiver.recv().await {
+            let modifies_tasks = event.modifies_tasks();
             match event {
                 Event::CheckProgress => {
                     event_handler.check_progress().await;
                 }
+                Event::CheckSchedule => {
+                    event_handler.check_schedule(time::OffsetDateTime::now_utc()).await;
+                }
+                Event::PersistTasks => {
+                    event_handler.persist_tasks();
+                }
                 Event::Shutdown => {
                     tracing::info!("Stopping Task scheduler");
                     event_handler.on_shutdown().await;
@@ -71,7 +107,25 @@ impl Worker {
                     event_handler.try_start_task().await;
                 }
                 Event::AddUri { new_task, start_immediately, sender } => {
-                    event_handler.add_uri(new_task, start_immediately, sender);
+                    event_handler.add_uri(*new_task, start_immediately, sender);
+                }
+                Event::AddUris { new_tasks, start_immediately, sender } => {
+                    event_handler.add_uris(new_tasks, start_immediately, sender);
+                }
+                Event::AddGroup { name, new_tasks, start_immediately, sender } => {
+                    event_handler.add_group(name, new_tasks, start_immediately, sender);
+                }
+                Event::AddGroupTasks { group_id, new_tasks, start_immediately, sender } => {
+                    event_handler.add_group_tasks(group_id, new_tasks, start_immediately, sender);
+                }
+                Event::PauseGroup { group_id, sender } => {
+                    event_handler.pause_group(group_id, sender).await;
+                }
+                Event::ResumeGroup { group_id, sender } => {
+                    event_handler.resume_group(group_id, sender);
+                }
+                Event::RemoveGroup { group_id, sender } => {
+                    event_handler.remove_group(group_id, sender).await;
                 }
                 Event::RemoveTask { task_id, sender } => {
                     event_handler.remove_task(task_id, sender).await;
@@ -112,8 +166,20 @@ impl Worker {
                 Event::GetAllTaskStatuses { sender } => {
                     event_handler.get_all_task_statuses(sender);
                 }
+                Event::GetGroupStatus { group_id, sender } => {
+                    event_handler.get_group_status(group_id, sender);
+                }
+                Event::GetAllGroupStatuses { sender } => {
+                    event_handler.get_all_group_statuses(sender);
+                }
                 Event::TaskCompleted { task_id } => {
-                    event_handler.on_task_completed(task_id).await;
+                    event_handler.on_task_completed(task_id);
+                }
+                Event::TaskJoined { task_id, result, seeder } => {
+                    event_handler.on_task_joined(task_id, result, seeder);
+                }
+                Event::RetryTask { task_id } => {
+                    event_handler.retry_task(task_id);
                 }
                 Event::IncreaseConcurrentNumber { task_id } => {
                     event_handler.increase_concurrent_number(task_id);
@@ -121,6 +187,19 @@ impl Worker {
                 Event::DecreaseConcurrentNumber { task_id } => {
                     event_handler.decrease_concurrent_number(task_id);
                 }
+                Event::SetGlobalSpeedLimit { limit } => {
+                    event_handler.set_global_speed_limit(limit);
+                }
+                Event::SetTaskSpeedLimit { task_id, limit, sender } => {
+                    event_handler.set_task_speed_limit(task_id, limit, sender);
+                }
+                Event::SetPriority { task_id, priority, sender } => {
+                    event_handler.set_priority(task_id, priority, sender);
+                }
+            }
+
+            if modifies_tasks {
+                event_handler.mark_tasks_modified();
             }
         }
 
@@ -142,39 +221,158 @@ struct PendingTask {
     pub task_id: u64,
 }
 
+fn spawn_progress_timer(event_sender: mpsc::UnboundedSender<Event>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(200));
+        loop {
+            let _ = interval.tick().await;
+            if event_sender.send(Event::CheckProgress).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[derive(Clone, Debug)]
+struct TaskGroup {
+    name: String,
+
+    task_ids: Vec<u64>,
+}
+
+async fn stop_downloader(task_id: u64, mut downloader: Downloader) {
+    tracing::info!("Stopping task {task_id}");
+    if let Err(err) = downloader.pause().await {
+        tracing::error!("{err}");
+    }
+    if let Err(err) = downloader.join().await {
+        tracing::error!("{err}");
+    }
+    tracing::info!("Stopped task {task_id}");
+}
+
 struct EventHandler {
     factory: DownloaderFactory,
     event_sender: mpsc::UnboundedSender<Event>,
+    task_event_sender: broadcast::Sender<model::TaskEvent>,
     max_concurrent_task_number: usize,
+    preemption: bool,
+    schedule: model::DownloadSchedule,
+    is_window_open: bool,
+    schedule_timer: Option<(time::OffsetDateTime, JoinHandle<()>)>,
+    scheduled_pauses: HashSet<u64>,
+    hook_runner: HookRunner,
     next_task_id: u64,
     tasks: HashMap<u64, model::CreateTask>,
     pending_tasks: BinaryHeap<PendingTask>,
     downloaders: HashMap<u64, Downloader>,
+    joining_tasks: HashSet<u64>,
+    seeders: HashMap<u64, JoinHandle<()>>,
     completed_tasks: HashSet<u64>,
     failed_tasks: HashSet<u64>,
     paused_tasks: HashSet<u64>,
     canceled_tasks: HashSet<u64>,
     download_progresses: HashMap<u64, DownloaderStatus>,
+    task_store: Option<TaskStore>,
+    has_unsaved_changes: bool,
+    persist_timer: Option<JoinHandle<()>>,
+    persisting: Option<JoinHandle<()>>,
+    retry_interval: RetryInterval,
+    retry_intervals: HashMap<u64, RetryInterval>,
+    remote_change_restarts: HashMap<u64, usize>,
+    attempts: HashMap<u64, usize>,
+    last_errors: HashMap<u64, String>,
+    speed_limits: HashMap<u64, u64>,
+    next_group_id: u64,
+    groups: HashMap<u64, TaskGroup>,
 }
 
 impl EventHandler {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        factory: DownloaderFactory,
+        event_sender: mpsc::UnboundedSender<Event>,
+        task_event_sender: broadcast::Sender<model::TaskEvent>,
+        max_concurrent_task_number: usize,
+        task_store: Option<TaskStore>,
+        retry_interval: RetryInterval,
+        preemption: bool,
+        schedule: model::DownloadSchedule,
+        hook_runner: HookRunner,
+    ) -> Self {
+        Self {
+            factory,
+            event_sender,
+            task_event_sender,
+            max_concurrent_task_number,
+            preemption,
+            schedule,
+            is_window_open: true,
+            schedule_timer: None,
+            scheduled_pauses: HashSet::new(),
+            hook_runner,
+            next_task_id: 0,
+            tasks: HashMap::new(),
+            pending_tasks: BinaryHeap::new(),
+            downloaders: HashMap::new(),
+            joining_tasks: HashSet::new(),
+            seeders: HashMap::new(),
+            completed_tasks: HashSet::new(),
+            failed_tasks: HashSet::new(),
+            paused_tasks: HashSet::new(),
+            canceled_tasks: HashSet::new(),
+            download_progresses: HashMap::new(),
+            task_store,
+            has_unsaved_changes: false,
+            persist_timer: None,
+            persisting: None,
+            retry_interval,
+            retry_intervals: HashMap::new(),
+            remote_change_restarts: HashMap::new(),
+            attempts: HashMap::new(),
+            last_errors: HashMap::new(),
+            speed_limits: HashMap::new(),
+            next_group_id: 0,
+            groups: HashMap::new(),
+        }
+    }
+
     async fn check_progress(&mut self) {
         let futs = self.downloaders.iter().map(|(&task_id, downloader)| {
-            async move { (task_id, downloader.is_completed(), downloader.scrape_status().await) }.boxed()
+            async move {
+                let status = downloader.scrape_status().await;
+                (task_id, downloader.is_completed(), downloader.is_finished(), status)
+            }
+            .boxed()
         });
         let maybe_progresses = future::join_all(futs).await;
-        for (task_id, is_completed, maybe_progress) in maybe_progresses {
+        for (task_id, is_completed, is_finished, maybe_progress) in maybe_progresses {
             if let Some(progress) = maybe_progress {
+                self.publish(model::TaskEvent::Progress {
+                    task_id,
+                    received_bytes: progress.chunks().iter().map(|chunk| chunk.received).sum(),
+                    total_length: progress.content_length(),
+                });
                 drop(self.download_progresses.insert(task_id, progress));
             }
-            if is_completed {
+            if is_completed || is_finished {
                 drop(self.event_sender.send(Event::TaskCompleted { task_id }));
             }
         }
+        self.seeders.retain(|_, seeder| !seeder.is_finished());
     }
 
     async fn on_shutdown(mut self) {
         self.pending_tasks.clear();
+        if let Some((_, schedule_timer)) = self.schedule_timer.take() {
+            schedule_timer.abort();
+        }
+        if let Some(persist_timer) = self.persist_timer.take() {
+            persist_timer.abort();
+        }
+        for (_, seeder) in self.seeders.drain() {
+            seeder.abort();
+        }
 
         // stop all downloading tasks
         let futs = self.downloaders.drain().map(|(task_id, mut downloader)| {
@@ -191,49 +389,416 @@ impl EventHandler {
             .boxed()
         });
         let _results = future::join_all(futs).await;
+
+        // tasks which were downloading are recorded as pending, they are resumed on
+        // next start
+        if let Some(persisting) = self.persisting.take() {
+            drop(persisting.await);
+        }
+        if let Some(task_store) = self.task_store.as_ref()
+            && let Err(err) = task_store.save(&self.snapshot()).await
+        {
+            tracing::warn!("Failed to persist tasks, error: {err}");
+        }
+    }
+
+    async fn restore_tasks(&mut self) {
+        let Some(task_store) = self.task_store.as_ref() else {
+            return;
+        };
+
+        let Snapshot { next_task_id, tasks, next_group_id, groups, .. } = match task_store
+            .load()
+            .await
+        {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return,
+            Err(err) => {
+                // the store may be written by a newer version, it is left untouched
+                tracing::warn!("Failed to restore tasks, tasks are not persisted, error: {err}");
+                self.task_store = None;
+                return;
+            }
+        };
+
+        let task_count = tasks.len();
+        self.next_task_id = next_task_id;
+        self.next_group_id = next_group_id;
+        for GroupRecord { id, name, task_ids } in groups {
+            drop(self.groups.insert(id, TaskGroup { name, task_ids }));
+            self.next_group_id = self.next_group_id.max(id + 1);
+        }
+        for TaskRecord {
+            id,
+            state,
+            task,
+            file_path,
+            content_length,
+            chunks,
+            attempts,
+            last_error,
+            speed_limit,
+        } in tasks
+        {
+            match state {
+                model::TaskState::Pending | model::TaskState::Downloading => {
+                    self.pending_tasks.push(PendingTask {
+                        priority: task.priority,
+                        timestamp: Reverse(task.creation_timestamp),
+                        task_id: id,
+                    });
+                    drop(self.event_sender.send(Event::TryStartTask));
+                }
+                model::TaskState::Paused => {
+                    let _ = self.paused_tasks.insert(id);
+                }
+                model::TaskState::Canceled => {
+                    let _ = self.canceled_tasks.insert(id);
+                }
+                model::TaskState::Completed => {
+                    let _ = self.completed_tasks.insert(id);
+                }
+                model::TaskState::Failed => {
+                    let _ = self.failed_tasks.insert(id);
+                }
+            }
+            drop(
+                self.download_progresses
+                    .insert(id, DownloaderStatus::with_progress(file_path, content_length, chunks)),
+            );
+            drop(self.tasks.insert(id, task));
+            let _ = self.attempts.insert(id, attempts);
+            if let Some(last_error) = last_error {
+                drop(self.last_errors.insert(id, last_error));
+            }
+            if let Some(speed_limit) = speed_limit {
+                let _ = self.speed_limits.insert(id, speed_limit);
+            }
+            self.next_task_id = self.next_task_id.max(id + 1);
+        }
+
+        tracing::info!("Restored {task_count} task(s) from `{}`", task_store.file_path().display());
+    }
+
+    /// Write the task table to the task store after a short delay, the changes
+    /// made in the meantime are written together
+    fn mark_tasks_modified(&mut self) {
+        if self.task_store.is_none() || self.has_unsaved_changes {
+            return;
+        }
+
+        self.has_unsaved_changes = true;
+        let event_sender = self.event_sender.clone();
+        self.persist_timer = Some(tokio::spawn(async move {
+            tokio::time::sleep(PERSIST_DELAY).await;
+            drop(event_sender.send(Event::PersistTasks));
+        }));
+    }
+
+    /// Write the task table to the task store in the background, writes are
+    /// applied in order
+    fn persist_tasks(&mut self) {
+        self.has_unsaved_changes = false;
+        self.persist_timer = None;
+        let Some(task_store) = self.task_store.clone() else {
+            return;
+        };
+
+        let snapshot = self.snapshot();
+        let previous = self.persisting.take();
+        self.persisting = Some(tokio::spawn(async move {
+            if let Some(previous) = previous {
+                drop(previous.await);
+            }
+            if let Err(err) = task_store.save(&snapshot).await {
+                tracing::warn!("Failed to persist tasks, error: {err}");
+            }
+        }));
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut tasks = self
+            .tasks
+            .iter()
+            .map(|(&id, task)| {
+                let progress = self.download_progresses.get(&id);
+                TaskRecord {
+                    id,
+                    // tasks paused by the schedule are resumed once the next window opens
+                    state: match self.task_state(id) {
+                        model::TaskState::Paused if self.scheduled_pauses.contains(&id) => {
+                            model::TaskState::Pending
+                        }
+                        state => state,
+                    },
+                    task: task.clone(),
+                    file_path: progress
+                        .map_or_else(|| task.uri.guess_filename(), |p| p.file_path().to_path_buf()),
+                    content_length: progress.map_or(0, DownloaderStatus::content_length),
+                    chunks: progress.map(DownloaderStatus::chunks).unwrap_or_default(),
+                    attempts: self.attempts.get(&id).copied().unwrap_or_default(),
+                    last_error: self.last_errors.get(&id).cloned(),
+                    speed_limit: self.speed_limits.get(&id).copied(),
+                }
+            })
+            .collect::<Vec<_>>();
+        tasks.sort_unstable_by_key(|task| task.id);
+        let mut groups = self
+            .groups
+            .iter()
+            .map(|(&id, TaskGroup { name, task_ids })| GroupRecord {
+                id,
+                name: name.clone(),
+                task_ids: task_ids.clone(),
+            })
+            .collect::<Vec<_>>();
+        groups.sort_unstable_by_key(|group| group.id);
+
+        Snapshot::new(self.next_task_id, tasks, self.next_group_id, groups)
+    }
+
+    /// Pause the downloading tasks when the download window closes, resume
+    /// them when the next window opens and start the pending tasks which
+    /// become due
+    async fn check_schedule(&mut self, now: time::OffsetDateTime) {
+        let is_open = self.schedule.is_open(now);
+        if is_open != self.is_window_open {
+            self.is_window_open = is_open;
+            if is_open {
+                tracing::info!("Download window opened");
+                let task_ids = self.scheduled_pauses.drain().collect::<Vec<_>>();
+                self.resume_paused_tasks(&task_ids);
+            } else {
+                tracing::info!("Download window closed");
+                // tasks which can not be resumed would lose their progress, they are
+                // left to finish
+                let task_ids = self
+                    .downloaders
+                    .iter()
+                    .filter(|(_, downloader)| downloader.is_resumable())
+                    .map(|(&task_id, _)| task_id)
+                    .collect::<Vec<_>>();
+                self.pause_downloading_tasks(&task_ids).await;
+                self.scheduled_pauses.extend(task_ids);
+            }
+            self.mark_tasks_modified();
+        }
+
+        if is_open {
+            let due_task_count =
+                self.pending_tasks.iter().filter(|task| self.is_due(task.task_id, now)).count();
+            let free_slots = self.max_concurrent_task_number.saturating_sub(self.downloaders.len());
+            for _ in 0..due_task_count.min(free_slots) {
+                drop(self.event_sender.send(Event::TryStartTask));
+            }
+        }
+        self.arm_schedule_timer(now);
+    }
+
+    /// Check the schedule again once the download window opens or closes or
+    /// the next pending task becomes due
+    fn arm_schedule_timer(&mut self, now: time::OffsetDateTime) {
+        let next_not_before = self
+            .pending_tasks
+            .iter()
+            .filter_map(|task| self.tasks.get(&task.task_id)?.not_before)
+            .filter(|not_before| *not_before > now)
+            .min();
+        let deadline = self.schedule.next_boundary(now).into_iter().chain(next_not_before).min();
+        if let Some((current_deadline, schedule_timer)) = self.schedule_timer.take() {
+            if Some(current_deadline) == deadline && !schedule_timer.is_finished() {
+                self.schedule_timer = Some((current_deadline, schedule_timer));
+                return;
+            }
+            schedule_timer.abort();
+        }
+        self.schedule_timer = deadline.map(|deadline| {
+            let delay = Duration::try_from(deadline - now).unwrap_or_default();
+            let event_sender = self.event_sender.clone();
+            let schedule_timer = tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                drop(event_sender.send(Event::CheckSchedule));
+            });
+            (deadline, schedule_timer)
+        });
+    }
+
+    fn is_due(&self, task_id: u64, now: time::OffsetDateTime) -> bool {
+        self.tasks
+            .get(&task_id)
+            .and_then(|task| task.not_before)
+            .is_none_or(|not_before| not_before <= now)
+    }
+
+    /// Take the pending task of the highest priority whose start time has
+    /// come
+    fn pop_due_task(&mut self, now: time::OffsetDateTime) -> Option<PendingTask> {
+        let mut not_due_tasks = Vec::new();
+        let mut due_task = None;
+        while let Some(task) = self.pending_tasks.pop() {
+            if self.is_due(task.task_id, now) {
+                due_task = Some(task);
+                break;
+            }
+            not_due_tasks.push(task);
+        }
+        self.pending_tasks.extend(not_due_tasks);
+        due_task
     }
 
     #[allow(clippy::cognitive_complexity)]
     async fn try_start_task(&mut self) {
+        let now = time::OffsetDateTime::now_utc();
+        self.arm_schedule_timer(now);
+        if !self.schedule.is_open(now) {
+            tracing::debug!("Download window is closed");
+            return;
+        }
+
+        if self.preemption && self.downloaders.len() >= self.max_concurrent_task_number {
+            self.preempt_task(now).await;
+        }
+
         if self.downloaders.len() < self.max_concurrent_task_number {
             // start downloader
-            let task_id = if let Some(task) = self.pending_tasks.pop() {
+            let task_id = if let Some(task) = self.pop_due_task(now) {
                 task.task_id
             } else {
                 tracing::debug!("No pending tasks");
                 return;
             };
 
+            self.mark_tasks_modified();
             let new_task = self.tasks.get(&task_id).expect("task must exist");
             tracing::info!("Starting task {task_id}, URI: {uri}", uri = new_task.uri);
+            *self.attempts.entry(task_id).or_default() += 1;
 
             match self.factory.create_new_task(new_task).await {
                 Ok(mut downloader) => {
+                    downloader.set_speed_limit(self.speed_limits.get(&task_id).copied());
                     if let Err(err) = downloader.start().await {
-                        tracing::error!("Failed to download task {task_id}, error: {err}");
-                        let _ = self.failed_tasks.insert(task_id);
                         if let Some(progress) = downloader.scrape_status().await {
                             drop(self.download_progresses.insert(task_id, progress));
                         }
+                        self.on_task_failed(task_id, &err);
                     } else {
                         tracing::info!("Started task {task_id}, URI: {uri}", uri = new_task.uri);
                         drop(self.downloaders.insert(task_id, downloader));
+                        self.publish(model::TaskEvent::Started { task_id });
                     }
                 }
-                Err(err) => {
-                    tracing::error!("Failed to download task {task_id}, error: {err}");
-                    let _ = self.failed_tasks.insert(task_id);
-                }
+                Err(err) => self.on_task_failed(task_id, &err),
             }
         }
     }
 
+    /// Send the downloading task of the lowest priority back to the queue if
+    /// the first due pending task has a higher priority
+    async fn preempt_task(&mut self, now: time::OffsetDateTime) {
+        let Some(pending_priority) = self
+            .pending_tasks
+            .iter()
+            .filter(|task| self.is_due(task.task_id, now))
+            .max()
+            .map(|task| task.priority)
+        else {
+            return;
+        };
+        // tasks which can not be resumed would lose their progress
+        let Some(task) = self
+            .downloaders
+            .iter()
+            .filter(|(_, downloader)| downloader.is_resumable())
+            .filter_map(|(&task_id, _)| {
+                let model::CreateTask { priority, creation_timestamp, .. } =
+                    self.tasks.get(&task_id)?;
+                Some(PendingTask {
+                    priority: *priority,
+                    timestamp: Reverse(*creation_timestamp),
+                    task_id,
+                })
+            })
+            .min()
+            .filter(|task| task.priority < pending_priority)
+        else {
+            return;
+        };
+
+        let task_id = task.task_id;
+        if let Some(downloader) = self.downloaders.remove(&task_id) {
+            tracing::info!("Preempting task {task_id} for a task of priority {pending_priority}");
+            stop_downloader(task_id, downloader).await;
+            self.pending_tasks.push(task);
+            self.mark_tasks_modified();
+            self.publish(model::TaskEvent::Preempted { task_id });
+        }
+    }
+
     fn add_uri(
         &mut self,
         new_task: model::CreateTask,
         start_immediately: bool,
         sender: oneshot::Sender<u64>,
     ) {
+        let task_id = self.insert_task(new_task, start_immediately);
+        let _ = sender.send(task_id);
+    }
+
+    fn add_uris(
+        &mut self,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<Vec<u64>>,
+    ) {
+        let task_ids = new_tasks
+            .into_iter()
+            .map(|new_task| self.insert_task(new_task, start_immediately))
+            .collect();
+        drop(sender.send(task_ids));
+    }
+
+    fn add_group(
+        &mut self,
+        name: String,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<(u64, Vec<u64>)>,
+    ) {
+        let group_id = self.next_group_id();
+        let name = if name.is_empty() { format!("Group {group_id}") } else { name };
+        let task_ids = new_tasks
+            .into_iter()
+            .map(|new_task| self.insert_task(new_task, start_immediately))
+            .collect::<Vec<_>>();
+        tracing::info!("Added new group {group_id} of {} task(s), name: {name}", task_ids.len());
+        drop(self.groups.insert(group_id, TaskGroup { name, task_ids: task_ids.clone() }));
+        drop(sender.send((group_id, task_ids)));
+    }
+
+    fn add_group_tasks(
+        &mut self,
+        group_id: u64,
+        new_tasks: Vec<model::CreateTask>,
+        start_immediately: bool,
+        sender: oneshot::Sender<Option<Vec<u64>>>,
+    ) {
+        let task_ids = if self.groups.contains_key(&group_id) {
+            let task_ids = new_tasks
+                .into_iter()
+                .map(|new_task| self.insert_task(new_task, start_immediately))
+                .collect::<Vec<_>>();
+            tracing::info!("Added {} task(s) to group {group_id}", task_ids.len());
+            if let Some(group) = self.groups.get_mut(&group_id) {
+                group.task_ids.extend_from_slice(&task_ids);
+            }
+            Some(task_ids)
+        } else {
+            None
+        };
+        drop(sender.send(task_ids));
+    }
+
+    fn insert_task(&mut self, new_task: model::CreateTask, start_immediately: bool) -> u64 {
         let (task_id, priority, timestamp) =
             (self.next_task_id(), new_task.priority, Reverse(new_task.creation_timestamp));
 
@@ -252,8 +817,9 @@ impl EventHandler {
         } else {
             let _ = self.paused_tasks.insert(task_id);
         }
+        self.publish(model::TaskEvent::Added { task_id });
         drop(self.event_sender.send(Event::TryStartTask));
-        let _ = sender.send(task_id);
+        task_id
     }
 
     #[allow(clippy::cognitive_complexity)]
@@ -270,6 +836,15 @@ impl EventHandler {
             tracing::info!("Removed task {task_id}");
 
             let _ = self.canceled_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Removed { task_id });
+            Some(task_id)
+        } else if self.joining_tasks.remove(&task_id) {
+            tracing::info!("Removed task {task_id}");
+            let _ = self.canceled_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Removed { task_id });
+            Some(task_id)
+        } else if self.stop_seeding(task_id) {
+            // the downloaded file is kept
             Some(task_id)
         } else {
             None
@@ -291,8 +866,16 @@ impl EventHandler {
             tracing::info!("Paused task {task_id}");
 
             let _ = self.paused_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Paused { task_id });
             drop(self.event_sender.send(Event::TryStartTask));
             Some(task_id)
+        } else if self.is_waiting(task_id) {
+            // `retry_task` drops the task if it is waiting for a retry
+            self.pending_tasks.retain(|task| task.task_id != task_id);
+            let _ = self.paused_tasks.insert(task_id);
+            tracing::info!("Paused task {task_id}");
+            self.publish(model::TaskEvent::Paused { task_id });
+            Some(task_id)
         } else {
             None
         };
@@ -301,9 +884,19 @@ impl EventHandler {
 
     async fn pause_all_tasks(&mut self) {
         tracing::info!("Pausing all tasks");
+        let task_ids = self.downloaders.keys().copied().collect::<Vec<_>>();
+        self.pause_downloading_tasks(&task_ids).await;
+        tracing::info!("Paused all tasks");
+    }
+
+    async fn pause_downloading_tasks(&mut self, task_ids: &[u64]) {
         let mut futs = Vec::new();
-        for (task_id, mut downloader) in self.downloaders.drain() {
+        for &task_id in task_ids {
+            let Some(mut downloader) = self.downloaders.remove(&task_id) else {
+                continue;
+            };
             let _ = self.paused_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Paused { task_id });
             futs.push(
                 async move {
                     if let Err(err) = downloader.pause().await {
@@ -317,12 +910,12 @@ impl EventHandler {
             );
         }
         drop(future::join_all(futs).await);
-        tracing::info!("Paused all tasks");
     }
 
     fn resume_task(&mut self, task_id: u64, sender: oneshot::Sender<Option<u64>>) {
         tracing::info!("Resuming task {task_id}");
         let task_id = if self.paused_tasks.remove(&task_id) {
+            let _ = self.scheduled_pauses.remove(&task_id);
             let model::CreateTask { priority, creation_timestamp, .. } =
                 self.tasks.get(&task_id).expect("task must exist");
             self.pending_tasks.push(PendingTask {
@@ -330,6 +923,7 @@ impl EventHandler {
                 timestamp: Reverse(*creation_timestamp),
                 task_id,
             });
+            self.publish(model::TaskEvent::Resumed { task_id });
             drop(self.event_sender.send(Event::TryStartTask));
             Some(task_id)
         } else {
@@ -338,9 +932,109 @@ impl EventHandler {
         let _ = sender.send(task_id);
     }
 
+    async fn pause_group(&mut self, group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>>) {
+        let Some(group) = self.groups.get(&group_id) else {
+            drop(sender.send(None));
+            return;
+        };
+
+        tracing::info!("Pausing group {group_id}");
+        let mut task_ids = Vec::new();
+        let mut futs = Vec::new();
+        for &task_id in &group.task_ids {
+            if let Some(downloader) = self.downloaders.remove(&task_id) {
+                futs.push(stop_downloader(task_id, downloader));
+            } else if !self.is_waiting(task_id) {
+                // `retry_task` drops the tasks which are waiting for a retry
+                continue;
+            }
+            task_ids.push(task_id);
+        }
+        self.pending_tasks.retain(|task| !task_ids.contains(&task.task_id));
+        drop(future::join_all(futs).await);
+
+        for &task_id in &task_ids {
+            let _ = self.paused_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Paused { task_id });
+        }
+        tracing::info!("Paused {} task(s) of group {group_id}", task_ids.len());
+        drop(self.event_sender.send(Event::TryStartTask));
+        drop(sender.send(Some(task_ids)));
+    }
+
+    fn resume_group(&mut self, group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>>) {
+        let Some(group) = self.groups.get(&group_id) else {
+            drop(sender.send(None));
+            return;
+        };
+
+        tracing::info!("Resuming group {group_id}");
+        let task_ids = group
+            .task_ids
+            .iter()
+            .copied()
+            .filter(|task_id| self.paused_tasks.remove(task_id))
+            .collect::<Vec<_>>();
+        for &task_id in &task_ids {
+            let model::CreateTask { priority, creation_timestamp, .. } =
+                self.tasks.get(&task_id).expect("task must exist");
+            self.pending_tasks.push(PendingTask {
+                priority: *priority,
+                timestamp: Reverse(*creation_timestamp),
+                task_id,
+            });
+            self.publish(model::TaskEvent::Resumed { task_id });
+        }
+        drop(self.event_sender.send(Event::TryStartTask));
+        drop(sender.send(Some(task_ids)));
+    }
+
+    async fn remove_group(&mut self, group_id: u64, sender: oneshot::Sender<Option<Vec<u64>>>) {
+        let Some(group) = self.groups.remove(&group_id) else {
+            drop(sender.send(None));
+            return;
+        };
+
+        tracing::info!("Removing group {group_id}");
+        for &task_id in &group.task_ids {
+            let _ = self.stop_seeding(task_id);
+        }
+        let task_ids = group
+            .task_ids
+            .into_iter()
+            .filter(|id| !self.completed_tasks.contains(id) && !self.canceled_tasks.contains(id))
+            .collect::<Vec<_>>();
+        let futs = task_ids
+            .iter()
+            .filter_map(|&task_id| {
+                self.downloaders.remove(&task_id).map(|d| stop_downloader(task_id, d))
+            })
+            .collect::<Vec<_>>();
+        self.pending_tasks.retain(|task| !task_ids.contains(&task.task_id));
+        drop(future::join_all(futs).await);
+
+        for &task_id in &task_ids {
+            let _ = self.paused_tasks.remove(&task_id);
+            let _ = self.canceled_tasks.insert(task_id);
+            self.publish(model::TaskEvent::Removed { task_id });
+        }
+        tracing::info!("Removed group {group_id}");
+        drop(self.event_sender.send(Event::TryStartTask));
+        drop(sender.send(Some(task_ids)));
+    }
+
     fn resume_all_tasks(&mut self) {
         tracing::info!("Resuming all tasks");
-        for task_id in self.paused_tasks.drain() {
+        let task_ids = self.paused_tasks.iter().copied().collect::<Vec<_>>();
+        self.resume_paused_tasks(&task_ids);
+    }
+
+    fn resume_paused_tasks(&mut self, task_ids: &[u64]) {
+        for &task_id in task_ids {
+            if !self.paused_tasks.remove(&task_id) {
+                continue;
+            }
+            let _ = self.scheduled_pauses.remove(&task_id);
             let model::CreateTask { priority, creation_timestamp, .. } =
                 self.tasks.get(&task_id).expect("task must exist");
             self.pending_tasks.push(PendingTask {
@@ -348,6 +1042,7 @@ impl EventHandler {
                 timestamp: Reverse(*creation_timestamp),
                 task_id,
             });
+            self.publish(model::TaskEvent::Resumed { task_id });
             drop(self.event_sender.send(Event::TryStartTask));
         }
     }
@@ -382,21 +1077,40 @@ impl EventHandler {
         drop(sender.send(self.completed_tasks.iter().copied().collect()));
     }
 
+    fn task_state(&self, id: u64) -> model::TaskState {
+        if self.canceled_tasks.contains(&id) {
+            model::TaskState::Canceled
+        } else if self.downloaders.contains_key(&id) || self.joining_tasks.contains(&id) {
+            model::TaskState::Downloading
+        } else if self.completed_tasks.contains(&id) {
+            model::TaskState::Completed
+        } else if self.paused_tasks.contains(&id) {
+            model::TaskState::Paused
+        } else if self.failed_tasks.contains(&id) {
+            model::TaskState::Failed
+        } else {
+            model::TaskState::Pending
+        }
+    }
+
+    // the seeder stops by itself once the upload limit is reached
+    fn stop_seeding(&mut self, task_id: u64) -> bool {
+        let Some(seeder) = self.seeders.remove(&task_id) else {
+            return false;
+        };
+        seeder.abort();
+        tracing::info!("Stopped seeding task {task_id}");
+        true
+    }
+
+    // pending or waiting for a retry
+    fn is_waiting(&self, id: u64) -> bool {
+        self.tasks.contains_key(&id) && self.task_state(id) == model::TaskState::Pending
+    }
+
     fn get_task_status_inner(&self, id: u64) -> Option<model::TaskStatus> {
         self.tasks.get(&id).and_then(|task| {
-            let state = if self.canceled_tasks.contains(&id) {
-                model::TaskState::Canceled
-            } else if self.downloaders.contains_key(&id) {
-                model::TaskState::Downloading
-            } else if self.completed_tasks.contains(&id) {
-                model::TaskState::Completed
-            } else if self.paused_tasks.contains(&id) {
-                model::TaskState::Paused
-            } else if self.failed_tasks.contains(&id) {
-                model::TaskState::Failed
-            } else {
-                model::TaskState::Pending
-            };
+            let state = self.task_state(id);
 
             self.download_progresses.get(&id).cloned().map(|downloader_status: DownloaderStatus| {
                 model::TaskStatus {
@@ -408,6 +1122,10 @@ impl EventHandler {
                     state,
                     priority: task.priority,
                     creation_timestamp: task.creation_timestamp,
+                    attempts: self.attempts.get(&id).copied().unwrap_or_default(),
+                    last_error: self.last_errors.get(&id).cloned(),
+                    connection_tuning_log: downloader_status.connection_tuning_log(),
+                    seeding: self.seeders.get(&id).is_some_and(|seeder| !seeder.is_finished()),
                 }
             })
         })
@@ -425,25 +1143,159 @@ impl EventHandler {
         drop(sender.send(task_statuses));
     }
 
-    async fn on_task_completed(&mut self, task_id: u64) {
+    fn get_group_status(
+        &self,
+        group_id: u64,
+        sender: oneshot::Sender<Option<model::TaskGroupStatus>>,
+    ) {
+        let group_status =
+            self.groups.get(&group_id).map(|group| self.get_group_status_inner(group_id, group));
+        drop(sender.send(group_status));
+    }
+
+    fn get_all_group_statuses(&self, sender: oneshot::Sender<Vec<model::TaskGroupStatus>>) {
+        let mut group_statuses = self
+            .groups
+            .iter()
+            .map(|(&id, group)| self.get_group_status_inner(id, group))
+            .collect::<Vec<_>>();
+        group_statuses.sort_unstable_by_key(|status| status.id);
+        drop(sender.send(group_statuses));
+    }
+
+    fn get_group_status_inner(&self, id: u64, group: &TaskGroup) -> model::TaskGroupStatus {
+        let tasks =
+            group.task_ids.iter().filter_map(|&id| self.get_task_status_inner(id)).collect();
+        model::TaskGroupStatus::new(id, group.name.clone(), tasks)
+    }
+
+    fn on_task_completed(&mut self, task_id: u64) {
         tracing::info!("Completed task {task_id}");
-        if let Some(downloader) = self.downloaders.remove(&task_id) {
-            match downloader.join().await {
-                Ok(Some((_, downloader_status))) => {
-                    if downloader_status.is_completed() {
-                        let _ = self.completed_tasks.insert(task_id);
-                    } else {
-                        let _ = self.failed_tasks.insert(task_id);
+        if let Some(mut downloader) = self.downloaders.remove(&task_id) {
+            // the checksum of the file is verified on joining, do not wait for it
+            let _ = self.joining_tasks.insert(task_id);
+            let event_sender = self.event_sender.clone();
+            let _handle = tokio::spawn(async move {
+                let result = downloader.join().await.map(|status| status.map(|(_, status)| status));
+                // only a verified file is seeded
+                let seeder = result
+                    .as_ref()
+                    .is_ok_and(|status| status.as_ref().is_some_and(DownloaderStatus::is_completed))
+                    .then(|| downloader.seed())
+                    .flatten();
+                drop(event_sender.send(Event::TaskJoined { task_id, result, seeder }));
+            });
+        }
+        drop(self.event_sender.send(Event::TryStartTask));
+    }
+
+    fn on_task_joined(
+        &mut self,
+        task_id: u64,
+        result: Result<Option<DownloaderStatus>, Error>,
+        seeder: Option<JoinHandle<()>>,
+    ) {
+        if !self.joining_tasks.remove(&task_id) {
+            if let Some(seeder) = seeder {
+                seeder.abort();
+            }
+            return;
+        }
+        match result {
+            Ok(Some(downloader_status)) => {
+                if downloader_status.is_completed() {
+                    if let Some(seeder) = seeder {
+                        tracing::info!("Seeding task {task_id}");
+                        drop(self.seeders.insert(task_id, seeder));
                     }
+                    let _ = self.completed_tasks.insert(task_id);
+                    drop(self.retry_intervals.remove(&task_id));
+                    let _unused = self.remote_change_restarts.remove(&task_id);
                     drop(self.download_progresses.insert(task_id, downloader_status));
-                }
-                Ok(None) => {}
-                Err(err) => {
+                    self.publish(model::TaskEvent::Completed { task_id });
+                } else {
                     let _ = self.failed_tasks.insert(task_id);
-                    tracing::warn!("Failed to download task {task_id}, error: {err}");
+                    let error = self
+                        .last_errors
+                        .get(&task_id)
+                        .cloned()
+                        .unwrap_or_else(|| "Transfer is incomplete".to_string());
+                    drop(self.download_progresses.insert(task_id, downloader_status));
+                    self.publish(model::TaskEvent::Failed { task_id, error });
                 }
+                self.run_hooks(task_id);
+            }
+            Ok(None) => {}
+            Err(err) => self.on_task_failed(task_id, &err),
+        }
+        drop(self.event_sender.send(Event::TryStartTask));
+    }
+
+    fn on_task_failed(&mut self, task_id: u64, err: &Error) {
+        tracing::error!("Failed to download task {task_id}, error: {err}");
+        drop(self.last_errors.insert(task_id, err.to_string()));
+
+        // the progress is discarded when the task is started again, the new
+        // content gets a fresh retry budget
+        let restarts = matches!(err, Error::RemoteContentChanged { .. })
+            && self
+                .tasks
+                .get(&task_id)
+                .is_some_and(|task| task.on_remote_change == model::RemoteChangePolicy::Restart);
+        if restarts {
+            let count = self.remote_change_restarts.entry(task_id).or_default();
+            if *count < MAX_REMOTE_CHANGE_RESTARTS {
+                *count += 1;
+                tracing::info!("Restarting task {task_id}, the remote content has changed");
+                drop(self.retry_intervals.remove(&task_id));
+                drop(self.event_sender.send(Event::RetryTask { task_id }));
+                return;
+            }
+        }
+
+        let retry_interval =
+            self.retry_intervals.entry(task_id).or_insert_with(|| self.retry_interval.clone());
+        match retry_interval.next() {
+            Some(interval) if err.is_retryable() => {
+                tracing::info!("Retrying task {task_id} in {interval:?}");
+                let event_sender = self.event_sender.clone();
+                let _handle = tokio::spawn(async move {
+                    tokio::time::sleep(interval).await;
+                    drop(event_sender.send(Event::RetryTask { task_id }));
+                });
+            }
+            _ => {
+                let _ = self.failed_tasks.insert(task_id);
+                self.publish(model::TaskEvent::Failed { task_id, error: err.to_string() });
+                self.run_hooks(task_id);
             }
         }
+    }
+
+    fn run_hooks(&self, task_id: u64) {
+        if let (Some(task), Some(status)) =
+            (self.tasks.get(&task_id), self.get_task_status_inner(task_id))
+        {
+            self.hook_runner.run(&task.uri, &task.hooks, &status);
+        }
+    }
+
+    fn retry_task(&mut self, task_id: u64) {
+        if self.downloaders.contains_key(&task_id)
+            || self.canceled_tasks.contains(&task_id)
+            || self.paused_tasks.contains(&task_id)
+        {
+            return;
+        }
+        let Some(model::CreateTask { priority, creation_timestamp, .. }) = self.tasks.get(&task_id)
+        else {
+            return;
+        };
+        self.pending_tasks.push(PendingTask {
+            priority: *priority,
+            timestamp: Reverse(*creation_timestamp),
+            task_id,
+        });
         drop(self.event_sender.send(Event::TryStartTask));
     }
 
@@ -461,9 +1313,75 @@ impl EventHandler {
         }
     }
 
+    fn set_global_speed_limit(&self, limit: Option<u64>) {
+        tracing::info!("Set global speed limit to {limit:?} byte(s) per second");
+        self.factory.set_speed_limit(limit);
+    }
+
+    fn set_task_speed_limit(
+        &mut self,
+        task_id: u64,
+        limit: Option<u64>,
+        sender: oneshot::Sender<Option<u64>>,
+    ) {
+        let task_id = if self.tasks.contains_key(&task_id) {
+            tracing::info!("Set speed limit of task {task_id} to {limit:?} byte(s) per second");
+            let limit = limit.filter(|&limit| limit > 0);
+            match limit {
+                Some(limit) => drop(self.speed_limits.insert(task_id, limit)),
+                None => drop(self.speed_limits.remove(&task_id)),
+            }
+            if let Some(downloader) = self.downloaders.get(&task_id) {
+                downloader.set_speed_limit(limit);
+            }
+            Some(task_id)
+        } else {
+            None
+        };
+        let _ = sender.send(task_id);
+    }
+
+    fn set_priority(
+        &mut self,
+        task_id: u64,
+        priority: model::Priority,
+        sender: oneshot::Sender<Option<u64>>,
+    ) {
+        let task_id = match self.task_state(task_id) {
+            model::TaskState::Completed | model::TaskState::Canceled => None,
+            _ => self.tasks.get_mut(&task_id).map(|task| {
+                tracing::info!("Setting priority of task {task_id} to {priority}");
+                task.priority = priority;
+                task_id
+            }),
+        };
+        if let Some(task_id) = task_id {
+            // the order of a binary heap is fixed on insertion, rebuild it
+            let mut pending_tasks = std::mem::take(&mut self.pending_tasks).into_vec();
+            for task in pending_tasks.iter_mut().filter(|task| task.task_id == task_id) {
+                task.priority = priority;
+            }
+            self.pending_tasks = BinaryHeap::from(pending_tasks);
+            drop(self.event_sender.send(Event::TryStartTask));
+        }
+        let _ = sender.send(task_id);
+    }
+
+    #[inline]
+    fn publish(&self, event: model::TaskEvent) {
+        // there may be no subscribers, ignore the error
+        drop(self.task_event_sender.send(event));
+    }
+
     const fn next_task_id(&mut self) -> u64 {
         let id = self.next_task_id;
         self.next_task_id += 1;
         id
     }
+
+    const fn next_group_id(&mut self) -> u64 {
+        let id = self.next_group_id;
+        self.next_group_id += 1;
+        id
+    }
 }
@@ -47,6 +47,19 @@ impl fmt::Display for AddUriError {
     }
 }
 
+#[derive(Debug)]
+pub enum AddMetalinkError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for AddMetalinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum PauseTaskError {
     Status { source: tonic::Status },
@@ -131,6 +144,21 @@ impl fmt::Display for GetTaskStatusError {
     }
 }
 
+#[derive(Debug)]
+pub enum GetGroupStatusError {
+    Status { source: tonic::Status },
+    InvalidResponse,
+}
+
+impl fmt::Display for GetGroupStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::InvalidResponse => f.write_str("invalid response"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum GetAllTaskStatusesError {
     Status { source: tonic::Status },
@@ -146,6 +174,73 @@ impl fmt::Display for GetAllTaskStatusesError {
     }
 }
 
+#[derive(Debug)]
+pub enum GetAllGroupStatusesError {
+    Status { source: tonic::Status },
+    InvalidResponse,
+}
+
+impl fmt::Display for GetAllGroupStatusesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::InvalidResponse => f.write_str("invalid response"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CreateGroupError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for CreateGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PauseGroupError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for PauseGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ResumeGroupError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ResumeGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RemoveGroupError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for RemoveGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum IncreaseConcurrentNumberError {
     Status { source: tonic::Status },
@@ -176,6 +271,60 @@ impl fmt::Display for DecreaseConcurrentNumberError {
     }
 }
 
+#[derive(Debug)]
+pub enum SetGlobalSpeedLimitError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SetGlobalSpeedLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetTaskSpeedLimitError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SetTaskSpeedLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetPriorityError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SetPriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WatchEventsError {
+    Status { source: tonic::Status },
+    InvalidResponse,
+}
+
+impl fmt::Display for WatchEventsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::InvalidResponse => f.write_str("invalid response"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum GetSystemVersionError {
     Status { source: tonic::Status },
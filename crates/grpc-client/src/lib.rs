@@ -12,7 +12,7 @@ use self::interceptor::Interceptor;
 pub use self::{
     error::{Error, Result},
     system::System,
-    task::Task,
+    task::{AddedTasks, Task, TaskEventStream},
 };
 
 #[derive(Clone, Debug)]
@@ -7,9 +7,11 @@ use tonic::Request;
 use crate::{
     Client,
     error::{
-        AddUriError, DecreaseConcurrentNumberError, GetAllTaskStatusesError, GetTaskStatusError,
-        IncreaseConcurrentNumberError, PauseAllTasksError, PauseTaskError, RemoveTaskError,
-        ResumeAllTasksError, ResumeTaskError,
+        AddMetalinkError, AddUriError, CreateGroupError, DecreaseConcurrentNumberError,
+        GetAllGroupStatusesError, GetAllTaskStatusesError, GetGroupStatusError, GetTaskStatusError,
+        IncreaseConcurrentNumberError, PauseAllTasksError, PauseGroupError, PauseTaskError,
+        RemoveGroupError, RemoveTaskError, ResumeAllTasksError, ResumeGroupError, ResumeTaskError,
+        SetGlobalSpeedLimitError, SetPriorityError, SetTaskSpeedLimitError, WatchEventsError,
     },
 };
 
@@ -17,8 +19,17 @@ pub trait Task {
     async fn add_uri(
         &self,
         create_task: model::CreateTask,
+        filter: model::PathFilter,
+        group_id: Option<u64>,
         start_immediately: bool,
-    ) -> Result<u64, AddUriError>;
+    ) -> Result<AddedTasks, AddUriError>;
+
+    async fn add_metalink(
+        &self,
+        create_tasks: model::CreateMetalinkTasks,
+        group_id: Option<u64>,
+        start_immediately: bool,
+    ) -> Result<Vec<u64>, AddMetalinkError>;
 
     async fn pause(&self, task_id: u64) -> Result<bool, PauseTaskError>;
 
@@ -36,6 +47,23 @@ pub trait Task {
         &self,
     ) -> Result<Vec<model::TaskStatus>, GetAllTaskStatusesError>;
 
+    async fn get_group_status(
+        &self,
+        group_id: u64,
+    ) -> Result<model::TaskGroupStatus, GetGroupStatusError>;
+
+    async fn get_all_group_statuses(
+        &self,
+    ) -> Result<Vec<model::TaskGroupStatus>, GetAllGroupStatusesError>;
+
+    async fn create_group(&self, name: String) -> Result<u64, CreateGroupError>;
+
+    async fn pause_group(&self, group_id: u64) -> Result<Vec<u64>, PauseGroupError>;
+
+    async fn resume_group(&self, group_id: u64) -> Result<Vec<u64>, ResumeGroupError>;
+
+    async fn remove_group(&self, group_id: u64) -> Result<Vec<u64>, RemoveGroupError>;
+
     async fn increase_concurrent_number(
         &self,
         task_id: u64,
@@ -45,6 +73,56 @@ pub trait Task {
         &self,
         task_id: u64,
     ) -> Result<bool, DecreaseConcurrentNumberError>;
+
+    async fn set_global_speed_limit(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<bool, SetGlobalSpeedLimitError>;
+
+    async fn set_task_speed_limit(
+        &self,
+        task_id: u64,
+        limit: Option<u64>,
+    ) -> Result<bool, SetTaskSpeedLimitError>;
+
+    async fn set_priority(
+        &self,
+        task_id: u64,
+        priority: model::Priority,
+    ) -> Result<bool, SetPriorityError>;
+
+    async fn watch_events(&self) -> Result<TaskEventStream, WatchEventsError>;
+}
+
+/// Tasks created by adding a URI, a URI pointing to a directory is expanded
+/// into a group of tasks
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddedTasks {
+    pub group_id: Option<u64>,
+
+    pub task_ids: Vec<u64>,
+}
+
+/// Stream of task events pushed by the server
+#[derive(Debug)]
+pub struct TaskEventStream {
+    inner: tonic::Streaming<proto::TaskEvent>,
+}
+
+impl TaskEventStream {
+    /// Receive the next event, `None` is returned if the server closes the
+    /// stream
+    ///
+    /// # Errors
+    pub async fn next(&mut self) -> Result<Option<model::TaskEvent>, WatchEventsError> {
+        match self.inner.message().await {
+            Ok(Some(event)) => model::TaskEvent::try_from(event)
+                .map(Some)
+                .map_err(|_| WatchEventsError::InvalidResponse),
+            Ok(None) => Ok(None),
+            Err(source) => Err(WatchEventsError::Status { source }),
+        }
+    }
 }
 
 impl Task for Client {
@@ -52,16 +130,26 @@ impl Task for Client {
         &self,
         model::CreateTask {
             uri,
+            mirrors,
             filename,
             output_directory,
             concurrent_number,
             connection_timeout,
             priority,
+            checksum,
+            headers,
+            basic_auth,
+            proxy,
+            not_before,
+            hooks,
+            on_remote_change,
             ..
         }: model::CreateTask,
+        model::PathFilter { include, exclude }: model::PathFilter,
+        group_id: Option<u64>,
         start_immediately: bool,
-    ) -> Result<u64, AddUriError> {
-        let proto::AddUriResponse { task_id } =
+    ) -> Result<AddedTasks, AddUriError> {
+        let proto::AddUriResponse { group_id, task_ids, .. } =
             proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
                 .add_uri(Request::new(proto::AddUriRequest {
                     uri: uri.to_string(),
@@ -72,12 +160,68 @@ impl Task for Client {
                     output_directory: output_directory
                         .map(|path| path.to_string_lossy().to_string()),
                     priority: Some(i32::from(proto::Priority::from(priority))),
+                    checksum: checksum.map(|checksum| checksum.to_string()),
+                    headers: headers.into_iter().map(proto::HttpHeader::from).collect(),
+                    basic_auth: basic_auth.map(proto::BasicAuth::from),
+                    proxy: proxy.map(proto::Proxy::from),
+                    mirrors: mirrors.iter().map(ToString::to_string).collect(),
+                    include,
+                    exclude,
+                    group_id,
+                    not_before: not_before.as_ref().map(proto::datetime_to_timestamp),
+                    hooks: Some(proto::TaskHooks::from(hooks)),
+                    on_remote_change: i32::from(proto::RemoteChangePolicy::from(on_remote_change)),
                 }))
                 .await
                 .map_err(|source| AddUriError::Status { source })?
                 .into_inner();
 
-        Ok(task_id)
+        Ok(AddedTasks { group_id, task_ids })
+    }
+
+    async fn add_metalink(
+        &self,
+        model::CreateMetalinkTasks {
+            metalink,
+            output_directory,
+            concurrent_number,
+            connection_timeout,
+            priority,
+            headers,
+            basic_auth,
+            proxy,
+            cookies,
+            not_before,
+            hooks,
+            on_remote_change,
+        }: model::CreateMetalinkTasks,
+        group_id: Option<u64>,
+        start_immediately: bool,
+    ) -> Result<Vec<u64>, AddMetalinkError> {
+        let proto::AddMetalinkResponse { task_ids } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .add_metalink(Request::new(proto::AddMetalinkRequest {
+                    start_immediately,
+                    metalink,
+                    output_directory: output_directory
+                        .map(|path| path.to_string_lossy().to_string()),
+                    priority: Some(i32::from(proto::Priority::from(priority))),
+                    connection_timeout: connection_timeout.map(|t| t.as_secs()),
+                    concurrent_number,
+                    headers: headers.into_iter().map(proto::HttpHeader::from).collect(),
+                    basic_auth: basic_auth.map(proto::BasicAuth::from),
+                    proxy: proxy.map(proto::Proxy::from),
+                    group_id,
+                    not_before: not_before.as_ref().map(proto::datetime_to_timestamp),
+                    hooks: Some(proto::TaskHooks::from(hooks)),
+                    on_remote_change: i32::from(proto::RemoteChangePolicy::from(on_remote_change)),
+                    cookies,
+                }))
+                .await
+                .map_err(|source| AddMetalinkError::Status { source })?
+                .into_inner();
+
+        Ok(task_ids)
     }
 
     async fn pause(&self, task_id: u64) -> Result<bool, PauseTaskError> {
@@ -139,8 +283,18 @@ impl Task for Client {
                 .await
                 .map_err(|source| GetTaskStatusError::Status { source })?
                 .into_inner();
-        let proto::TaskStatus { metadata, state, total_length, chunks, concurrent_number, .. } =
-            status.ok_or(GetTaskStatusError::InvalidResponse)?;
+        let proto::TaskStatus {
+            metadata,
+            state,
+            total_length,
+            chunks,
+            concurrent_number,
+            attempts,
+            last_error,
+            connection_tuning_log,
+            seeding,
+            ..
+        } = status.ok_or(GetTaskStatusError::InvalidResponse)?;
         let proto::TaskMetadata { id, file_path, priority, creation_timestamp, .. } =
             metadata.ok_or(GetTaskStatusError::InvalidResponse)?;
         let creation_timestamp = creation_timestamp.ok_or(GetTaskStatusError::InvalidResponse)?;
@@ -158,6 +312,14 @@ impl Task for Client {
             priority: priority.into(),
             creation_timestamp: proto::timestamp_to_datetime(&creation_timestamp)
                 .map_err(|_| GetTaskStatusError::InvalidResponse)?,
+            attempts: usize::try_from(attempts).unwrap_or_default(),
+            last_error,
+            connection_tuning_log: connection_tuning_log
+                .into_iter()
+                .map(model::ConnectionTuningDecision::try_from)
+                .collect::<Result<_, _>>()
+                .map_err(|_| GetTaskStatusError::InvalidResponse)?,
+            seeding,
         })
     }
 
@@ -173,7 +335,16 @@ impl Task for Client {
 
         let mut ret = Vec::with_capacity(statuses.len());
         for proto::TaskStatus {
-            metadata, state, total_length, chunks, concurrent_number, ..
+            metadata,
+            state,
+            total_length,
+            chunks,
+            concurrent_number,
+            attempts,
+            last_error,
+            connection_tuning_log,
+            seeding,
+            ..
         } in statuses
         {
             let proto::TaskMetadata { id, file_path, priority, creation_timestamp, .. } =
@@ -195,12 +366,94 @@ impl Task for Client {
                 state,
                 priority: model::Priority::from(priority),
                 creation_timestamp,
+                attempts: usize::try_from(attempts).unwrap_or_default(),
+                last_error,
+                connection_tuning_log: connection_tuning_log
+                    .into_iter()
+                    .map(model::ConnectionTuningDecision::try_from)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| GetAllTaskStatusesError::InvalidResponse)?,
+                seeding,
             });
         }
 
         Ok(ret)
     }
 
+    async fn get_group_status(
+        &self,
+        group_id: u64,
+    ) -> Result<model::TaskGroupStatus, GetGroupStatusError> {
+        let proto::GetGroupStatusResponse { status } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .get_group_status(Request::new(proto::GetGroupStatusRequest { group_id }))
+                .await
+                .map_err(|source| GetGroupStatusError::Status { source })?
+                .into_inner();
+        status.ok_or(GetGroupStatusError::InvalidResponse).and_then(|status| {
+            model::TaskGroupStatus::try_from(status)
+                .map_err(|_| GetGroupStatusError::InvalidResponse)
+        })
+    }
+
+    async fn get_all_group_statuses(
+        &self,
+    ) -> Result<Vec<model::TaskGroupStatus>, GetAllGroupStatusesError> {
+        let proto::GetAllGroupStatusesResponse { statuses } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .get_all_group_statuses(Request::new(()))
+                .await
+                .map_err(|source| GetAllGroupStatusesError::Status { source })?
+                .into_inner();
+        statuses
+            .into_iter()
+            .map(|status| {
+                model::TaskGroupStatus::try_from(status)
+                    .map_err(|_| GetAllGroupStatusesError::InvalidResponse)
+            })
+            .collect()
+    }
+
+    async fn create_group(&self, name: String) -> Result<u64, CreateGroupError> {
+        let proto::CreateGroupResponse { group_id } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .create_group(Request::new(proto::CreateGroupRequest { name }))
+                .await
+                .map_err(|source| CreateGroupError::Status { source })?
+                .into_inner();
+        Ok(group_id)
+    }
+
+    async fn pause_group(&self, group_id: u64) -> Result<Vec<u64>, PauseGroupError> {
+        let proto::PauseGroupResponse { task_ids } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .pause_group(Request::new(proto::PauseGroupRequest { group_id }))
+                .await
+                .map_err(|source| PauseGroupError::Status { source })?
+                .into_inner();
+        Ok(task_ids)
+    }
+
+    async fn resume_group(&self, group_id: u64) -> Result<Vec<u64>, ResumeGroupError> {
+        let proto::ResumeGroupResponse { task_ids } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .resume_group(Request::new(proto::ResumeGroupRequest { group_id }))
+                .await
+                .map_err(|source| ResumeGroupError::Status { source })?
+                .into_inner();
+        Ok(task_ids)
+    }
+
+    async fn remove_group(&self, group_id: u64) -> Result<Vec<u64>, RemoveGroupError> {
+        let proto::RemoveGroupResponse { task_ids } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .remove_group(Request::new(proto::RemoveGroupRequest { group_id }))
+                .await
+                .map_err(|source| RemoveGroupError::Status { source })?
+                .into_inner();
+        Ok(task_ids)
+    }
+
     async fn increase_concurrent_number(
         &self,
         task_id: u64,
@@ -230,4 +483,61 @@ impl Task for Client {
                 .into_inner();
         Ok(ok)
     }
+
+    async fn set_global_speed_limit(
+        &self,
+        limit: Option<u64>,
+    ) -> Result<bool, SetGlobalSpeedLimitError> {
+        let proto::SetGlobalSpeedLimitResponse { ok } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .set_global_speed_limit(Request::new(proto::SetGlobalSpeedLimitRequest { limit }))
+                .await
+                .map_err(|source| SetGlobalSpeedLimitError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn set_task_speed_limit(
+        &self,
+        task_id: u64,
+        limit: Option<u64>,
+    ) -> Result<bool, SetTaskSpeedLimitError> {
+        let proto::SetTaskSpeedLimitResponse { ok } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .set_task_speed_limit(Request::new(proto::SetTaskSpeedLimitRequest {
+                    task_id,
+                    limit,
+                }))
+                .await
+                .map_err(|source| SetTaskSpeedLimitError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn set_priority(
+        &self,
+        task_id: u64,
+        priority: model::Priority,
+    ) -> Result<bool, SetPriorityError> {
+        let proto::SetPriorityResponse { ok } =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .set_priority(Request::new(proto::SetPriorityRequest {
+                    task_id,
+                    priority: i32::from(proto::Priority::from(priority)),
+                }))
+                .await
+                .map_err(|source| SetPriorityError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn watch_events(&self) -> Result<TaskEventStream, WatchEventsError> {
+        let inner =
+            proto::TaskClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+                .watch_events(Request::new(()))
+                .await
+                .map_err(|source| WatchEventsError::Status { source })?
+                .into_inner();
+        Ok(TaskEventStream { inner })
+    }
 }
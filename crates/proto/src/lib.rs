@@ -26,15 +26,25 @@ mod proto {
 }
 
 use caracal_base::model;
+use snafu::OptionExt;
 
 pub use self::{
+    error::UnexpectedDataFormatError,
     proto::{
-        AddUriRequest, AddUriResponse, Chunk, DecreaseConcurrentNumberRequest,
-        DecreaseConcurrentNumberResponse, GetAllTaskStatusesResponse, GetSystemVersionResponse,
-        GetTaskStatusRequest, GetTaskStatusResponse, IncreaseConcurrentNumberRequest,
-        IncreaseConcurrentNumberResponse, PauseAllTasksResponse, PauseTaskRequest,
-        PauseTaskResponse, Priority, RemoveTaskRequest, RemoveTaskResponse, ResumeAllTasksResponse,
-        ResumeTaskRequest, ResumeTaskResponse, TaskMetadata, TaskState, TaskStatus,
+        AddMetalinkRequest, AddMetalinkResponse, AddUriRequest, AddUriResponse, BasicAuth, Chunk,
+        ConnectionTuningAction, ConnectionTuningDecision, CreateGroupRequest, CreateGroupResponse,
+        DecreaseConcurrentNumberRequest, DecreaseConcurrentNumberResponse,
+        GetAllGroupStatusesResponse, GetAllTaskStatusesResponse, GetGroupStatusRequest,
+        GetGroupStatusResponse, GetSystemVersionResponse, GetTaskStatusRequest,
+        GetTaskStatusResponse, HttpHeader, IncreaseConcurrentNumberRequest,
+        IncreaseConcurrentNumberResponse, PauseAllTasksResponse, PauseGroupRequest,
+        PauseGroupResponse, PauseTaskRequest, PauseTaskResponse, Priority, Proxy,
+        RemoteChangePolicy, RemoveGroupRequest, RemoveGroupResponse, RemoveTaskRequest,
+        RemoveTaskResponse, ResumeAllTasksResponse, ResumeGroupRequest, ResumeGroupResponse,
+        ResumeTaskRequest, ResumeTaskResponse, SetGlobalSpeedLimitRequest,
+        SetGlobalSpeedLimitResponse, SetPriorityRequest, SetPriorityResponse,
+        SetTaskSpeedLimitRequest, SetTaskSpeedLimitResponse, TaskEvent, TaskEventKind,
+        TaskGroupStatus, TaskHooks, TaskMetadata, TaskState, TaskStatus,
         system_client::SystemClient,
         system_server::{System, SystemServer},
         task_client::TaskClient,
@@ -67,6 +77,78 @@ impl From<model::Priority> for Priority {
     }
 }
 
+impl From<RemoteChangePolicy> for model::RemoteChangePolicy {
+    fn from(value: RemoteChangePolicy) -> Self {
+        match value {
+            RemoteChangePolicy::Restart => Self::Restart,
+            RemoteChangePolicy::Fail => Self::Fail,
+        }
+    }
+}
+
+impl From<model::RemoteChangePolicy> for RemoteChangePolicy {
+    fn from(value: model::RemoteChangePolicy) -> Self {
+        match value {
+            model::RemoteChangePolicy::Restart => Self::Restart,
+            model::RemoteChangePolicy::Fail => Self::Fail,
+        }
+    }
+}
+
+impl From<ConnectionTuningAction> for model::ConnectionTuningAction {
+    fn from(value: ConnectionTuningAction) -> Self {
+        match value {
+            ConnectionTuningAction::Increase => Self::Increase,
+            ConnectionTuningAction::Decrease => Self::Decrease,
+            ConnectionTuningAction::BackOff => Self::BackOff,
+        }
+    }
+}
+
+impl From<model::ConnectionTuningAction> for ConnectionTuningAction {
+    fn from(value: model::ConnectionTuningAction) -> Self {
+        match value {
+            model::ConnectionTuningAction::Increase => Self::Increase,
+            model::ConnectionTuningAction::Decrease => Self::Decrease,
+            model::ConnectionTuningAction::BackOff => Self::BackOff,
+        }
+    }
+}
+
+impl From<model::ConnectionTuningDecision> for ConnectionTuningDecision {
+    fn from(decision: model::ConnectionTuningDecision) -> Self {
+        let model::ConnectionTuningDecision { timestamp, action, concurrent_number, speed } =
+            decision;
+        Self {
+            timestamp: Some(datetime_to_timestamp(&timestamp)),
+            action: i32::from(ConnectionTuningAction::from(action)),
+            concurrent_number: u64::try_from(concurrent_number).unwrap_or_default(),
+            speed,
+        }
+    }
+}
+
+impl TryFrom<ConnectionTuningDecision> for model::ConnectionTuningDecision {
+    type Error = UnexpectedDataFormatError;
+
+    fn try_from(decision: ConnectionTuningDecision) -> Result<Self, Self::Error> {
+        let ConnectionTuningDecision { timestamp, action, concurrent_number, speed } = decision;
+        let timestamp = timestamp
+            .as_ref()
+            .and_then(|timestamp| timestamp_to_datetime(timestamp).ok())
+            .context(error::MissingFieldSnafu { field: "timestamp" })?;
+        let action = ConnectionTuningAction::try_from(action).map_err(|_| {
+            UnexpectedDataFormatError::UnknownValue { value: action.to_string().into() }
+        })?;
+        Ok(Self {
+            timestamp,
+            action: model::ConnectionTuningAction::from(action),
+            concurrent_number: usize::try_from(concurrent_number).unwrap_or_default(),
+            speed,
+        })
+    }
+}
+
 impl From<TaskState> for model::TaskState {
     fn from(value: TaskState) -> Self {
         match value {
@@ -106,3 +188,199 @@ impl From<Chunk> for model::ProgressChunk {
         Self { start, end, received, is_completed }
     }
 }
+
+impl From<model::HttpHeader> for HttpHeader {
+    fn from(model::HttpHeader { name, value }: model::HttpHeader) -> Self { Self { name, value } }
+}
+
+impl From<HttpHeader> for model::HttpHeader {
+    fn from(HttpHeader { name, value }: HttpHeader) -> Self { Self { name, value } }
+}
+
+impl From<model::BasicAuth> for BasicAuth {
+    fn from(model::BasicAuth { username, password }: model::BasicAuth) -> Self {
+        Self { username, password }
+    }
+}
+
+impl From<BasicAuth> for model::BasicAuth {
+    fn from(BasicAuth { username, password }: BasicAuth) -> Self { Self { username, password } }
+}
+
+impl From<model::Proxy> for Proxy {
+    fn from(model::Proxy { uri, username, password, no_proxy }: model::Proxy) -> Self {
+        Self { uri, username, password, no_proxy }
+    }
+}
+
+impl From<Proxy> for model::Proxy {
+    fn from(Proxy { uri, username, password, no_proxy }: Proxy) -> Self {
+        Self { uri, username, password, no_proxy }
+    }
+}
+
+impl From<model::TaskHooks> for TaskHooks {
+    fn from(model::TaskHooks { commands, webhooks }: model::TaskHooks) -> Self {
+        Self { commands, webhooks: webhooks.iter().map(ToString::to_string).collect() }
+    }
+}
+
+impl From<model::TaskEvent> for TaskEvent {
+    fn from(value: model::TaskEvent) -> Self {
+        let task_id = value.task_id();
+        let (kind, received_bytes, total_length, error) = match value {
+            model::TaskEvent::Added { .. } => (TaskEventKind::Added, 0, 0, None),
+            model::TaskEvent::Started { .. } => (TaskEventKind::Started, 0, 0, None),
+            model::TaskEvent::Progress { received_bytes, total_length, .. } => {
+                (TaskEventKind::Progress, received_bytes, total_length, None)
+            }
+            model::TaskEvent::Paused { .. } => (TaskEventKind::Paused, 0, 0, None),
+            model::TaskEvent::Resumed { .. } => (TaskEventKind::Resumed, 0, 0, None),
+            model::TaskEvent::Completed { .. } => (TaskEventKind::Completed, 0, 0, None),
+            model::TaskEvent::Failed { error, .. } => (TaskEventKind::Failed, 0, 0, Some(error)),
+            model::TaskEvent::Removed { .. } => (TaskEventKind::Removed, 0, 0, None),
+            model::TaskEvent::Preempted { .. } => (TaskEventKind::Preempted, 0, 0, None),
+        };
+        Self { task_id, kind: i32::from(kind), received_bytes, total_length, error }
+    }
+}
+
+impl TryFrom<TaskEvent> for model::TaskEvent {
+    type Error = UnexpectedDataFormatError;
+
+    fn try_from(
+        TaskEvent { task_id, kind, received_bytes, total_length, error }: TaskEvent,
+    ) -> Result<Self, Self::Error> {
+        let kind = TaskEventKind::try_from(kind).map_err(|_| {
+            UnexpectedDataFormatError::UnknownValue { value: kind.to_string().into() }
+        })?;
+        Ok(match kind {
+            TaskEventKind::Added => Self::Added { task_id },
+            TaskEventKind::Started => Self::Started { task_id },
+            TaskEventKind::Progress => Self::Progress { task_id, received_bytes, total_length },
+            TaskEventKind::Paused => Self::Paused { task_id },
+            TaskEventKind::Resumed => Self::Resumed { task_id },
+            TaskEventKind::Completed => Self::Completed { task_id },
+            TaskEventKind::Failed => Self::Failed {
+                task_id,
+                error: error.context(error::MissingFieldSnafu { field: "error" })?,
+            },
+            TaskEventKind::Removed => Self::Removed { task_id },
+            TaskEventKind::Preempted => Self::Preempted { task_id },
+        })
+    }
+}
+
+impl From<model::TaskStatus> for TaskStatus {
+    fn from(
+        model::TaskStatus {
+            id,
+            file_path,
+            state,
+            priority,
+            creation_timestamp,
+            chunks,
+            content_length,
+            concurrent_number,
+            attempts,
+            last_error,
+            connection_tuning_log,
+            seeding,
+        }: model::TaskStatus,
+    ) -> Self {
+        Self {
+            metadata: Some(TaskMetadata {
+                id,
+                priority: i32::from(Priority::from(priority)),
+                creation_timestamp: Some(datetime_to_timestamp(&creation_timestamp)),
+                size: Some(content_length),
+                file_path: file_path.to_str().unwrap_or_default().to_string(),
+            }),
+            state: i32::from(TaskState::from(state)),
+            received_bytes: chunks.iter().map(|chunk| chunk.received).sum(),
+            total_length: content_length,
+            concurrent_number: u64::try_from(concurrent_number).unwrap_or(1),
+            chunks: chunks.into_iter().map(Chunk::from).collect(),
+            attempts: u64::try_from(attempts).unwrap_or_default(),
+            last_error,
+            connection_tuning_log: connection_tuning_log
+                .into_iter()
+                .map(ConnectionTuningDecision::from)
+                .collect(),
+            seeding,
+        }
+    }
+}
+
+impl TryFrom<TaskStatus> for model::TaskStatus {
+    type Error = UnexpectedDataFormatError;
+
+    fn try_from(
+        TaskStatus {
+            metadata,
+            state,
+            total_length,
+            chunks,
+            concurrent_number,
+            attempts,
+            last_error,
+            connection_tuning_log,
+            seeding,
+            ..
+        }: TaskStatus,
+    ) -> Result<Self, Self::Error> {
+        let TaskMetadata { id, file_path, priority, creation_timestamp, .. } =
+            metadata.context(error::MissingFieldSnafu { field: "metadata" })?;
+        let creation_timestamp = creation_timestamp
+            .as_ref()
+            .and_then(|timestamp| timestamp_to_datetime(timestamp).ok())
+            .context(error::MissingFieldSnafu { field: "creation_timestamp" })?;
+        let state = TaskState::try_from(state).map_err(|_| {
+            UnexpectedDataFormatError::UnknownValue { value: state.to_string().into() }
+        })?;
+        Ok(Self {
+            id,
+            file_path: file_path.into(),
+            content_length: total_length,
+            chunks: chunks.into_iter().map(model::ProgressChunk::from).collect(),
+            concurrent_number: usize::try_from(concurrent_number).unwrap_or(1),
+            state: model::TaskState::from(state),
+            priority: model::Priority::from(priority),
+            creation_timestamp,
+            attempts: usize::try_from(attempts).unwrap_or_default(),
+            last_error,
+            connection_tuning_log: connection_tuning_log
+                .into_iter()
+                .map(model::ConnectionTuningDecision::try_from)
+                .collect::<Result<_, _>>()?,
+            seeding,
+        })
+    }
+}
+
+impl From<model::TaskGroupStatus> for TaskGroupStatus {
+    fn from(status: model::TaskGroupStatus) -> Self {
+        let (received_bytes, total_length) = (status.received_bytes(), status.content_length());
+        let model::TaskGroupStatus { id, name, state, tasks } = status;
+        Self {
+            id,
+            name,
+            state: i32::from(TaskState::from(state)),
+            received_bytes,
+            total_length,
+            tasks: tasks.into_iter().map(TaskStatus::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<TaskGroupStatus> for model::TaskGroupStatus {
+    type Error = UnexpectedDataFormatError;
+
+    fn try_from(
+        TaskGroupStatus { id, name, tasks, .. }: TaskGroupStatus,
+    ) -> Result<Self, Self::Error> {
+        let tasks =
+            tasks.into_iter().map(model::TaskStatus::try_from).collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(id, name, tasks))
+    }
+}
@@ -1,6 +1,10 @@
 use std::{collections::HashMap, net::SocketAddr, path::PathBuf, time::Duration};
 
-use caracal_base::profile::{minio::MinioAlias, ssh::SshConfig};
+use caracal_base::{
+    model,
+    profile::{minio::MinioAlias, s3::S3Profile, ssh::SshConfig},
+    utils::RetryInterval,
+};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -10,6 +14,8 @@ pub struct Config {
 
     pub minio_aliases: HashMap<String, MinioAlias>,
 
+    pub s3_profiles: HashMap<String, S3Profile>,
+
     pub grpc_listen_address: Option<SocketAddr>,
 
     pub grpc_local_socket: Option<PathBuf>,
@@ -18,6 +24,8 @@ pub struct Config {
 
     pub dbus: DBusConfig,
 
+    pub desktop_notification: DesktopNotificationConfig,
+
     pub web: WebConfig,
 
     pub metrics: MetricsConfig,
@@ -30,6 +38,30 @@ pub struct TaskSchedulerConfig {
     pub concurrent_number: usize,
 
     pub default_output_directory: PathBuf,
+
+    pub task_store_file_path: Option<PathBuf>,
+
+    pub retry_interval: RetryInterval,
+
+    pub chunk_retry_interval: RetryInterval,
+
+    pub global_speed_limit: Option<u64>,
+
+    pub preemption: bool,
+
+    pub schedule: model::DownloadSchedule,
+
+    pub hooks: model::TaskHooks,
+
+    pub hook_timeout: Duration,
+
+    pub allow_task_commands: bool,
+
+    pub torrent_seed_ratio: f64,
+
+    pub verify_on_resume: bool,
+
+    pub connection_tuning: Option<caracal_engine::ConnectionTuning>,
 }
 
 #[derive(Clone, Debug)]
@@ -37,6 +69,8 @@ pub struct HttpConfig {
     pub user_agent: String,
 
     pub concurrent_connections: u16,
+
+    pub proxy: Option<model::Proxy>,
 }
 
 #[derive(Clone, Debug)]
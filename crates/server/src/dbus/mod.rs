@@ -0,0 +1,185 @@
+mod task;
+
+use caracal_base::model;
+use caracal_engine::TaskScheduler;
+use tokio::sync::broadcast::error::RecvError;
+
+pub use self::task::TaskInterface;
+use crate::config::DBusConfig;
+
+/// The well-known name of the daemon, the identifier tells apart multiple
+/// daemons on the same bus
+pub fn service_name(DBusConfig { identifier, .. }: &DBusConfig) -> String {
+    identifier.as_ref().filter(|identifier| !identifier.is_empty()).map_or_else(
+        || caracal_base::DBUS_SERVICE_NAME.to_string(),
+        |identifier| format!("{}.{identifier}", caracal_base::DBUS_SERVICE_NAME),
+    )
+}
+
+/// # Errors
+///
+/// This function will return an error if the session bus is not available or
+/// the name is taken.
+pub async fn connect(
+    config: &DBusConfig,
+    task_scheduler: TaskScheduler,
+) -> zbus::Result<zbus::Connection> {
+    zbus::connection::Builder::session()?
+        .name(service_name(config))?
+        .serve_at(caracal_base::DBUS_MANAGER_OBJECT_PATH, TaskInterface::new(task_scheduler))?
+        .build()
+        .await
+}
+
+/// Emit `StateChanged` signals for the events of tasks until the scheduler is
+/// closed or `shutdown` resolves
+///
+/// # Errors
+///
+/// This function will return an error if `org.caracal.Task` is not served on
+/// the connection.
+pub async fn forward_task_events<F>(
+    connection: &zbus::Connection,
+    task_scheduler: TaskScheduler,
+    shutdown: F,
+) -> zbus::Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    let interface = connection
+        .object_server()
+        .interface::<_, TaskInterface>(caracal_base::DBUS_MANAGER_OBJECT_PATH)
+        .await?;
+    let mut events = task_scheduler.subscribe();
+    let mut shutdown = std::pin::pin!(shutdown);
+    loop {
+        let event = tokio::select! {
+            () = &mut shutdown => break,
+            event = events.recv() => event,
+        };
+        let task_id = match event {
+            Ok(model::TaskEvent::Progress { .. }) => continue,
+            Ok(event) => event.task_id(),
+            Err(RecvError::Lagged(n)) => {
+                tracing::warn!("D-Bus service lagged behind, {n} event(s) skipped");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let Ok(maybe_status) = task_scheduler.get_task_status(task_id).await else {
+            break;
+        };
+        if let Some(status) = maybe_status {
+            let state = status.state.to_string();
+            if let Err(err) =
+                TaskInterface::state_changed(interface.signal_emitter(), task_id, &state).await
+            {
+                tracing::warn!("Failed to emit D-Bus signal, error: {err}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use caracal_engine::{DownloaderFactory, TaskScheduler};
+    use futures::StreamExt;
+
+    use super::{TaskInterface, service_name};
+    use crate::config::DBusConfig;
+
+    #[zbus::proxy(
+        interface = "org.caracal.Task",
+        default_service = "org.caracal.caracal-daemon",
+        default_path = "/org/caracal/caracal_daemon/manager"
+    )]
+    trait Task {
+        fn add_uri(
+            &self,
+            uri: &str,
+            output_directory: &str,
+            filename: &str,
+            start_immediately: bool,
+        ) -> zbus::Result<Vec<u64>>;
+
+        fn pause(&self, task_id: u64) -> zbus::Result<bool>;
+
+        fn resume(&self, task_id: u64) -> zbus::Result<bool>;
+
+        fn remove(&self, task_id: u64) -> zbus::Result<bool>;
+
+        fn get_all_task_statuses(&self) -> zbus::Result<Vec<(u64, String, String, u64, u64)>>;
+
+        #[zbus(signal)]
+        fn state_changed(&self, task_id: u64, state: &str) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn test_service_name() {
+        let config = DBusConfig { enable: true, identifier: None };
+        assert_eq!(service_name(&config), "org.caracal.caracal-daemon");
+        let config = DBusConfig { enable: true, identifier: Some("laptop".to_string()) };
+        assert_eq!(service_name(&config), "org.caracal.caracal-daemon.laptop");
+    }
+
+    #[tokio::test]
+    async fn test_task_interface() {
+        let factory = DownloaderFactory::builder().unwrap().build().unwrap();
+        // no task is started so that the states of the tasks are predictable
+        let (task_scheduler, handle) = TaskScheduler::new(factory, 0);
+
+        // a pair of connected peers stands in for the session bus
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair().unwrap();
+        let server = zbus::connection::Builder::unix_stream(server_stream)
+            .server(zbus::Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at(
+                caracal_base::DBUS_MANAGER_OBJECT_PATH,
+                TaskInterface::new(task_scheduler.clone()),
+            )
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = futures::future::try_join(server, client).await.unwrap();
+        let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+        let forwarder = tokio::spawn({
+            let task_scheduler = task_scheduler.clone();
+            async move {
+                super::forward_task_events(&server, task_scheduler, async move {
+                    drop(shutdown_receiver.await);
+                })
+                .await
+            }
+        });
+
+        let proxy = TaskProxy::new(&client).await.unwrap();
+        let mut state_changes = proxy.receive_state_changed().await.unwrap();
+        let task_ids = proxy.add_uri("/tmp/debian.iso", "", "", false).await.unwrap();
+        assert_eq!(task_ids.len(), 1);
+        let task_id = task_ids[0];
+        assert!(proxy.add_uri("::", "", "", false).await.is_err());
+
+        let args = state_changes.next().await.unwrap();
+        let args = args.args().unwrap();
+        assert_eq!((args.task_id, args.state), (task_id, "Paused"));
+
+        let statuses = proxy.get_all_task_statuses().await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].0, task_id);
+        assert_eq!(statuses[0].2, "Paused");
+
+        assert!(proxy.resume(task_id).await.unwrap());
+        assert!(!proxy.resume(task_id).await.unwrap());
+        let args = state_changes.next().await.unwrap();
+        let args = args.args().unwrap();
+        assert_eq!((args.task_id, args.state), (task_id, "Pending"));
+        assert!(!proxy.pause(task_id + 1).await.unwrap());
+        assert!(!proxy.remove(task_id + 1).await.unwrap());
+
+        drop(shutdown_sender);
+        forwarder.await.unwrap().unwrap();
+        handle.abort();
+    }
+}
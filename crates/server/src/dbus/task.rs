@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use caracal_base::{model, utils::parse_uri};
+use caracal_engine::TaskScheduler;
+use zbus::{fdo, object_server::SignalEmitter};
+
+use crate::metrics;
+
+/// `org.caracal.Task`, the status of a task is `(id, file_path, state,
+/// received_bytes, content_length)`
+pub struct TaskInterface {
+    task_scheduler: TaskScheduler,
+}
+
+impl TaskInterface {
+    #[inline]
+    pub const fn new(task_scheduler: TaskScheduler) -> Self { Self { task_scheduler } }
+}
+
+#[zbus::interface(name = "org.caracal.Task")]
+impl TaskInterface {
+    /// Add the URI as a task, or the files under it as a group of tasks if it
+    /// points to a directory, `output_directory` and `filename` are ignored if
+    /// they are empty
+    async fn add_uri(
+        &self,
+        uri: &str,
+        output_directory: &str,
+        filename: &str,
+        start_immediately: bool,
+    ) -> fdo::Result<Vec<u64>> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let uri = parse_uri(uri).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        let new_task = model::CreateTask {
+            filename: (!filename.is_empty()).then(|| PathBuf::from(filename)),
+            output_directory: (!output_directory.is_empty())
+                .then(|| PathBuf::from(output_directory)),
+            ..model::CreateTask::new(uri)
+        };
+        self.task_scheduler
+            .check_task(&new_task)
+            .await
+            .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        match self
+            .task_scheduler
+            .add_directory(new_task.clone(), &model::PathFilter::default(), None, start_immediately)
+            .await
+        {
+            Ok(Some((_, task_ids))) => Ok(task_ids),
+            Ok(None) => self
+                .task_scheduler
+                .add_uri(new_task, start_immediately)
+                .await
+                .map(|task_id| vec![task_id])
+                .map_err(service_shutdown_error),
+            Err(err @ caracal_engine::TaskSchedulerError::ExpandDirectory { .. }) => {
+                Err(fdo::Error::InvalidArgs(err.to_string()))
+            }
+            Err(err) => Err(service_shutdown_error(err)),
+        }
+    }
+
+    async fn pause(&self, task_id: u64) -> fdo::Result<bool> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+        self.task_scheduler
+            .pause_task(task_id)
+            .await
+            .map(|task_id| task_id.is_some())
+            .map_err(service_shutdown_error)
+    }
+
+    async fn resume(&self, task_id: u64) -> fdo::Result<bool> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+        self.task_scheduler
+            .resume_task(task_id)
+            .await
+            .map(|task_id| task_id.is_some())
+            .map_err(service_shutdown_error)
+    }
+
+    async fn remove(&self, task_id: u64) -> fdo::Result<bool> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+        self.task_scheduler
+            .remove_task(task_id)
+            .await
+            .map(|task_id| task_id.is_some())
+            .map_err(service_shutdown_error)
+    }
+
+    async fn get_all_task_statuses(&self) -> fdo::Result<Vec<(u64, String, String, u64, u64)>> {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+        let mut statuses =
+            self.task_scheduler.get_all_task_statuses().await.map_err(service_shutdown_error)?;
+        statuses.sort_unstable_by_key(|status| status.id);
+        Ok(statuses
+            .into_iter()
+            .map(|status| {
+                (
+                    status.id,
+                    status.file_path.to_string_lossy().to_string(),
+                    status.state.to_string(),
+                    status.chunks.iter().map(|chunk| chunk.received).sum(),
+                    status.content_length,
+                )
+            })
+            .collect())
+    }
+
+    /// Emitted when a task is added, started, paused, resumed, completed,
+    /// failed or removed
+    #[zbus(signal)]
+    pub async fn state_changed(
+        emitter: &SignalEmitter<'_>,
+        task_id: u64,
+        state: &str,
+    ) -> zbus::Result<()>;
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn service_shutdown_error<E>(_err: E) -> fdo::Error {
+    fdo::Error::Failed("Caracal is shutting down".to_string())
+}
@@ -1,21 +1,35 @@
-use std::{path::PathBuf, time::Duration};
+use std::{path::PathBuf, pin::Pin, time::Duration};
 
-use caracal_base::model;
+use caracal_base::{model, utils::parse_uri};
 use caracal_engine::TaskScheduler;
 use caracal_proto as proto;
+use futures::Stream;
 use time::OffsetDateTime;
+use tokio_stream::{
+    StreamExt,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+
+use crate::ShutdownSignal;
 
 pub struct TaskService {
     task_scheduler: TaskScheduler,
+    shutdown_signal: ShutdownSignal,
 }
 
 impl TaskService {
     #[inline]
-    pub const fn new(task_scheduler: TaskScheduler) -> Self { Self { task_scheduler } }
+    pub const fn new(task_scheduler: TaskScheduler, shutdown_signal: ShutdownSignal) -> Self {
+        Self { task_scheduler, shutdown_signal }
+    }
 }
 
 #[tonic::async_trait]
 impl proto::Task for TaskService {
+    type WatchEventsStream =
+        Pin<Box<dyn Stream<Item = Result<proto::TaskEvent, tonic::Status>> + Send>>;
+
+    #[allow(clippy::too_many_lines)]
     async fn add_uri(
         &self,
         request: tonic::Request<proto::AddUriRequest>,
@@ -28,12 +42,32 @@ impl proto::Task for TaskService {
             start_immediately,
             connection_timeout,
             concurrent_number,
+            checksum,
+            headers,
+            basic_auth,
+            proxy,
+            mirrors,
+            include,
+            exclude,
+            group_id,
+            not_before,
+            hooks,
+            on_remote_change,
         } = request.into_inner();
-        let uri = uri
-            .parse::<http::Uri>()
+        let uri =
+            parse_uri(&uri).map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+        let mirrors = mirrors
+            .iter()
+            .map(|mirror| parse_uri(mirror))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+        let checksum = checksum
+            .map(|checksum| checksum.parse::<model::Checksum>())
+            .transpose()
             .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
         let new_task = model::CreateTask {
             uri,
+            mirrors,
             filename: filename.map(PathBuf::from),
             output_directory: output_directory.map(PathBuf::from),
             concurrent_number,
@@ -44,15 +78,129 @@ impl proto::Task for TaskService {
                 )
             }),
             creation_timestamp: OffsetDateTime::now_utc(),
+            checksum,
+            headers: headers.into_iter().map(model::HttpHeader::from).collect(),
+            basic_auth: basic_auth.map(model::BasicAuth::from),
+            proxy: proxy.map(model::Proxy::from),
+            content_length: None,
+            not_before: parse_not_before(not_before.as_ref())?,
+            hooks: parse_task_hooks(hooks)?,
+            on_remote_change: parse_remote_change_policy(on_remote_change),
         };
 
+        self.task_scheduler
+            .check_task(&new_task)
+            .await
+            .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+
+        // a directory is expanded into a group of tasks
+        let filter = model::PathFilter { include, exclude };
+        match self
+            .task_scheduler
+            .add_directory(new_task.clone(), &filter, group_id, start_immediately)
+            .await
+        {
+            Ok(Some((group_id, task_ids))) => {
+                return Ok(tonic::Response::new(proto::AddUriResponse {
+                    task_id: task_ids.first().copied().unwrap_or_default(),
+                    group_id: Some(group_id),
+                    task_ids,
+                }));
+            }
+            Ok(None) => {}
+            Err(err @ caracal_engine::TaskSchedulerError::ExpandDirectory { .. }) => {
+                return Err(tonic::Status::invalid_argument(err.to_string()));
+            }
+            Err(caracal_engine::TaskSchedulerError::GroupNotFound { group_id }) => {
+                return Err(tonic::Status::not_found(group_id.to_string()));
+            }
+            Err(err) => return Err(service_shutdown_status(err)),
+        }
+
+        if let Some(group_id) = group_id {
+            let task_ids = self
+                .task_scheduler
+                .add_group_tasks(group_id, vec![new_task], start_immediately)
+                .await
+                .map_err(service_shutdown_status)?
+                .ok_or_else(|| tonic::Status::not_found(group_id.to_string()))?;
+            return Ok(tonic::Response::new(proto::AddUriResponse {
+                task_id: task_ids.first().copied().unwrap_or_default(),
+                group_id: Some(group_id),
+                task_ids,
+            }));
+        }
+
         let task_id = self
             .task_scheduler
             .add_uri(new_task, start_immediately)
             .await
             .map_err(service_shutdown_status)?;
 
-        Ok(tonic::Response::new(proto::AddUriResponse { task_id }))
+        Ok(tonic::Response::new(proto::AddUriResponse {
+            task_id,
+            group_id: None,
+            task_ids: vec![task_id],
+        }))
+    }
+
+    async fn add_metalink(
+        &self,
+        request: tonic::Request<proto::AddMetalinkRequest>,
+    ) -> Result<tonic::Response<proto::AddMetalinkResponse>, tonic::Status> {
+        let proto::AddMetalinkRequest {
+            start_immediately,
+            metalink,
+            output_directory,
+            priority,
+            connection_timeout,
+            concurrent_number,
+            headers,
+            basic_auth,
+            proxy,
+            group_id,
+            not_before,
+            hooks,
+            on_remote_change,
+            cookies,
+        } = request.into_inner();
+        let new_tasks = model::CreateMetalinkTasks {
+            metalink,
+            output_directory: output_directory.map(PathBuf::from),
+            concurrent_number,
+            connection_timeout: connection_timeout.map(Duration::from_secs),
+            priority: priority.map_or(model::Priority::Normal, |v| {
+                model::Priority::from(
+                    proto::Priority::try_from(v).unwrap_or(proto::Priority::Normal),
+                )
+            }),
+            headers: headers.into_iter().map(model::HttpHeader::from).collect(),
+            basic_auth: basic_auth.map(model::BasicAuth::from),
+            proxy: proxy.map(model::Proxy::from),
+            cookies,
+            not_before: parse_not_before(not_before.as_ref())?,
+            hooks: parse_task_hooks(hooks)?,
+            on_remote_change: parse_remote_change_policy(on_remote_change),
+        }
+        .create_tasks()
+        .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+
+        if let Some(group_id) = group_id {
+            return self
+                .task_scheduler
+                .add_group_tasks(group_id, new_tasks, start_immediately)
+                .await
+                .map_err(service_shutdown_status)?
+                .map(|task_ids| tonic::Response::new(proto::AddMetalinkResponse { task_ids }))
+                .ok_or_else(|| tonic::Status::not_found(group_id.to_string()));
+        }
+
+        let task_ids = self
+            .task_scheduler
+            .add_uris(new_tasks, start_immediately)
+            .await
+            .map_err(service_shutdown_status)?;
+        Ok(tonic::Response::new(proto::AddMetalinkResponse { task_ids }))
     }
 
     async fn pause(
@@ -133,6 +281,10 @@ impl proto::Task for TaskService {
             chunks,
             content_length,
             concurrent_number,
+            attempts,
+            last_error,
+            connection_tuning_log,
+            seeding,
         }) =
             self.task_scheduler.get_task_status(task_id).await.map_err(service_shutdown_status)?
         {
@@ -152,6 +304,13 @@ impl proto::Task for TaskService {
                     total_length: content_length,
                     concurrent_number: concurrent_number as u64,
                     chunks,
+                    attempts: attempts as u64,
+                    last_error,
+                    connection_tuning_log: connection_tuning_log
+                        .into_iter()
+                        .map(proto::ConnectionTuningDecision::from)
+                        .collect(),
+                    seeding,
                 }),
             }))
         } else {
@@ -176,6 +335,10 @@ impl proto::Task for TaskService {
                 chunks,
                 content_length,
                 concurrent_number,
+                attempts,
+                last_error,
+                connection_tuning_log,
+                seeding,
             } = sts;
 
             let received_bytes = chunks.iter().map(|chunk| chunk.received).sum();
@@ -194,11 +357,102 @@ impl proto::Task for TaskService {
                 total_length: content_length,
                 concurrent_number: u64::try_from(concurrent_number).unwrap_or(1),
                 chunks,
+                attempts: attempts as u64,
+                last_error,
+                connection_tuning_log: connection_tuning_log
+                    .into_iter()
+                    .map(proto::ConnectionTuningDecision::from)
+                    .collect(),
+                seeding,
             });
         }
         Ok(tonic::Response::new(proto::GetAllTaskStatusesResponse { statuses: task_statuses }))
     }
 
+    async fn get_group_status(
+        &self,
+        request: tonic::Request<proto::GetGroupStatusRequest>,
+    ) -> Result<tonic::Response<proto::GetGroupStatusResponse>, tonic::Status> {
+        let proto::GetGroupStatusRequest { group_id } = request.into_inner();
+
+        self.task_scheduler
+            .get_group_status(group_id)
+            .await
+            .map_err(service_shutdown_status)?
+            .map(|status| {
+                tonic::Response::new(proto::GetGroupStatusResponse {
+                    status: Some(proto::TaskGroupStatus::from(status)),
+                })
+            })
+            .ok_or_else(|| tonic::Status::not_found(group_id.to_string()))
+    }
+
+    async fn get_all_group_statuses(
+        &self,
+        _request: tonic::Request<()>,
+    ) -> Result<tonic::Response<proto::GetAllGroupStatusesResponse>, tonic::Status> {
+        let statuses =
+            self.task_scheduler.get_all_group_statuses().await.map_err(service_shutdown_status)?;
+        Ok(tonic::Response::new(proto::GetAllGroupStatusesResponse {
+            statuses: statuses.into_iter().map(proto::TaskGroupStatus::from).collect(),
+        }))
+    }
+
+    async fn create_group(
+        &self,
+        request: tonic::Request<proto::CreateGroupRequest>,
+    ) -> Result<tonic::Response<proto::CreateGroupResponse>, tonic::Status> {
+        let proto::CreateGroupRequest { name } = request.into_inner();
+
+        self.task_scheduler
+            .add_group(name, Vec::new(), false)
+            .await
+            .map(|(group_id, _)| tonic::Response::new(proto::CreateGroupResponse { group_id }))
+            .map_err(service_shutdown_status)
+    }
+
+    async fn pause_group(
+        &self,
+        request: tonic::Request<proto::PauseGroupRequest>,
+    ) -> Result<tonic::Response<proto::PauseGroupResponse>, tonic::Status> {
+        let proto::PauseGroupRequest { group_id } = request.into_inner();
+
+        self.task_scheduler
+            .pause_group(group_id)
+            .await
+            .map_err(service_shutdown_status)?
+            .map(|task_ids| tonic::Response::new(proto::PauseGroupResponse { task_ids }))
+            .ok_or_else(|| tonic::Status::not_found(group_id.to_string()))
+    }
+
+    async fn resume_group(
+        &self,
+        request: tonic::Request<proto::ResumeGroupRequest>,
+    ) -> Result<tonic::Response<proto::ResumeGroupResponse>, tonic::Status> {
+        let proto::ResumeGroupRequest { group_id } = request.into_inner();
+
+        self.task_scheduler
+            .resume_group(group_id)
+            .await
+            .map_err(service_shutdown_status)?
+            .map(|task_ids| tonic::Response::new(proto::ResumeGroupResponse { task_ids }))
+            .ok_or_else(|| tonic::Status::not_found(group_id.to_string()))
+    }
+
+    async fn remove_group(
+        &self,
+        request: tonic::Request<proto::RemoveGroupRequest>,
+    ) -> Result<tonic::Response<proto::RemoveGroupResponse>, tonic::Status> {
+        let proto::RemoveGroupRequest { group_id } = request.into_inner();
+
+        self.task_scheduler
+            .remove_group(group_id)
+            .await
+            .map_err(service_shutdown_status)?
+            .map(|task_ids| tonic::Response::new(proto::RemoveGroupResponse { task_ids }))
+            .ok_or_else(|| tonic::Status::not_found(group_id.to_string()))
+    }
+
     async fn increase_concurrent_number(
         &self,
         request: tonic::Request<proto::IncreaseConcurrentNumberRequest>,
@@ -216,9 +470,91 @@ impl proto::Task for TaskService {
         self.task_scheduler.decrease_concurrent_number(task_id).map_err(service_shutdown_status)?;
         Ok(tonic::Response::new(proto::DecreaseConcurrentNumberResponse { ok: true }))
     }
+
+    async fn set_global_speed_limit(
+        &self,
+        request: tonic::Request<proto::SetGlobalSpeedLimitRequest>,
+    ) -> Result<tonic::Response<proto::SetGlobalSpeedLimitResponse>, tonic::Status> {
+        let proto::SetGlobalSpeedLimitRequest { limit } = request.into_inner();
+        self.task_scheduler.set_global_speed_limit(limit).map_err(service_shutdown_status)?;
+        Ok(tonic::Response::new(proto::SetGlobalSpeedLimitResponse { ok: true }))
+    }
+
+    async fn set_task_speed_limit(
+        &self,
+        request: tonic::Request<proto::SetTaskSpeedLimitRequest>,
+    ) -> Result<tonic::Response<proto::SetTaskSpeedLimitResponse>, tonic::Status> {
+        let proto::SetTaskSpeedLimitRequest { task_id, limit } = request.into_inner();
+        match self
+            .task_scheduler
+            .set_task_speed_limit(task_id, limit)
+            .await
+            .map_err(service_shutdown_status)?
+        {
+            Some(_) => Ok(tonic::Response::new(proto::SetTaskSpeedLimitResponse { ok: true })),
+            None => Err(tonic::Status::not_found(task_id.to_string())),
+        }
+    }
+
+    async fn set_priority(
+        &self,
+        request: tonic::Request<proto::SetPriorityRequest>,
+    ) -> Result<tonic::Response<proto::SetPriorityResponse>, tonic::Status> {
+        let proto::SetPriorityRequest { task_id, priority } = request.into_inner();
+        match self
+            .task_scheduler
+            .set_priority(task_id, model::Priority::from(priority))
+            .await
+            .map_err(service_shutdown_status)?
+        {
+            Some(_) => Ok(tonic::Response::new(proto::SetPriorityResponse { ok: true })),
+            None => Err(tonic::Status::not_found(task_id.to_string())),
+        }
+    }
+
+    async fn watch_events(
+        &self,
+        _request: tonic::Request<()>,
+    ) -> Result<tonic::Response<Self::WatchEventsStream>, tonic::Status> {
+        let stream =
+            BroadcastStream::new(self.task_scheduler.subscribe()).filter_map(|event| match event {
+                Ok(event) => Some(Ok(proto::TaskEvent::from(event))),
+                Err(BroadcastStreamRecvError::Lagged(n)) => {
+                    tracing::warn!("Subscriber of task events lagged behind, {n} event(s) skipped");
+                    None
+                }
+            });
+        let stream = futures::StreamExt::take_until(stream, self.shutdown_signal.clone());
+        Ok(tonic::Response::new(Box::pin(stream)))
+    }
 }
 
 #[allow(clippy::needless_pass_by_value)]
 fn service_shutdown_status<E>(_err: E) -> tonic::Status {
     tonic::Status::unavailable("Caracal is shutting down")
 }
+
+fn parse_task_hooks(hooks: Option<proto::TaskHooks>) -> Result<model::TaskHooks, tonic::Status> {
+    let proto::TaskHooks { commands, webhooks } = hooks.unwrap_or_default();
+    let webhooks = webhooks
+        .iter()
+        .map(|webhook| parse_uri(webhook))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?;
+    Ok(model::TaskHooks { commands, webhooks })
+}
+
+fn parse_remote_change_policy(policy: i32) -> model::RemoteChangePolicy {
+    model::RemoteChangePolicy::from(
+        proto::RemoteChangePolicy::try_from(policy).unwrap_or(proto::RemoteChangePolicy::Restart),
+    )
+}
+
+fn parse_not_before(
+    not_before: Option<&prost_types::Timestamp>,
+) -> Result<Option<OffsetDateTime>, tonic::Status> {
+    not_before
+        .map(proto::timestamp_to_datetime)
+        .transpose()
+        .map_err(|err| tonic::Status::invalid_argument(err.to_string()))
+}
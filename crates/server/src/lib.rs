@@ -1,13 +1,15 @@
 pub mod config;
+mod dbus;
 mod error;
 mod grpc;
 mod metrics;
+mod notification;
 mod web;
 
 use std::{future::Future, net::SocketAddr, path::PathBuf, pin::Pin};
 
 use caracal_engine::{DownloaderFactory, MINIMUM_CHUNK_SIZE, TaskScheduler};
-use futures::FutureExt;
+use futures::{FutureExt, future::Shared};
 use sigfinn::{ExitStatus, LifecycleManager, Shutdown};
 use snafu::ResultExt;
 use tokio::{
@@ -19,25 +21,32 @@ use tokio_stream::wrappers::UnixListenerStream;
 pub use self::{
     config::Config,
     error::{Error, Result},
+    notification::DesktopNotifier,
     web::ApiDoc,
 };
 use crate::metrics::Metrics;
 
+// streams of events are closed when the server is shutting down, otherwise the
+// graceful shutdown waits for them forever
+type ShutdownSignal = Shared<Shutdown>;
+
 /// # Errors
 ///
 /// This function will return an error if the server fails to start.
-#[allow(clippy::cognitive_complexity)]
+#[allow(clippy::cognitive_complexity, clippy::too_many_lines)]
 pub async fn serve_with_shutdown(
     Config {
         task_scheduler,
         ssh_servers,
         minio_aliases,
+        s3_profiles,
         grpc_listen_address,
         grpc_local_socket,
         grpc_access_token,
         metrics: metrics_config,
         web: web_config,
-        dbus: _,
+        dbus: dbus_config,
+        desktop_notification,
     }: Config,
 ) -> Result<()> {
     let lifecycle_manager = LifecycleManager::<Error>::new();
@@ -60,10 +69,45 @@ pub async fn serve_with_shutdown(
             .minimum_chunk_size(MINIMUM_CHUNK_SIZE)
             .ssh_servers(ssh_servers)
             .minio_aliases(minio_aliases)
+            .s3_profiles(s3_profiles)
+            .retry_interval(task_scheduler.chunk_retry_interval)
+            .speed_limit(task_scheduler.global_speed_limit)
+            .proxy(task_scheduler.http.proxy)
+            .torrent_seed_ratio(task_scheduler.torrent_seed_ratio)
+            .verify_on_resume(task_scheduler.verify_on_resume)
+            .connection_tuning(task_scheduler.connection_tuning)
             .build()
             .context(error::InitializeDownloaderSnafu)?;
 
-        TaskScheduler::new(downloader_factory, task_scheduler.concurrent_number)
+        if let Some(limit) = task_scheduler.global_speed_limit {
+            tracing::info!("Limiting transfer rate of all tasks to {limit} byte(s) per second");
+        }
+        if let Some(connection_tuning) = task_scheduler.connection_tuning {
+            tracing::info!(
+                "Tuning the number of connections of a task between {} and {}",
+                connection_tuning.min_connections,
+                connection_tuning.max_connections
+            );
+        }
+        if task_scheduler.torrent_seed_ratio > 0.0 {
+            tracing::info!(
+                "Seeding completed torrents until ratio {} is reached",
+                task_scheduler.torrent_seed_ratio
+            );
+        }
+        if let Some(ref path) = task_scheduler.task_store_file_path {
+            tracing::info!("Persisting tasks to {}", path.display());
+        }
+        TaskScheduler::builder(downloader_factory)
+            .max_concurrent_task_number(task_scheduler.concurrent_number)
+            .task_store_file_path(task_scheduler.task_store_file_path)
+            .retry_interval(task_scheduler.retry_interval)
+            .preemption(task_scheduler.preemption)
+            .schedule(task_scheduler.schedule)
+            .hooks(task_scheduler.hooks)
+            .hook_timeout(task_scheduler.hook_timeout)
+            .allow_task_commands(task_scheduler.allow_task_commands)
+            .build()
     };
     let _handle = lifecycle_manager.spawn(
         "Task Scheduler",
@@ -92,6 +136,20 @@ pub async fn serve_with_shutdown(
         );
     }
 
+    if dbus_config.enable {
+        let _handle = lifecycle_manager.spawn(
+            "D-Bus service",
+            create_dbus_service_future(dbus_config, task_scheduler.clone()),
+        );
+    }
+
+    if desktop_notification.enable {
+        let _handle = lifecycle_manager.spawn(
+            "Desktop notifier",
+            create_desktop_notifier_future(desktop_notification, task_scheduler.clone()),
+        );
+    }
+
     let _handle = lifecycle_manager
         .spawn("Web server", create_web_server_future(web_config.listen_address, task_scheduler));
 
@@ -119,6 +177,7 @@ fn create_grpc_local_socket_server_future(
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
+            let signal = signal.shared();
             tracing::info!("Listening Caracal gRPC endpoint on {}", local_socket.display());
             if let Some(local_socket_parent) = local_socket.parent()
                 && let Err(err) = tokio::fs::create_dir_all(&local_socket_parent)
@@ -142,7 +201,7 @@ fn create_grpc_local_socket_server_future(
                     interceptor.clone(),
                 ))
                 .add_service(caracal_proto::TaskServer::with_interceptor(
-                    grpc::TaskService::new(task_scheduler),
+                    grpc::TaskService::new(task_scheduler, signal.clone()),
                     interceptor,
                 ))
                 .serve_with_incoming_shutdown(uds_stream, signal)
@@ -173,6 +232,7 @@ fn create_grpc_http_server_future(
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
+            let signal = signal.shared();
             tracing::info!("Listening Caracal gRPC endpoint on {listen_address}");
 
             let interceptor = grpc::Interceptor::new(grpc_access_token);
@@ -182,7 +242,7 @@ fn create_grpc_http_server_future(
                     interceptor.clone(),
                 ))
                 .add_service(caracal_proto::TaskServer::with_interceptor(
-                    grpc::TaskService::new(task_scheduler),
+                    grpc::TaskService::new(task_scheduler, signal.clone()),
                     interceptor,
                 ))
                 .serve_with_shutdown(listen_address, signal)
@@ -223,12 +283,67 @@ fn create_task_scheduler_future(
     }
 }
 
+fn create_dbus_service_future(
+    config: config::DBusConfig,
+    task_scheduler: TaskScheduler,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |shutdown_signal| {
+        async move {
+            // the daemon keeps running without a session bus
+            let connection = match dbus::connect(&config, task_scheduler.clone()).await {
+                Ok(connection) => connection,
+                Err(err) => {
+                    tracing::warn!("{}", Error::from(err));
+                    return ExitStatus::Success;
+                }
+            };
+            tracing::info!("Serving D-Bus service `{}`", dbus::service_name(&config));
+            match dbus::forward_task_events(&connection, task_scheduler, shutdown_signal).await {
+                Ok(()) => {
+                    tracing::info!("Stopped D-Bus service gracefully");
+                    ExitStatus::Success
+                }
+                Err(err) => ExitStatus::Error(Error::from(err)),
+            }
+        }
+        .boxed()
+    }
+}
+
+fn create_desktop_notifier_future(
+    config: config::DesktopNotificationConfig,
+    task_scheduler: TaskScheduler,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |shutdown_signal| {
+        async move {
+            // notifications are optional, the daemon keeps running without a session bus
+            let notifier = match zbus::Connection::session().await {
+                Ok(connection) => DesktopNotifier::new(&connection, &config).await,
+                Err(err) => Err(err),
+            };
+            match notifier {
+                Ok(notifier) => {
+                    tracing::info!("Sending desktop notifications");
+                    notifier.serve(task_scheduler, shutdown_signal).await;
+                    tracing::info!("Stopped Desktop notifier gracefully");
+                }
+                Err(err) => {
+                    tracing::warn!("Desktop notifications are disabled, error: {err}");
+                }
+            }
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
 fn create_web_server_future(
     listen_address: SocketAddr,
     task_scheduler: TaskScheduler,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |shutdown_signal| {
         async move {
+            let shutdown_signal = shutdown_signal.shared();
             tracing::info!("Listening Web server on {listen_address}");
 
             let middleware_stack = tower::ServiceBuilder::new();
@@ -237,6 +352,7 @@ fn create_web_server_future(
                 .merge(web::controller::api_v1_router())
                 .merge(web::swagger::ui_router())
                 .layer(axum::Extension(task_scheduler))
+                .layer(axum::Extension(shutdown_signal.clone()))
                 .layer(middleware_stack)
                 .into_make_service_with_connect_info::<SocketAddr>();
 
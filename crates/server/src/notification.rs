@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use caracal_base::model;
+use caracal_engine::TaskScheduler;
+use tokio::sync::broadcast::error::RecvError;
+use zbus::zvariant::Value;
+
+use crate::config::DesktopNotificationConfig;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, &Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
+
+/// Sends freedesktop notifications when tasks are completed or failed
+pub struct DesktopNotifier {
+    proxy: NotificationsProxy<'static>,
+
+    icon: String,
+
+    timeout: i32,
+
+    long_plaintext_length: usize,
+}
+
+impl DesktopNotifier {
+    /// # Errors
+    ///
+    /// This function will return an error if the notification proxy can not
+    /// be created on the connection.
+    pub async fn new(
+        connection: &zbus::Connection,
+        config: &DesktopNotificationConfig,
+    ) -> zbus::Result<Self> {
+        let DesktopNotificationConfig { icon, timeout, long_plaintext_length, .. } = config;
+        Ok(Self {
+            proxy: NotificationsProxy::new(connection).await?,
+            icon: icon.to_string_lossy().to_string(),
+            timeout: i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX),
+            long_plaintext_length: *long_plaintext_length,
+        })
+    }
+
+    /// Forward the completed and failed tasks to the notification server
+    /// until the scheduler is closed or `shutdown` resolves
+    pub async fn serve<F>(self, task_scheduler: TaskScheduler, shutdown: F)
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let mut events = task_scheduler.subscribe();
+        let mut shutdown = std::pin::pin!(shutdown);
+        loop {
+            let event = tokio::select! {
+                () = &mut shutdown => break,
+                event = events.recv() => event,
+            };
+            let task_id = match event {
+                Ok(
+                    model::TaskEvent::Completed { task_id }
+                    | model::TaskEvent::Failed { task_id, .. },
+                ) => task_id,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("Desktop notifier lagged behind, {n} event(s) skipped");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            match task_scheduler.get_task_status(task_id).await {
+                Ok(Some(status)) => {
+                    if let Err(err) = self.notify(&status).await {
+                        tracing::warn!("Failed to send desktop notification, error: {err}");
+                    }
+                }
+                Ok(None) => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// This function will return an error if the notification server rejects
+    /// the notification.
+    pub async fn notify(&self, status: &model::TaskStatus) -> zbus::Result<u32> {
+        let file_name = status.file_path.file_name().map_or_else(
+            || status.file_path.to_string_lossy(),
+            |file_name| file_name.to_string_lossy(),
+        );
+        let (summary, body) = match status.state {
+            model::TaskState::Completed => ("Download completed", file_name.to_string()),
+            _ => (
+                "Download failed",
+                status
+                    .last_error
+                    .as_ref()
+                    .map_or_else(|| file_name.to_string(), |error| format!("{file_name}: {error}")),
+            ),
+        };
+        let body = shorten(&body, self.long_plaintext_length);
+        self.proxy
+            .notify(
+                caracal_base::PROJECT_NAME_WITH_INITIAL_CAPITAL,
+                0,
+                &self.icon,
+                summary,
+                &body,
+                &[],
+                HashMap::new(),
+                self.timeout,
+            )
+            .await
+    }
+}
+
+/// Truncate `text` to `max_length` characters, the length is unlimited if it
+/// is zero
+fn shorten(text: &str, max_length: usize) -> String {
+    if max_length == 0 || text.chars().count() <= max_length {
+        text.to_string()
+    } else {
+        let mut text = text.chars().take(max_length.saturating_sub(1)).collect::<String>();
+        text.push('…');
+        text
+    }
+}
+
+#[cfg(test)]
+// the unused arguments of the stand-in server are passed through by `zbus::interface`
+#[allow(clippy::used_underscore_binding)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use caracal_base::model;
+    use time::OffsetDateTime;
+    use tokio::sync::mpsc;
+
+    use super::{DesktopNotifier, shorten};
+    use crate::config::DesktopNotificationConfig;
+
+    struct NotificationServer {
+        sender: mpsc::UnboundedSender<(String, String, String, i32)>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl NotificationServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            _replaces_id: u32,
+            app_icon: &str,
+            summary: &str,
+            body: &str,
+            _actions: Vec<&str>,
+            _hints: std::collections::HashMap<&str, zbus::zvariant::Value<'_>>,
+            expire_timeout: i32,
+        ) -> u32 {
+            drop(self.sender.send((
+                app_icon.to_string(),
+                summary.to_string(),
+                body.to_string(),
+                expire_timeout,
+            )));
+            1
+        }
+    }
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("debian.iso", 0), "debian.iso");
+        assert_eq!(shorten("debian.iso", 10), "debian.iso");
+        assert_eq!(shorten("debian-12.iso", 10), "debian-12…");
+    }
+
+    #[tokio::test]
+    async fn test_notify() {
+        // a pair of connected peers stands in for the session bus
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let guid = zbus::Guid::generate();
+        let server = zbus::connection::Builder::unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at("/org/freedesktop/Notifications", NotificationServer { sender })
+            .unwrap()
+            .build();
+        let client = zbus::connection::Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = futures::future::try_join(server, client).await.unwrap();
+
+        let config = DesktopNotificationConfig {
+            enable: true,
+            icon: PathBuf::from("/usr/share/icons/caracal.png"),
+            timeout: Duration::from_secs(5),
+            long_plaintext_length: 24,
+        };
+        let notifier = DesktopNotifier::new(&client, &config).await.unwrap();
+        let mut status = model::TaskStatus {
+            id: 1,
+            file_path: PathBuf::from("/tmp/debian.iso"),
+            content_length: 1024,
+            chunks: Vec::new(),
+            concurrent_number: 0,
+            state: model::TaskState::Completed,
+            priority: model::Priority::Normal,
+            creation_timestamp: OffsetDateTime::now_utc(),
+            attempts: 1,
+            last_error: None,
+            connection_tuning_log: Vec::new(),
+            seeding: false,
+        };
+        assert_eq!(notifier.notify(&status).await.unwrap(), 1);
+        let (icon, summary, body, timeout) = receiver.recv().await.unwrap();
+        assert_eq!(icon, "/usr/share/icons/caracal.png");
+        assert_eq!(summary, "Download completed");
+        assert_eq!(body, "debian.iso");
+        assert_eq!(timeout, 5000);
+
+        status.state = model::TaskState::Failed;
+        status.last_error = Some("Connection timed out".to_string());
+        let _unused = notifier.notify(&status).await.unwrap();
+        let (_, summary, body, _) = receiver.recv().await.unwrap();
+        assert_eq!(summary, "Download failed");
+        assert_eq!(body, "debian.iso: Connection …");
+
+        drop(server);
+    }
+}
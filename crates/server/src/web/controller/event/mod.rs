@@ -0,0 +1,5 @@
+pub mod v1;
+
+use axum::{Router, routing};
+
+pub fn v1() -> Router { Router::new().route("/v1/events", routing::get(v1::watch)) }
@@ -0,0 +1,50 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::Extension,
+    response::sse::{self, KeepAlive, Sse},
+};
+use caracal_base::model;
+use caracal_engine::TaskScheduler;
+use futures::Stream;
+use tokio_stream::{
+    StreamExt,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+
+use crate::ShutdownSignal;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    responses(
+        (
+            status = 200,
+            description = "Stream of task lifecycle and progress events, each event carries a \
+                           JSON encoded task event",
+            body = model::TaskEvent,
+            content_type = "text/event-stream"
+        ),
+    ),
+    tag = "Event"
+)]
+pub async fn watch(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Extension(shutdown_signal): Extension<ShutdownSignal>,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let stream = BroadcastStream::new(task_scheduler.subscribe()).filter_map(|event| match event {
+        Ok(event) => match sse::Event::default().json_data(&event) {
+            Ok(event) => Some(Ok(event)),
+            Err(err) => {
+                tracing::warn!("Failed to encode task event, error: {err}");
+                None
+            }
+        },
+        Err(BroadcastStreamRecvError::Lagged(n)) => {
+            tracing::warn!("Subscriber of task events lagged behind, {n} event(s) skipped");
+            None
+        }
+    });
+    let stream = futures::StreamExt::take_until(stream, shutdown_signal);
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,16 @@
+pub mod v1;
+
+use axum::{Router, routing};
+
+pub fn v1() -> Router {
+    Router::new().nest(
+        "/v1/group",
+        Router::new()
+            .route("/", routing::post(v1::create))
+            .route("/", routing::get(v1::list))
+            .route("/pause/{group_id}", routing::post(v1::pause))
+            .route("/resume/{group_id}", routing::post(v1::resume))
+            .route("/remove/{group_id}", routing::delete(v1::remove))
+            .route("/{group_id}", routing::get(v1::get)),
+    )
+}
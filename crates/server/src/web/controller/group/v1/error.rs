@@ -0,0 +1,122 @@
+use axum::{
+    body,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum CreateGroupError {
+    Internal,
+}
+
+impl IntoResponse for CreateGroupError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum GetAllGroupStatusesError {
+    Internal,
+}
+
+impl IntoResponse for GetAllGroupStatusesError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum GetGroupError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for GetGroupError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum RemoveGroupError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for RemoveGroupError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum PauseGroupError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for PauseGroupError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum ResumeGroupError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for ResumeGroupError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
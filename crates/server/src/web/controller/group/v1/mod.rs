@@ -0,0 +1,182 @@
+mod error;
+
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+};
+use caracal_base::model;
+use caracal_engine::TaskScheduler;
+
+use self::error::{
+    CreateGroupError, GetAllGroupStatusesError, GetGroupError, PauseGroupError, RemoveGroupError,
+    ResumeGroupError,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/group",
+    request_body = model::CreateTaskGroup,
+    responses(
+        (status = 201, description = "Group created successfully", body = u64, example = 1),
+        (status = 400, description = "Bad request", body = CreateGroupError),
+        (status = 500, description = "Internal server error", body = CreateGroupError)
+    ),
+    tag = "Group"
+)]
+pub async fn create(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Json(model::CreateTaskGroup { name, tasks }): Json<model::CreateTaskGroup>,
+) -> Result<(StatusCode, Json<u64>), CreateGroupError> {
+    match task_scheduler.add_group(name, tasks, true).await {
+        Ok((group_id, _)) => Ok((StatusCode::CREATED, Json(group_id))),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(CreateGroupError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/group/{group_id}",
+    params(
+        ("group_id", Path, description = "ID of the group to retrieve")
+    ),
+    responses(
+        (status = 200, description = "Group found successfully", body = model::TaskGroupStatus),
+        (status = 404, description = "Group not found", body = GetGroupError),
+        (status = 500, description = "Internal server error", body = GetGroupError)
+    ),
+    tag = "Group"
+)]
+pub async fn get(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(group_id): Path<u64>,
+) -> Result<(StatusCode, Json<model::TaskGroupStatus>), GetGroupError> {
+    match task_scheduler.get_group_status(group_id).await {
+        Ok(Some(group_status)) => Ok((StatusCode::OK, Json(group_status))),
+        Ok(None) => Err(GetGroupError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(GetGroupError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/group",
+    responses(
+        (
+            status = 200,
+            description = "List of groups retrieved successfully",
+            body = Vec<model::TaskGroupStatus>
+        ),
+        (status = 500, description = "Internal server error", body = GetAllGroupStatusesError)
+    ),
+    tag = "Group"
+)]
+pub async fn list(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+) -> Result<(StatusCode, Json<Vec<model::TaskGroupStatus>>), GetAllGroupStatusesError> {
+    match task_scheduler.get_all_group_statuses().await {
+        Ok(groups) => Ok((StatusCode::OK, Json(groups))),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(GetAllGroupStatusesError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/group/remove/{group_id}",
+    params(
+        ("group_id", Path, description = "ID of the group to remove")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Group removed successfully, IDs of the canceled tasks are returned",
+            body = Vec<u64>
+        ),
+        (status = 404, description = "Group not found", body = RemoveGroupError),
+        (status = 500, description = "Internal server error", body = RemoveGroupError)
+    ),
+    tag = "Group"
+)]
+pub async fn remove(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(group_id): Path<u64>,
+) -> Result<(StatusCode, Json<Vec<u64>>), RemoveGroupError> {
+    match task_scheduler.remove_group(group_id).await {
+        Ok(Some(task_ids)) => Ok((StatusCode::OK, Json(task_ids))),
+        Ok(None) => Err(RemoveGroupError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(RemoveGroupError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/group/pause/{group_id}",
+    params(
+        ("group_id", Path, description = "ID of the group to pause")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Group paused successfully, IDs of the paused tasks are returned",
+            body = Vec<u64>
+        ),
+        (status = 404, description = "Group not found", body = PauseGroupError),
+        (status = 500, description = "Internal server error", body = PauseGroupError)
+    ),
+    tag = "Group"
+)]
+pub async fn pause(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(group_id): Path<u64>,
+) -> Result<(StatusCode, Json<Vec<u64>>), PauseGroupError> {
+    match task_scheduler.pause_group(group_id).await {
+        Ok(Some(task_ids)) => Ok((StatusCode::OK, Json(task_ids))),
+        Ok(None) => Err(PauseGroupError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(PauseGroupError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/group/resume/{group_id}",
+    params(
+        ("group_id", Path, description = "ID of the group to resume")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Group resumed successfully, IDs of the resumed tasks are returned",
+            body = Vec<u64>
+        ),
+        (status = 404, description = "Group not found", body = ResumeGroupError),
+        (status = 500, description = "Internal server error", body = ResumeGroupError)
+    ),
+    tag = "Group"
+)]
+pub async fn resume(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(group_id): Path<u64>,
+) -> Result<(StatusCode, Json<Vec<u64>>), ResumeGroupError> {
+    match task_scheduler.resume_group(group_id).await {
+        Ok(Some(task_ids)) => Ok((StatusCode::OK, Json(task_ids))),
+        Ok(None) => Err(ResumeGroupError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(ResumeGroupError::Internal)
+        }
+    }
+}
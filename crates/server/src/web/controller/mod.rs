@@ -1,8 +1,13 @@
+pub mod event;
+pub mod group;
 pub mod system;
 pub mod task;
 
 use axum::Router;
 
 pub fn api_v1_router() -> Router {
-    Router::new().nest("/api", Router::new().merge(task::v1()).merge(system::v1()))
+    Router::new().nest(
+        "/api",
+        Router::new().merge(task::v1()).merge(group::v1()).merge(event::v1()).merge(system::v1()),
+    )
 }
@@ -13,6 +13,9 @@ pub fn v1() -> Router {
             .route("/resume/{task_id}", routing::post(v1::resume))
             .route("/resume/", routing::post(v1::resume_all))
             .route("/remove/{task_id}", routing::delete(v1::remove))
+            .route("/speed-limit/{task_id}", routing::put(v1::set_task_speed_limit))
+            .route("/speed-limit/", routing::put(v1::set_global_speed_limit))
+            .route("/priority/{task_id}", routing::put(v1::set_priority))
             .route("/{task_id}", routing::get(v1::get)),
     )
 }
@@ -156,3 +156,61 @@ impl IntoResponse for PauseAllTasksError {
             .expect("response should always build successfully")
     }
 }
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum SetGlobalSpeedLimitError {
+    Internal,
+}
+
+impl IntoResponse for SetGlobalSpeedLimitError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum SetTaskSpeedLimitError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for SetTaskSpeedLimitError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
+
+#[derive(Clone, Debug, ToSchema)]
+pub enum SetPriorityError {
+    NotFound,
+    Internal,
+}
+
+impl IntoResponse for SetPriorityError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotFound => (StatusCode::NOT_FOUND, body::Body::from(())),
+            Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, body::Body::from(())),
+        };
+
+        Response::builder()
+            .status(status)
+            .body(body)
+            .expect("response should always build successfully")
+    }
+}
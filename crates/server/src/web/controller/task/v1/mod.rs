@@ -9,7 +9,8 @@ use caracal_engine::TaskScheduler;
 
 use self::error::{
     CreateTaskError, GetAllTaskStatusesError, GetTaskError, PauseAllTasksError, PauseTaskError,
-    RemoveTaskError, ResumeAllTasksError, ResumeTaskStatusesError,
+    RemoveTaskError, ResumeAllTasksError, ResumeTaskStatusesError, SetGlobalSpeedLimitError,
+    SetPriorityError, SetTaskSpeedLimitError,
 };
 
 #[utoipa::path(
@@ -203,3 +204,84 @@ pub async fn resume_all(
         }
     }
 }
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/task/speed-limit/",
+    request_body = model::SpeedLimit,
+    responses(
+        (status = 200, description = "Speed limit of all tasks set successfully"),
+        (status = 500, description = "Internal server error", body = SetGlobalSpeedLimitError)
+    ),
+    tag = "Task"
+)]
+pub async fn set_global_speed_limit(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Json(model::SpeedLimit { limit }): Json<model::SpeedLimit>,
+) -> Result<StatusCode, SetGlobalSpeedLimitError> {
+    match task_scheduler.set_global_speed_limit(limit) {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(SetGlobalSpeedLimitError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/task/speed-limit/{task_id}",
+    params(
+        ("task_id", Path, description = "ID of the task to limit")
+    ),
+    request_body = model::SpeedLimit,
+    responses(
+        (status = 200, description = "Speed limit of task set successfully", body = u64),
+        (status = 404, description = "Task not found", body = SetTaskSpeedLimitError),
+        (status = 500, description = "Internal server error", body = SetTaskSpeedLimitError)
+    ),
+    tag = "Task"
+)]
+pub async fn set_task_speed_limit(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(task_id): Path<u64>,
+    Json(model::SpeedLimit { limit }): Json<model::SpeedLimit>,
+) -> Result<(StatusCode, Json<u64>), SetTaskSpeedLimitError> {
+    match task_scheduler.set_task_speed_limit(task_id, limit).await {
+        Ok(Some(task_id)) => Ok((StatusCode::OK, Json(task_id))),
+        Ok(None) => Err(SetTaskSpeedLimitError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(SetTaskSpeedLimitError::Internal)
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/task/priority/{task_id}",
+    params(
+        ("task_id", Path, description = "ID of the task to reprioritize")
+    ),
+    request_body = model::TaskPriority,
+    responses(
+        (status = 200, description = "Priority of task set successfully", body = u64),
+        (status = 404, description = "Task not found", body = SetPriorityError),
+        (status = 500, description = "Internal server error", body = SetPriorityError)
+    ),
+    tag = "Task"
+)]
+pub async fn set_priority(
+    Extension(task_scheduler): Extension<TaskScheduler>,
+    Path(task_id): Path<u64>,
+    Json(model::TaskPriority { priority }): Json<model::TaskPriority>,
+) -> Result<(StatusCode, Json<u64>), SetPriorityError> {
+    match task_scheduler.set_priority(task_id, priority).await {
+        Ok(Some(task_id)) => Ok((StatusCode::OK, Json(task_id))),
+        Ok(None) => Err(SetPriorityError::NotFound),
+        Err(source) => {
+            tracing::error!("{source}");
+            Err(SetPriorityError::Internal)
+        }
+    }
+}
@@ -16,18 +16,39 @@ use crate::web::controller;
         controller::task::v1::pause_all,
         controller::task::v1::resume,
         controller::task::v1::resume_all,
+        controller::task::v1::set_global_speed_limit,
+        controller::task::v1::set_task_speed_limit,
+        controller::task::v1::set_priority,
+        controller::group::v1::list,
+        controller::group::v1::create,
+        controller::group::v1::get,
+        controller::group::v1::remove,
+        controller::group::v1::pause,
+        controller::group::v1::resume,
+        controller::event::v1::watch,
         controller::system::v1::get_version,
     ),
     components(
         schemas(
+            model::ConnectionTuningAction,
+            model::ConnectionTuningDecision,
             model::CreateTask,
+            model::CreateTaskGroup,
             model::ProgressChunk,
+            model::RemoteChangePolicy,
+            model::SpeedLimit,
+            model::TaskEvent,
+            model::TaskGroupStatus,
+            model::TaskHooks,
+            model::TaskPriority,
             model::TaskState,
             model::TaskStatus,
         )
     ),
     tags(
         (name = "Task", description = "Task management endpoints."),
+        (name = "Group", description = "Task group management endpoints."),
+        (name = "Event", description = "Live feed of task events."),
         (name = "System", description = "System information.")
     ),
 )]